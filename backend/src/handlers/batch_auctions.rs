@@ -0,0 +1,51 @@
+use actix_web::{web, HttpResponse, Responder, post};
+use sea_orm::{DatabaseConnection, ActiveValue};
+use serde::{Serialize, Deserialize};
+use validator::Validate;
+
+use crate::handlers::pauses::update_market_halt;
+use crate::schema::PauseAction;
+
+#[derive(Serialize, Deserialize, Validate)]
+struct BatchAuctionRequest {
+    #[validate(length(min = 1))]
+    pauser_address: String,
+    #[validate(range(min = 1))]
+    interval: i64,
+    #[validate(range(min = 1))]
+    ends_at: Option<i64>,
+    #[serde(default)]
+    reason: String,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct EndBatchAuctionRequest {
+    #[validate(length(min = 1))]
+    pauser_address: String,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Switches a market to frequent batch auctions, e.g. ahead of a news event.
+#[post("/markets/{market_id}/batch-auction")]
+pub async fn start_batch_auction(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<BatchAuctionRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let (interval, ends_at) = (req.interval, req.ends_at);
+    update_market_halt(&conn, market_id.into_inner(), &req.pauser_address, |market| {
+        market.batch_auction_interval = ActiveValue::Set(Some(interval));
+        market.batch_auction_ends_at = ActiveValue::Set(ends_at);
+    }, PauseAction::BatchAuctionStarted, req.reason.clone(), None).await
+}
+
+#[post("/markets/{market_id}/batch-auction/end")]
+pub async fn end_batch_auction(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<EndBatchAuctionRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    update_market_halt(&conn, market_id.into_inner(), &req.pauser_address, |market| {
+        market.batch_auction_interval = ActiveValue::Set(None);
+        market.batch_auction_ends_at = ActiveValue::Set(None);
+    }, PauseAction::BatchAuctionEnded, req.reason.clone(), None).await
+}
@@ -0,0 +1,243 @@
+use actix_web::{web, HttpResponse, Responder, get, post};
+use sea_orm::{DatabaseConnection, DbErr, entity::*, QueryOrder, ActiveValue};
+use serde::{Serialize, Deserialize};
+use validator::Validate;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use crate::schema::{liquidity_reward_programs, liquidity_rewards, markets, RewardFormula};
+
+#[derive(Serialize, Deserialize)]
+struct RewardProgramResponse {
+    market_id: String,
+    budget: String,
+    per_sample: String,
+    sample_interval: i64,
+    max_spread: String,
+    min_size: String,
+    formula: String,
+    last_sampled_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LiquidityRewardResponse {
+    user_address: String,
+    last_score: String,
+    total_score: String,
+    accrued: String,
+    claimed: String,
+    updated_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MarketRewardsResponse {
+    program: Option<RewardProgramResponse>,
+    rewards: Vec<LiquidityRewardResponse>, // Highest total score first
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct ConfigureRewardsRequest {
+    #[validate(length(min = 1))]
+    creator_address: String,
+    /// Added to whatever is left of the current budget.
+    budget: String,
+    per_sample: String,
+    #[validate(range(min = 1))]
+    sample_interval: i64,
+    max_spread: String,
+    #[serde(default)]
+    min_size: Option<String>,
+    /// "linear" or "quadratic" (the default).
+    #[serde(default)]
+    formula: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct ClaimRewardsRequest {
+    #[validate(length(min = 1))]
+    user_address: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClaimRewardsResponse {
+    claimed: String,
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn parse_decimal(field: &str, value: &str) -> Result<Decimal, String> {
+    Decimal::from_str(value)
+        .ok()
+        .filter(|value| *value >= Decimal::ZERO)
+        .ok_or_else(|| format!("Invalid {}: {}", field, value))
+}
+
+/// The market's liquidity reward program and every address's scores and accruals.
+#[get("/markets/{market_id}/rewards")]
+pub async fn get_market_rewards(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>) -> impl Responder {
+    let market_id = market_id.into_inner();
+    let program = match liquidity_reward_programs::Entity::find_by_id(market_id.clone()).one(&**conn).await {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("Error fetching reward program: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let result = liquidity_rewards::Entity::find()
+        .filter(liquidity_rewards::Column::MarketId.eq(market_id))
+        .order_by_desc(liquidity_rewards::Column::TotalScore)
+        .all(&**conn)
+        .await;
+
+    match result {
+        Ok(rewards) => HttpResponse::Ok().json(MarketRewardsResponse {
+            program: program.map(|program| RewardProgramResponse {
+                market_id: program.market_id,
+                budget: program.budget.to_string(),
+                per_sample: program.per_sample.to_string(),
+                sample_interval: program.sample_interval,
+                max_spread: program.max_spread.to_string(),
+                min_size: program.min_size.to_string(),
+                formula: program.formula.to_string(),
+                last_sampled_at: program.last_sampled_at,
+            }),
+            rewards: rewards.into_iter().map(|reward| LiquidityRewardResponse {
+                user_address: reward.user_address,
+                last_score: reward.last_score.to_string(),
+                total_score: reward.total_score.to_string(),
+                accrued: reward.accrued.to_string(),
+                claimed: reward.claimed.to_string(),
+                updated_at: reward.updated_at.to_string(),
+            }).collect(),
+        }),
+        Err(e) => {
+            eprintln!("Error fetching liquidity rewards: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Starts or reconfigures the market's liquidity reward program and tops up its budget.
+/// Creator only.
+#[post("/markets/{market_id}/rewards")]
+pub async fn configure_rewards(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<ConfigureRewardsRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let market_id = market_id.into_inner();
+    let parsed = (|| {
+        let budget = parse_decimal("budget", &req.budget)?;
+        let per_sample = parse_decimal("per_sample", &req.per_sample)?;
+        let max_spread = parse_decimal("max_spread", &req.max_spread)?;
+        let min_size = parse_decimal("min_size", req.min_size.as_deref().unwrap_or("0"))?;
+        if per_sample.is_zero() || max_spread.is_zero() || max_spread >= Decimal::from(50) {
+            return Err("per_sample must be positive and max_spread between 0 and 50".to_string());
+        }
+        let formula = match req.formula.as_deref().map(str::to_lowercase).as_deref() {
+            Some("linear") => RewardFormula::Linear,
+            None | Some("quadratic") => RewardFormula::Quadratic,
+            Some(other) => return Err(format!("Unknown formula: {}", other)),
+        };
+        Ok((budget, per_sample, max_spread, min_size, formula))
+    })();
+    let (budget, per_sample, max_spread, min_size, formula) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => return HttpResponse::BadRequest().json(format!("Validation error: {}", e)),
+    };
+
+    match markets::Entity::find_by_id(market_id.clone()).one(&**conn).await {
+        Ok(Some(market)) if market.creator_address.eq_ignore_ascii_case(&req.creator_address) => {}
+        Ok(Some(_)) => return HttpResponse::Forbidden().json("Only the market creator can configure rewards"),
+        Ok(None) => return HttpResponse::NotFound().json(format!("Market not found: {}", market_id)),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let result: Result<(), DbErr> = async {
+        match liquidity_reward_programs::Entity::find_by_id(market_id.clone()).one(&**conn).await? {
+            Some(program) => {
+                let remaining = program.budget;
+                let mut program: liquidity_reward_programs::ActiveModel = program.into();
+                program.budget = ActiveValue::Set(remaining + budget);
+                program.per_sample = ActiveValue::Set(per_sample);
+                program.sample_interval = ActiveValue::Set(req.sample_interval);
+                program.max_spread = ActiveValue::Set(max_spread);
+                program.min_size = ActiveValue::Set(min_size);
+                program.formula = ActiveValue::Set(formula);
+                program.updated_at = ActiveValue::Set(now());
+                program.update(&**conn).await?;
+            }
+            None => {
+                let program = liquidity_reward_programs::ActiveModel {
+                    market_id: ActiveValue::Set(market_id.clone()),
+                    budget: ActiveValue::Set(budget),
+                    per_sample: ActiveValue::Set(per_sample),
+                    sample_interval: ActiveValue::Set(req.sample_interval),
+                    max_spread: ActiveValue::Set(max_spread),
+                    min_size: ActiveValue::Set(min_size),
+                    formula: ActiveValue::Set(formula),
+                    last_sampled_at: ActiveValue::Set(None),
+                    created_at: ActiveValue::Set(now()),
+                    updated_at: ActiveValue::Set(now()),
+                };
+                liquidity_reward_programs::Entity::insert(program).exec(&**conn).await?;
+            }
+        }
+        Ok(())
+    }.await;
+
+    match result {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => {
+            eprintln!("Error configuring liquidity rewards: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Marks the caller's accrued liquidity rewards in a market as claimed and returns the amount.
+#[post("/markets/{market_id}/rewards/claim")]
+pub async fn claim_rewards(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<ClaimRewardsRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let market_id = market_id.into_inner();
+    if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &market_id, false).await {
+        return response;
+    }
+
+    let reward = liquidity_rewards::Entity::find()
+        .filter(liquidity_rewards::Column::MarketId.eq(market_id))
+        .filter(liquidity_rewards::Column::UserAddress.eq(req.user_address.to_lowercase()))
+        .one(&**conn)
+        .await;
+    match reward {
+        Ok(Some(reward)) => {
+            let amount = reward.accrued;
+            let claimed = reward.claimed + amount;
+            let mut reward: liquidity_rewards::ActiveModel = reward.into();
+            reward.accrued = ActiveValue::Set(Decimal::ZERO);
+            reward.claimed = ActiveValue::Set(claimed);
+            reward.updated_at = ActiveValue::Set(now());
+            match reward.update(&**conn).await {
+                Ok(_) => HttpResponse::Ok().json(ClaimRewardsResponse { claimed: amount.to_string() }),
+                Err(e) => {
+                    eprintln!("Error claiming liquidity rewards: {}", e);
+                    HttpResponse::InternalServerError().finish()
+                }
+            }
+        }
+        Ok(None) => HttpResponse::Ok().json(ClaimRewardsResponse { claimed: Decimal::ZERO.to_string() }),
+        Err(e) => {
+            eprintln!("Error fetching liquidity rewards: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use actix_web::{web, Responder, get, post};
+use sea_orm::{DatabaseConnection, entity::*, ActiveValue};
+use serde::{Serialize, Deserialize};
+use rust_decimal::Decimal;
+use validator::Validate;
+use std::str::FromStr;
+
+use crate::schema::{market_groups, markets, user_positions, MarketStatus};
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+#[derive(Serialize, Deserialize)]
+struct MarketGroupResponse {
+    id: String,
+    title: String,
+    creator_address: String,
+    winning_market_id: Option<String>,
+    converted_amount: String,
+    market_ids: Vec<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct CreateMarketGroupRequest {
+    /// Deterministic id derived by the market factory from creator, title and salt.
+    #[validate(length(min = 1))]
+    id: String,
+    #[validate(length(min = 1))]
+    title: String,
+    #[validate(length(min = 1))]
+    creator_address: String,
+    #[validate(length(min = 2))]
+    market_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct ResolveMarketGroupRequest {
+    #[validate(length(min = 1))]
+    oracle_address: String,
+    #[validate(length(min = 1))]
+    winning_market_id: String,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct NegativeRiskConversionRequest {
+    #[validate(length(min = 1))]
+    user_address: String,
+    #[validate(length(min = 1))]
+    target_market_id: String,
+    #[validate(length(min = 1))]
+    amount: String,
+}
+
+async fn group_market_ids(conn: &DatabaseConnection, group_id: &str) -> Result<Vec<String>, sea_orm::DbErr> {
+    let members = markets::Entity::find()
+        .filter(markets::Column::GroupId.eq(group_id.to_string()))
+        .all(conn)
+        .await?;
+    Ok(members.into_iter().map(|market| market.id).collect())
+}
+
+fn group_response(group: market_groups::Model, market_ids: Vec<String>) -> MarketGroupResponse {
+    MarketGroupResponse {
+        id: group.id,
+        title: group.title,
+        creator_address: group.creator_address,
+        winning_market_id: group.winning_market_id,
+        converted_amount: group.converted_amount.to_string(),
+        market_ids,
+        created_at: group.created_at.to_string(),
+        updated_at: group.updated_at.to_string(),
+    }
+}
+
+#[get("/groups")]
+pub async fn get_all_groups(conn: web::Data<DatabaseConnection>) -> impl Responder {
+    let result = market_groups::Entity::find().all(&**conn).await;
+
+    match result {
+        Ok(groups) => {
+            let mut group_responses = Vec::with_capacity(groups.len());
+            for group in groups {
+                match group_market_ids(&conn, &group.id).await {
+                    Ok(market_ids) => group_responses.push(group_response(group, market_ids)),
+                    Err(e) => {
+                        eprintln!("Error fetching group markets: {}", e);
+                        return web::HttpResponse::InternalServerError().finish();
+                    }
+                }
+            }
+            web::Json(group_responses)
+        }
+        Err(e) => {
+            eprintln!("Error fetching groups: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[get("/groups/{group_id}")]
+pub async fn get_group_by_id(conn: web::Data<DatabaseConnection>, group_id: web::Path<String>) -> impl Responder {
+    let group_id = group_id.into_inner();
+    let result = market_groups::Entity::find_by_id(group_id.clone()).one(&**conn).await;
+
+    match result {
+        Ok(Some(group)) => {
+            match group_market_ids(&conn, &group_id).await {
+                Ok(market_ids) => web::Json(group_response(group, market_ids)),
+                Err(e) => {
+                    eprintln!("Error fetching group markets: {}", e);
+                    web::HttpResponse::InternalServerError().finish()
+                }
+            }
+        }
+        Ok(None) => {
+            web::HttpResponse::NotFound().finish()
+        }
+        Err(e) => {
+            eprintln!("Error fetching group: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/groups")]
+pub async fn create_group(conn: web::Data<DatabaseConnection>, req: web::Json<CreateMarketGroupRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+
+    let mut members = Vec::with_capacity(req.market_ids.len());
+    for market_id in &req.market_ids {
+        match markets::Entity::find_by_id(market_id.clone()).one(&**conn).await {
+            Ok(Some(market)) => {
+                if market.group_id.is_some() {
+                    return web::HttpResponse::Conflict().json(format!("Market {} already belongs to a group", market_id));
+                }
+                if market.status != crate::schema::MarketStatus::Active {
+                    return web::HttpResponse::Conflict().json(format!("Market {} is not active", market_id));
+                }
+                members.push(market);
+            }
+            Ok(None) => {
+                return web::HttpResponse::NotFound().json(format!("Market not found: {}", market_id));
+            }
+            Err(e) => {
+                eprintln!("Error fetching market: {}", e);
+                return web::HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+
+    let new_group = market_groups::ActiveModel {
+        id: ActiveValue::Set(req.id.clone()),
+        title: ActiveValue::Set(req.title.clone()),
+        creator_address: ActiveValue::Set(req.creator_address.clone()),
+        winning_market_id: ActiveValue::Set(None),
+        converted_amount: ActiveValue::Set(Decimal::from(0)),
+        created_at: ActiveValue::Set(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64),
+        updated_at: ActiveValue::Set(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64),
+    };
+    if let Err(e) = market_groups::Entity::insert(new_group).exec(&**conn).await {
+        eprintln!("Error creating group: {}", e);
+        return web::HttpResponse::InternalServerError().finish();
+    }
+
+    for market in members {
+        let mut market: markets::ActiveModel = market.into();
+        market.group_id = ActiveValue::Set(Some(req.id.clone()));
+        if let Err(e) = market.update(&**conn).await {
+            eprintln!("Error adding market to group: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let group = market_groups::Entity::find_by_id(req.id.clone()).one(&**conn).await.unwrap().unwrap();
+    web::Json(group_response(group, req.market_ids.clone()))
+}
+
+/// Resolves the winning market YES and every other member NO. The caller must be the oracle
+/// of every member, and each member must be resolvable on its own: active or expired, not
+/// paused, and past its resolution time unless its proposed early resolution agrees.
+#[post("/groups/{group_id}/resolve")]
+pub async fn resolve_group(conn: web::Data<DatabaseConnection>, group_id: web::Path<String>, req: web::Json<ResolveMarketGroupRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let group_id = group_id.into_inner();
+    let group = match market_groups::Entity::find_by_id(group_id.clone()).one(&**conn).await {
+        Ok(Some(group)) => group,
+        Ok(None) => return web::HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching group: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if group.winning_market_id.is_some() {
+        return web::HttpResponse::Conflict().json("Group already resolved");
+    }
+
+    let members = match markets::Entity::find()
+        .filter(markets::Column::GroupId.eq(group_id.clone()))
+        .all(&**conn)
+        .await
+    {
+        Ok(members) => members,
+        Err(e) => {
+            eprintln!("Error fetching group markets: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if !members.iter().any(|market| market.id == req.winning_market_id) {
+        return web::HttpResponse::BadRequest().json(format!("Market {} is not in this group", req.winning_market_id));
+    }
+    for market in &members {
+        if !market.oracle_id.eq_ignore_ascii_case(&req.oracle_address) {
+            return web::HttpResponse::Forbidden().json("Only the oracle of every market in the group can resolve it");
+        }
+        match market.status {
+            MarketStatus::Active | MarketStatus::Expired => {}
+            MarketStatus::Resolved => {
+                return web::HttpResponse::Conflict().json(format!("Market {} is already resolved", market.id));
+            }
+            MarketStatus::Locked | MarketStatus::Voided => {
+                return web::HttpResponse::BadRequest().json(format!("Market {} can't be resolved", market.id));
+            }
+        }
+        let outcome = market.id == req.winning_market_id;
+        match market.proposed_outcome {
+            Some(proposed) if proposed != outcome => {
+                return web::HttpResponse::BadRequest().json(format!("Outcome does not match the proposed resolution of market {}", market.id));
+            }
+            Some(_) => {}
+            None => {
+                let resolution_timestamp = market.resolution_timestamp.unwrap_or(market.expiry_timestamp);
+                if now() < resolution_timestamp {
+                    return web::HttpResponse::BadRequest().json(format!("Market {} cannot be resolved before {}", market.id, resolution_timestamp));
+                }
+            }
+        }
+        if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &market.id, false).await {
+            return response;
+        }
+    }
+
+    let market_ids: Vec<String> = members.iter().map(|market| market.id.clone()).collect();
+    for market in members {
+        let outcome = market.id == req.winning_market_id;
+        let market_id = market.id.clone();
+        let mut market: markets::ActiveModel = market.into();
+        market.status = ActiveValue::Set(crate::schema::MarketStatus::Resolved);
+        market.resolved_outcome = ActiveValue::Set(Some(outcome));
+        if let Err(e) = market.update(&**conn).await {
+            eprintln!("Error resolving group market: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+        if let Err(e) = crate::handlers::markets::apply_parent_resolution(&conn, &market_id, Some(outcome)).await {
+            eprintln!("Error updating conditional markets: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let mut group: market_groups::ActiveModel = group.into();
+    group.winning_market_id = ActiveValue::Set(Some(req.winning_market_id.clone()));
+    group.updated_at = ActiveValue::Set(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64);
+
+    match group.update(&**conn).await {
+        Ok(group) => web::Json(group_response(group, market_ids)),
+        Err(e) => {
+            eprintln!("Error updating group: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Records a negative risk conversion: `amount` NO in every member except the target
+/// becomes `amount` YES in the target plus `(n - 2) * amount` collateral.
+#[post("/groups/{group_id}/convert")]
+pub async fn convert_negative_risk(conn: web::Data<DatabaseConnection>, group_id: web::Path<String>, req: web::Json<NegativeRiskConversionRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let amount = match Decimal::from_str(&req.amount) {
+        Ok(amount) if amount > Decimal::from(0) => amount,
+        _ => return web::HttpResponse::BadRequest().json("amount must be a positive number"),
+    };
+    let group_id = group_id.into_inner();
+    let group = match market_groups::Entity::find_by_id(group_id.clone()).one(&**conn).await {
+        Ok(Some(group)) => group,
+        Ok(None) => return web::HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching group: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if group.winning_market_id.is_some() {
+        return web::HttpResponse::Conflict().json("Group already resolved");
+    }
+
+    let market_ids = match group_market_ids(&conn, &group_id).await {
+        Ok(market_ids) => market_ids,
+        Err(e) => {
+            eprintln!("Error fetching group markets: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if !market_ids.contains(&req.target_market_id) {
+        return web::HttpResponse::BadRequest().json(format!("Market {} is not in this group", req.target_market_id));
+    }
+
+    let positions = match user_positions::Entity::find()
+        .filter(user_positions::Column::UserAddress.eq(req.user_address.clone()))
+        .filter(user_positions::Column::MarketId.is_in(market_ids.clone()))
+        .all(&**conn)
+        .await
+    {
+        Ok(positions) => positions,
+        Err(e) => {
+            eprintln!("Error fetching user positions: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let Some(target) = positions.iter().find(|position| position.market_id == req.target_market_id).cloned() else {
+        return web::HttpResponse::NotFound().json(format!("User position not found for market: {}", req.target_market_id));
+    };
+    let others: Vec<_> = positions.into_iter().filter(|position| position.market_id != req.target_market_id).collect();
+    let has_enough_no = |position: &user_positions::Model| {
+        Decimal::from_str(&position.no_token_balance).map(|balance| balance >= amount).unwrap_or(false)
+    };
+    if others.len() != market_ids.len() - 1 || !others.iter().all(has_enough_no) {
+        return web::HttpResponse::BadRequest().json("Insufficient NO balance in the other group markets");
+    }
+
+    for position in others {
+        let no_balance = Decimal::from_str(&position.no_token_balance).unwrap();
+        let mut position: user_positions::ActiveModel = position.into();
+        position.no_token_balance = ActiveValue::Set((no_balance - amount).to_string());
+        if let Err(e) = position.update(&**conn).await {
+            eprintln!("Error updating user position: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let collateral = amount * Decimal::from(market_ids.len() as i64 - 2);
+    let yes_balance = Decimal::from_str(&target.yes_token_balance).unwrap();
+    let collateral_balance = Decimal::from_str(&target.collateral_balance).unwrap();
+    let mut target: user_positions::ActiveModel = target.into();
+    target.yes_token_balance = ActiveValue::Set((yes_balance + amount).to_string());
+    target.collateral_balance = ActiveValue::Set((collateral_balance + collateral).to_string());
+    if let Err(e) = target.update(&**conn).await {
+        eprintln!("Error updating user position: {}", e);
+        return web::HttpResponse::InternalServerError().finish();
+    }
+
+    let converted_amount = group.converted_amount + amount;
+    let mut group: market_groups::ActiveModel = group.into();
+    group.converted_amount = ActiveValue::Set(converted_amount);
+    match group.update(&**conn).await {
+        Ok(group) => web::Json(group_response(group, market_ids)),
+        Err(e) => {
+            eprintln!("Error updating group: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
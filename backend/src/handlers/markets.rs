@@ -1,12 +1,13 @@
 use actix_web::{web, Responder, get, post, put, delete};
-use sea_orm::{DatabaseConnection, DbErr, entity::*, ActiveValue};
+use sea_orm::{DatabaseConnection, DbErr, entity::*, sea_query::Expr, ActiveValue};
 use serde::{Serialize, Deserialize};
 use rust_decimal::Decimal;
 use validator::Validate;
 use std::sync::Arc;
 use hyperliquid::{HyperliquidClient, HyperliquidConfig};
+use sha3::{Digest, Keccak256};
 
-use crate::schema::markets;
+use crate::schema::{markets, trades};
 
 #[derive(Serialize, Deserialize)]
 struct MarketResponse {
@@ -15,6 +16,7 @@ struct MarketResponse {
     expiry_timestamp: i64,
     oracle_id: String,
     collateral_token: String,
+    resolution_source: String,
     status: String,
     yes_token_address: String,
     no_token_address: String,
@@ -24,10 +26,46 @@ struct MarketResponse {
     total_volume: String,
     current_price: String,
     creator_address: String,
+    description: String,
+    rules: String,
+    resolution_source_urls: Vec<String>,
+    category: String,
+    tags: Vec<String>,
+    image_url: Option<String>,
+    group_id: Option<String>,
+    parent_market_id: Option<String>,
+    parent_required_outcome: Option<bool>,
+    paused: bool,
+    halted_until: Option<i64>,
+    trading_close_timestamp: Option<i64>,
+    resolution_timestamp: Option<i64>,
+    proposed_outcome: Option<bool>,
+    event_timestamp: Option<i64>,
+    batch_auction_interval: Option<i64>,
+    batch_auction_ends_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MetadataRevisionResponse {
+    editor_address: String,
+    description: String,
+    rules: String,
+    resolution_source: String,
+    resolution_source_urls: Vec<String>,
+    category: String,
+    tags: Vec<String>,
+    image_url: Option<String>,
+    created_at: String,
 }
 
 #[derive(Serialize, Deserialize, Validate)]
 struct CreateMarketRequest {
+    /// Deterministic id derived by the market factory from creator, question, expiry and salt.
+    /// Checked against those fields.
+    #[validate(length(min = 1))]
+    id: String,
+    #[serde(default)]
+    salt: u64,
     #[validate(length(min = 1))]
     question: String,
     #[validate(range(min = 1))]
@@ -37,11 +75,47 @@ struct CreateMarketRequest {
     #[validate(length(min = 1))]
     collateral_token: String,
     #[validate(length(min = 1))]
+    resolution_source: String,
+    #[validate(length(min = 1))]
     yes_token_address: String,
     #[validate(length(min = 1))]
     no_token_address: String,
     #[validate(length(min = 1))]
     creator_address: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    rules: String,
+    #[serde(default)]
+    resolution_source_urls: Vec<String>,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[validate(url)]
+    image_url: Option<String>,
+    /// Makes the market conditional: it stays locked until this market resolves.
+    #[validate(length(min = 1))]
+    parent_market_id: Option<String>,
+    /// Parent outcome that activates the market; any other outcome voids it.
+    #[serde(default = "default_parent_required_outcome")]
+    parent_required_outcome: bool,
+    /// Trading stops at this time; omitted, trading runs until resolution.
+    #[validate(range(min = 1))]
+    trading_close_timestamp: Option<i64>,
+    /// Earliest time the oracle may resolve without proposing early resolution; defaults to expiry.
+    #[validate(range(min = 1))]
+    resolution_timestamp: Option<i64>,
+    /// Opens in batch auction mode, clearing every this many seconds.
+    #[validate(range(min = 1))]
+    batch_auction_interval: Option<i64>,
+    /// When the opening auction hands over to continuous matching; omitted, it runs until ended.
+    #[validate(range(min = 1))]
+    batch_auction_ends_at: Option<i64>,
+}
+
+fn default_parent_required_outcome() -> bool {
+    true
 }
 
 #[derive(Serialize, Deserialize, Validate)]
@@ -61,6 +135,30 @@ struct UpdateMarketRequest {
     #[validate(length(min = 1))]
     no_token_address: Option<String>,
     resolved_outcome: Option<bool>,
+    // Metadata, editable only before the market's first trade
+    description: Option<String>,
+    rules: Option<String>,
+    #[validate(length(min = 1))]
+    resolution_source: Option<String>,
+    resolution_source_urls: Option<Vec<String>>,
+    category: Option<String>,
+    tags: Option<Vec<String>>,
+    #[validate(url)]
+    image_url: Option<String>,
+    #[validate(length(min = 1))]
+    editor_address: Option<String>,
+}
+
+impl UpdateMarketRequest {
+    fn edits_metadata(&self) -> bool {
+        self.description.is_some()
+            || self.rules.is_some()
+            || self.resolution_source.is_some()
+            || self.resolution_source_urls.is_some()
+            || self.category.is_some()
+            || self.tags.is_some()
+            || self.image_url.is_some()
+    }
 }
 
 #[derive(Serialize, Deserialize, Validate)]
@@ -80,30 +178,72 @@ struct ResolveMarketRequest {
     outcome: bool,
 }
 
+#[derive(Serialize, Deserialize, Validate)]
+struct ProposeResolutionRequest {
+    #[validate(length(min = 1))]
+    oracle_address: String,
+    outcome: bool,
+    #[validate(range(min = 1))]
+    event_timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct CancelResolutionRequest {
+    #[validate(length(min = 1))]
+    oracle_address: String,
+}
+
+fn market_response(market: markets::Model) -> MarketResponse {
+    MarketResponse {
+        id: market.id,
+        question: market.question,
+        expiry_timestamp: market.expiry_timestamp,
+        oracle_id: market.oracle_id,
+        collateral_token: market.collateral_token,
+        resolution_source: market.resolution_source,
+        status: market.status.to_string(),
+        yes_token_address: market.yes_token_address,
+        no_token_address: market.no_token_address,
+        resolved_outcome: market.resolved_outcome,
+        created_at: market.created_at.to_string(),
+        updated_at: market.updated_at.to_string(),
+        total_volume: market.total_volume.to_string(),
+        current_price: market.current_price.to_string(),
+        creator_address: market.creator_address,
+        description: market.description,
+        rules: market.rules,
+        resolution_source_urls: market.resolution_source_urls,
+        category: market.category,
+        tags: market.tags,
+        image_url: market.image_url,
+        group_id: market.group_id,
+        parent_market_id: market.parent_market_id,
+        parent_required_outcome: market.parent_required_outcome,
+        paused: market.paused,
+        halted_until: market.halted_until,
+        trading_close_timestamp: market.trading_close_timestamp,
+        resolution_timestamp: market.resolution_timestamp,
+        proposed_outcome: market.proposed_outcome,
+        event_timestamp: market.event_timestamp,
+        batch_auction_interval: market.batch_auction_interval,
+        batch_auction_ends_at: market.batch_auction_ends_at,
+    }
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
 #[get("/markets")]
 pub async fn get_all_markets(conn: web::Data<DatabaseConnection>) -> impl Responder {
     let result = markets::Entity::find().all(&**conn).await;
 
     match result {
         Ok(markets) => {
-            let market_responses: Vec<MarketResponse> = markets.into_iter().map(|market| {
-                MarketResponse {
-                    id: market.id,
-                    question: market.question,
-                    expiry_timestamp: market.expiry_timestamp,
-                    oracle_id: market.oracle_id,
-                    collateral_token: market.collateral_token,
-                    status: market.status.to_string(),
-                    yes_token_address: market.yes_token_address,
-                    no_token_address: market.no_token_address,
-                    resolved_outcome: market.resolved_outcome,
-                    created_at: market.created_at.to_string(),
-                    updated_at: market.updated_at.to_string(),
-                    total_volume: market.total_volume.to_string(),
-                    current_price: market.current_price.to_string(),
-                    creator_address: market.creator_address,
-                }
-            }).collect();
+            let market_responses: Vec<MarketResponse> = markets.into_iter().map(market_response).collect();
             web::Json(market_responses)
         }
         Err(e) => {
@@ -119,23 +259,7 @@ pub async fn get_market_by_id(conn: web::Data<DatabaseConnection>, market_id: we
 
     match result {
         Ok(Some(market)) => {
-            let market_response = MarketResponse {
-                id: market.id,
-                question: market.question,
-                expiry_timestamp: market.expiry_timestamp,
-                oracle_id: market.oracle_id,
-                collateral_token: market.collateral_token,
-                status: market.status.to_string(),
-                yes_token_address: market.yes_token_address,
-                no_token_address: market.no_token_address,
-                resolved_outcome: market.resolved_outcome,
-                created_at: market.created_at.to_string(),
-                updated_at: market.updated_at.to_string(),
-                total_volume: market.total_volume.to_string(),
-                current_price: market.current_price.to_string(),
-                creator_address: market.creator_address,
-            };
-            web::Json(market_response)
+            web::Json(market_response(market))
         }
         Ok(None) => {
             web::HttpResponse::NotFound().finish()
@@ -147,32 +271,99 @@ pub async fn get_market_by_id(conn: web::Data<DatabaseConnection>, market_id: we
     }
 }
 
+/// Recomputes the id the market factory derives from the creation parameters, as in
+/// `MarketFactoryState::generate_market_id`. `None` if the creator address is malformed.
+fn derive_market_id(creator_address: &str, question: &str, expiry_timestamp: i64, salt: u64) -> Option<String> {
+    let creator = hex::decode(creator_address.trim_start_matches("0x"))
+        .ok()
+        .filter(|bytes| bytes.len() == 20)?;
+    let mut hasher = Keccak256::new();
+    hasher.update(&creator);
+    hasher.update(Keccak256::digest(question.as_bytes()));
+    hasher.update((expiry_timestamp as u64).to_be_bytes());
+    hasher.update(salt.to_be_bytes());
+    Some(format!("0x{}", hex::encode(hasher.finalize())))
+}
+
 #[post("/markets")]
 pub async fn create_market(conn: web::Data<DatabaseConnection>, req: web::Json<CreateMarketRequest>) -> impl Responder {
     if let Err(e) = req.validate() {
         return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
     }
+    match derive_market_id(&req.creator_address, &req.question, req.expiry_timestamp, req.salt) {
+        Some(id) if id.eq_ignore_ascii_case(&req.id) => {}
+        Some(_) => return web::HttpResponse::BadRequest().json("Market id does not match its creator, question, expiry and salt"),
+        None => return web::HttpResponse::BadRequest().json("Invalid creator address"),
+    }
+    if let Some(parent_market_id) = &req.parent_market_id {
+        match markets::Entity::find_by_id(parent_market_id.clone()).one(&**conn).await {
+            Ok(Some(parent)) => {
+                if !matches!(parent.status, crate::schema::MarketStatus::Active | crate::schema::MarketStatus::Locked) {
+                    return web::HttpResponse::BadRequest().json(format!("Parent market {} is already settled", parent_market_id));
+                }
+                if parent.expiry_timestamp > req.expiry_timestamp {
+                    return web::HttpResponse::BadRequest().json("Market must not expire before its parent");
+                }
+            }
+            Ok(None) => {
+                return web::HttpResponse::BadRequest().json(format!("Parent market not found: {}", parent_market_id));
+            }
+            Err(e) => {
+                eprintln!("Error fetching parent market: {}", e);
+                return web::HttpResponse::InternalServerError().finish();
+            }
+        }
+    }
+    let resolution_timestamp = req.resolution_timestamp.unwrap_or(req.expiry_timestamp);
+    if resolution_timestamp > req.expiry_timestamp {
+        return web::HttpResponse::BadRequest().json("Resolution must not be after expiry");
+    }
+    if let Some(trading_close_timestamp) = req.trading_close_timestamp {
+        if trading_close_timestamp > resolution_timestamp {
+            return web::HttpResponse::BadRequest().json("Trading must close no later than resolution");
+        }
+    }
+    if req.batch_auction_ends_at.is_some() && req.batch_auction_interval.is_none() {
+        return web::HttpResponse::BadRequest().json("Batch auction end requires an interval");
+    }
+    let status = if req.parent_market_id.is_some() {
+        crate::schema::MarketStatus::Locked
+    } else {
+        crate::schema::MarketStatus::Active
+    };
     let new_market = markets::ActiveModel {
-        id: ActiveValue::NotSet,
+        id: ActiveValue::Set(req.id.clone()),
         question: ActiveValue::Set(req.question.clone()),
         expiry_timestamp: ActiveValue::Set(req.expiry_timestamp),
         oracle_id: ActiveValue::Set(req.oracle_id.clone()),
         collateral_token: ActiveValue::Set(req.collateral_token.clone()),
-        status: ActiveValue::Set(crate::schema::MarketStatus::Active),
+        resolution_source: ActiveValue::Set(req.resolution_source.clone()),
+        status: ActiveValue::Set(status),
         yes_token_address: ActiveValue::Set(req.yes_token_address.clone()),
         no_token_address: ActiveValue::Set(req.no_token_address.clone()),
         resolved_outcome: ActiveValue::Set(None),
-        created_at: ActiveValue::Set(std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64),
-        updated_at: ActiveValue::Set(std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64),
+        created_at: ActiveValue::Set(now()),
+        updated_at: ActiveValue::Set(now()),
         total_volume: ActiveValue::Set(Decimal::from(0)),
         current_price: ActiveValue::Set(Decimal::from(0)),
         creator_address: ActiveValue::Set(req.creator_address.clone()),
+        description: ActiveValue::Set(req.description.clone()),
+        rules: ActiveValue::Set(req.rules.clone()),
+        resolution_source_urls: ActiveValue::Set(req.resolution_source_urls.clone()),
+        category: ActiveValue::Set(req.category.clone()),
+        tags: ActiveValue::Set(req.tags.clone()),
+        image_url: ActiveValue::Set(req.image_url.clone()),
+        group_id: ActiveValue::Set(None),
+        parent_market_id: ActiveValue::Set(req.parent_market_id.clone()),
+        parent_required_outcome: ActiveValue::Set(req.parent_market_id.as_ref().map(|_| req.parent_required_outcome)),
+        paused: ActiveValue::Set(false),
+        halted_until: ActiveValue::Set(None),
+        trading_close_timestamp: ActiveValue::Set(req.trading_close_timestamp),
+        resolution_timestamp: ActiveValue::Set(req.resolution_timestamp),
+        proposed_outcome: ActiveValue::Set(None),
+        event_timestamp: ActiveValue::Set(None),
+        batch_auction_interval: ActiveValue::Set(req.batch_auction_interval),
+        batch_auction_ends_at: ActiveValue::Set(req.batch_auction_ends_at),
     };
 
     let result = markets::Entity::insert(new_market).exec(&**conn).await;
@@ -181,23 +372,7 @@ pub async fn create_market(conn: web::Data<DatabaseConnection>, req: web::Json<C
         Ok(insert_result) => {
             let market_id = insert_result.last_insert_id;
             let market = markets::Entity::find_by_id(market_id).one(&**conn).await.unwrap().unwrap();
-            let market_response = MarketResponse {
-                id: market.id,
-                question: market.question,
-                expiry_timestamp: market.expiry_timestamp,
-                oracle_id: market.oracle_id,
-                collateral_token: market.collateral_token,
-                status: market.status.to_string(),
-                yes_token_address: market.yes_token_address,
-                no_token_address: market.no_token_address,
-                resolved_outcome: market.resolved_outcome,
-                created_at: market.created_at.to_string(),
-                updated_at: market.updated_at.to_string(),
-                total_volume: market.total_volume.to_string(),
-                current_price: market.current_price.to_string(),
-                creator_address: market.creator_address,
-            };
-            web::Json(market_response)
+            web::Json(market_response(market))
         }
         Err(e) => {
             eprintln!("Error creating market: {}", e);
@@ -216,6 +391,47 @@ pub async fn update_market(conn: web::Data<DatabaseConnection>, market_id: web::
 
     match market {
         Ok(Some(market)) => {
+            if req.edits_metadata() {
+                let editor_address = match &req.editor_address {
+                    Some(editor_address) => editor_address.clone(),
+                    None => return web::HttpResponse::BadRequest().json("editor_address is required to edit metadata"),
+                };
+
+                let trade_count = crate::schema::trades::Entity::find()
+                    .filter(crate::schema::trades::Column::MarketId.eq(market_id.clone()))
+                    .count(&**conn)
+                    .await;
+                match trade_count {
+                    Ok(0) => {}
+                    Ok(_) => {
+                        return web::HttpResponse::Conflict().json("Market metadata cannot be edited after the first trade");
+                    }
+                    Err(e) => {
+                        eprintln!("Error counting market trades: {}", e);
+                        return web::HttpResponse::InternalServerError().finish();
+                    }
+                }
+
+                // Keep the metadata being replaced in the edit history
+                let revision = crate::schema::market_metadata_revisions::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    market_id: ActiveValue::Set(market.id.clone()),
+                    editor_address: ActiveValue::Set(editor_address),
+                    description: ActiveValue::Set(market.description.clone()),
+                    rules: ActiveValue::Set(market.rules.clone()),
+                    resolution_source: ActiveValue::Set(market.resolution_source.clone()),
+                    resolution_source_urls: ActiveValue::Set(market.resolution_source_urls.clone()),
+                    category: ActiveValue::Set(market.category.clone()),
+                    tags: ActiveValue::Set(market.tags.clone()),
+                    image_url: ActiveValue::Set(market.image_url.clone()),
+                    created_at: ActiveValue::Set(now()),
+                };
+                if let Err(e) = crate::schema::market_metadata_revisions::Entity::insert(revision).exec(&**conn).await {
+                    eprintln!("Error saving metadata revision: {}", e);
+                    return web::HttpResponse::InternalServerError().finish();
+                }
+            }
+
             let mut market: markets::ActiveModel = market.into();
             if let Some(question) = &req.question {
                 market.question = ActiveValue::Set(question.clone());
@@ -241,32 +457,34 @@ pub async fn update_market(conn: web::Data<DatabaseConnection>, market_id: web::
             if let Some(resolved_outcome) = req.resolved_outcome {
                 market.resolved_outcome = ActiveValue::Set(Some(resolved_outcome));
             }
-            market.updated_at = ActiveValue::Set(std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs() as i64);
+            if let Some(description) = &req.description {
+                market.description = ActiveValue::Set(description.clone());
+            }
+            if let Some(rules) = &req.rules {
+                market.rules = ActiveValue::Set(rules.clone());
+            }
+            if let Some(resolution_source) = &req.resolution_source {
+                market.resolution_source = ActiveValue::Set(resolution_source.clone());
+            }
+            if let Some(resolution_source_urls) = &req.resolution_source_urls {
+                market.resolution_source_urls = ActiveValue::Set(resolution_source_urls.clone());
+            }
+            if let Some(category) = &req.category {
+                market.category = ActiveValue::Set(category.clone());
+            }
+            if let Some(tags) = &req.tags {
+                market.tags = ActiveValue::Set(tags.clone());
+            }
+            if let Some(image_url) = &req.image_url {
+                market.image_url = ActiveValue::Set(Some(image_url.clone()));
+            }
+            market.updated_at = ActiveValue::Set(now());
 
             let result = market.update(&**conn).await;
 
             match result {
                 Ok(market) => {
-                    let market_response = MarketResponse {
-                        id: market.id,
-                        question: market.question,
-                        expiry_timestamp: market.expiry_timestamp,
-                        oracle_id: market.oracle_id,
-                        collateral_token: market.collateral_token,
-                        status: market.status.to_string(),
-                        yes_token_address: market.yes_token_address,
-                        no_token_address: market.no_token_address,
-                        resolved_outcome: market.resolved_outcome,
-                        created_at: market.created_at.to_string(),
-                        updated_at: market.updated_at.to_string(),
-                        total_volume: market.total_volume.to_string(),
-                        current_price: market.current_price.to_string(),
-                        creator_address: market.creator_address,
-                    };
-                    web::Json(market_response)
+                    web::Json(market_response(market))
                 }
                 Err(e) => {
                     eprintln!("Error updating market: {}", e);
@@ -284,6 +502,68 @@ pub async fn update_market(conn: web::Data<DatabaseConnection>, market_id: web::
     }
 }
 
+/// Activates or voids the locked markets conditional on `parent_market_id` once it
+/// resolves to `outcome`. `None` means the parent was voided, which voids its children and,
+/// in turn, theirs.
+pub(crate) async fn apply_parent_resolution(conn: &DatabaseConnection, parent_market_id: &str, outcome: Option<bool>) -> Result<(), DbErr> {
+    let mut pending = vec![(parent_market_id.to_string(), outcome)];
+    while let Some((parent_market_id, outcome)) = pending.pop() {
+        let children = markets::Entity::find()
+            .filter(markets::Column::ParentMarketId.eq(parent_market_id))
+            .filter(markets::Column::Status.eq(crate::schema::MarketStatus::Locked))
+            .all(conn)
+            .await?;
+
+        for child in children {
+            let activates = outcome.is_some() && outcome == child.parent_required_outcome;
+            if !activates {
+                pending.push((child.id.clone(), None));
+            }
+            // Refunds of voided positions are settled on-chain; positions are updated by the indexer
+            let mut child: markets::ActiveModel = child.into();
+            child.status = ActiveValue::Set(if activates {
+                crate::schema::MarketStatus::Active
+            } else {
+                crate::schema::MarketStatus::Voided
+            });
+            child.updated_at = ActiveValue::Set(now());
+            child.update(conn).await?;
+        }
+    }
+    Ok(())
+}
+
+#[get("/markets/{market_id}/metadata/history")]
+pub async fn get_market_metadata_history(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>) -> impl Responder {
+    let result = crate::schema::market_metadata_revisions::Entity::find()
+        .filter(crate::schema::market_metadata_revisions::Column::MarketId.eq(market_id.into_inner()))
+        .all(&**conn)
+        .await;
+
+    match result {
+        Ok(revisions) => {
+            let revision_responses: Vec<MetadataRevisionResponse> = revisions.into_iter().map(|revision| {
+                MetadataRevisionResponse {
+                    editor_address: revision.editor_address,
+                    description: revision.description,
+                    rules: revision.rules,
+                    resolution_source: revision.resolution_source,
+                    resolution_source_urls: revision.resolution_source_urls,
+                    category: revision.category,
+                    tags: revision.tags,
+                    image_url: revision.image_url,
+                    created_at: revision.created_at.to_string(),
+                }
+            }).collect();
+            web::Json(revision_responses)
+        }
+        Err(e) => {
+            eprintln!("Error fetching metadata history: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
 #[delete("/markets/{market_id}")]
 pub async fn delete_market(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>) -> impl Responder {
     let market_id = market_id.into_inner();
@@ -310,6 +590,9 @@ pub async fn mint_tokens(conn: web::Data<DatabaseConnection>, hl_client: web::Da
         return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
     }
     let market_id = market_id.into_inner();
+    if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &market_id, false).await {
+        return response;
+    }
     let user_address = req.user_address.clone();
     let yes_amount = Decimal::from_str(&req.yes_amount).unwrap();
     let no_amount = Decimal::from_str(&req.no_amount).unwrap();
@@ -350,10 +633,7 @@ pub async fn mint_tokens(conn: web::Data<DatabaseConnection>, hl_client: web::Da
                         yes_token_balance: ActiveValue::Set(yes_amount.to_string()),
                         no_token_balance: ActiveValue::Set(no_amount.to_string()),
                         collateral_balance: ActiveValue::Set(Decimal::from(0).to_string()),
-                        last_updated_at: ActiveValue::Set(std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs() as i64),
+                        last_updated_at: ActiveValue::Set(now()),
                     };
                     let insert_result = crate::schema::user_positions::Entity::insert(new_position).exec(&**conn).await;
                     match insert_result {
@@ -442,6 +722,26 @@ pub async fn resolve_market(conn: web::Data<DatabaseConnection>, hl_client: web:
     let market_id = market_id.into_inner();
     let outcome = req.outcome;
 
+    match markets::Entity::find_by_id(market_id.clone()).one(&**conn).await {
+        Ok(Some(market)) => match market.proposed_outcome {
+            Some(proposed) if proposed != outcome => {
+                return web::HttpResponse::BadRequest().json("Outcome does not match the proposed resolution");
+            }
+            Some(_) => {}
+            None => {
+                let resolution_timestamp = market.resolution_timestamp.unwrap_or(market.expiry_timestamp);
+                if now() < resolution_timestamp {
+                    return web::HttpResponse::BadRequest().json(format!("Market cannot be resolved before {}", resolution_timestamp));
+                }
+            }
+        },
+        Ok(None) => return web::HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    }
+
     let resolve_result = hl_client.resolve_market(market_id.clone(), outcome).await;
 
     match resolve_result {
@@ -458,6 +758,10 @@ pub async fn resolve_market(conn: web::Data<DatabaseConnection>, hl_client: web:
                     let update_result = market.update(&**conn).await;
                     match update_result {
                         Ok(_) => {
+                            if let Err(e) = apply_parent_resolution(&conn, &market_id, Some(outcome)).await {
+                                eprintln!("Error updating conditional markets: {}", e);
+                                return web::HttpResponse::InternalServerError().json(format!("Error updating conditional markets: {}", e));
+                            }
                             web::HttpResponse::Ok().finish()
                         }
                         Err(e) => {
@@ -481,4 +785,120 @@ pub async fn resolve_market(conn: web::Data<DatabaseConnection>, hl_client: web:
             web::HttpResponse::InternalServerError().json(format!("Error resolving market: {}", e))
         }
     }
-} 
\ No newline at end of file
+} 
+
+/// Returns the response to send if the market no longer accepts orders: after its trading
+/// close, or while an early resolution is pending.
+pub(crate) async fn reject_if_trading_closed(conn: &DatabaseConnection, market_id: &str) -> Option<web::HttpResponse> {
+    match markets::Entity::find_by_id(market_id.to_string()).one(conn).await {
+        Ok(Some(market)) => {
+            if market.proposed_outcome.is_some() {
+                return Some(web::HttpResponse::Conflict().json("Trading is closed pending early resolution"));
+            }
+            match market.trading_close_timestamp {
+                Some(close) if now() >= close => Some(web::HttpResponse::Conflict().json("Trading is closed")),
+                _ => None,
+            }
+        }
+        Ok(None) => Some(web::HttpResponse::NotFound().json(format!("Market not found: {}", market_id))),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            Some(web::HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// The oracle proposes resolving before the scheduled resolution time because the event
+/// already happened. Trading stops and trades executed at or after `event_timestamp` are flagged.
+#[post("/markets/{market_id}/propose-resolution")]
+pub async fn propose_early_resolution(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<ProposeResolutionRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    if req.event_timestamp > now() {
+        return web::HttpResponse::BadRequest().json("Event time must not be in the future");
+    }
+    let market_id = market_id.into_inner();
+    let market = match markets::Entity::find_by_id(market_id.clone()).one(&**conn).await {
+        Ok(Some(market)) => market,
+        Ok(None) => return web::HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if !market.oracle_id.eq_ignore_ascii_case(&req.oracle_address) {
+        return web::HttpResponse::Forbidden().json("Only the market's oracle can propose resolution");
+    }
+    if market.status != crate::schema::MarketStatus::Active || market.group_id.is_some() {
+        return web::HttpResponse::BadRequest().json("Market can't be resolved early");
+    }
+
+    let mut market: markets::ActiveModel = market.into();
+    market.proposed_outcome = ActiveValue::Set(Some(req.outcome));
+    market.event_timestamp = ActiveValue::Set(Some(req.event_timestamp));
+    market.updated_at = ActiveValue::Set(now());
+    if let Err(e) = market.update(&**conn).await {
+        eprintln!("Error updating market: {}", e);
+        return web::HttpResponse::InternalServerError().finish();
+    }
+
+    let flagged = trades::Entity::update_many()
+        .col_expr(trades::Column::Flagged, Expr::value(true))
+        .filter(trades::Column::MarketId.eq(market_id))
+        .filter(trades::Column::CreatedAt.gte(req.event_timestamp))
+        .exec(&**conn)
+        .await;
+    match flagged {
+        Ok(result) => web::HttpResponse::Ok().json(result.rows_affected),
+        Err(e) => {
+            eprintln!("Error flagging trades: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Withdraws a pending early resolution, reopening trading and clearing the trade flags.
+#[post("/markets/{market_id}/cancel-resolution")]
+pub async fn cancel_early_resolution(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<CancelResolutionRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let market_id = market_id.into_inner();
+    let market = match markets::Entity::find_by_id(market_id.clone()).one(&**conn).await {
+        Ok(Some(market)) => market,
+        Ok(None) => return web::HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if !market.oracle_id.eq_ignore_ascii_case(&req.oracle_address) {
+        return web::HttpResponse::Forbidden().json("Only the market's oracle can cancel resolution");
+    }
+    if market.proposed_outcome.is_none() || market.status != crate::schema::MarketStatus::Active {
+        return web::HttpResponse::BadRequest().json("No early resolution has been proposed");
+    }
+
+    let mut market: markets::ActiveModel = market.into();
+    market.proposed_outcome = ActiveValue::Set(None);
+    market.event_timestamp = ActiveValue::Set(None);
+    market.updated_at = ActiveValue::Set(now());
+    if let Err(e) = market.update(&**conn).await {
+        eprintln!("Error updating market: {}", e);
+        return web::HttpResponse::InternalServerError().finish();
+    }
+
+    let unflagged = trades::Entity::update_many()
+        .col_expr(trades::Column::Flagged, Expr::value(false))
+        .filter(trades::Column::MarketId.eq(market_id))
+        .exec(&**conn)
+        .await;
+    match unflagged {
+        Ok(_) => web::HttpResponse::Ok().finish(),
+        Err(e) => {
+            eprintln!("Error clearing trade flags: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
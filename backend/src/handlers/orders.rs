@@ -1,15 +1,16 @@
-use actix_web::{web, Responder, get, post, delete};
+use actix_web::{web, Responder, get, post, patch, delete};
 use sea_orm::{DatabaseConnection, DbErr, entity::*, ActiveValue};
 use serde::{Serialize, Deserialize};
 use rust_decimal::Decimal;
 
-use crate::schema::{orders, OrderSide, OrderStatus};
+use crate::schema::{orders, OrderSide, OrderStatus, OrderType, TimeInForce};
 
 #[derive(Serialize, Deserialize)]
 struct OrderResponse {
     id: i32,
     market_id: String,
     user_address: String,
+    is_yes: bool,
     side: String,
     price: String,
     amount: String,
@@ -18,17 +19,198 @@ struct OrderResponse {
     created_at: String,
     updated_at: String,
     tx_hash: Option<String>,
+    order_type: String,
+    time_in_force: String,
+    expires_at: Option<i64>,
+    post_only: bool,
+    reduce_only: bool,
+    stop_price: Option<String>,
+    max_slippage: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct CreateOrderRequest {
     market_id: String,
     user_address: String,
+    /// The outcome traded; defaults to YES.
+    #[serde(default = "default_is_yes")]
+    is_yes: bool,
     side: String,
-    price: String,
+    /// Limit price; omitted for market orders.
+    price: Option<String>,
+    amount: String,
+    /// "limit" or "market".
+    #[serde(default = "default_order_type")]
+    order_type: String,
+    /// GTC, IOC, FOK or GTT. Defaults to GTC for limit orders and IOC for market orders.
+    time_in_force: Option<String>,
+    /// Required for GTT.
+    expires_at: Option<i64>,
+    #[serde(default)]
+    post_only: bool,
+    #[serde(default)]
+    reduce_only: bool,
+    /// Makes the order a stop (market) or stop-limit order on the last trade price.
+    stop_price: Option<String>,
+    /// Market orders only: how many cents from the best price the order may trade.
+    max_slippage: Option<String>,
+}
+
+fn default_is_yes() -> bool {
+    true
+}
+
+fn default_order_type() -> String {
+    "limit".to_string()
+}
+
+#[derive(Serialize, Deserialize)]
+struct AmendOrderRequest {
+    user_address: String,
+    /// New limit price; keeps the current one if omitted.
+    price: Option<String>,
+    /// New unfilled size.
     amount: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct BatchOrderRequest {
+    user_address: String,
+    #[serde(default)]
+    place: Vec<CreateOrderRequest>,
+    /// Ids of orders to cancel.
+    #[serde(default)]
+    cancel: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BatchItemResult {
+    ok: bool,
+    order: Option<OrderResponse>,
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BatchOrderResponse {
+    /// One result per order in `place`, in request order.
+    placed: Vec<BatchItemResult>,
+    /// One result per id in `cancel`, in request order.
+    cancelled: Vec<BatchItemResult>,
+}
+
+impl BatchItemResult {
+    fn ok(order: OrderResponse) -> Self {
+        Self { ok: true, order: Some(order), error: None }
+    }
+
+    fn err(error: impl Into<String>) -> Self {
+        Self { ok: false, order: None, error: Some(error.into()) }
+    }
+}
+
+fn order_response(order: orders::Model) -> OrderResponse {
+    OrderResponse {
+        id: order.id,
+        market_id: order.market_id,
+        user_address: order.user_address,
+        is_yes: order.is_yes,
+        side: order.side.to_string(),
+        price: order.price.to_string(),
+        amount: order.amount.to_string(),
+        filled_amount: order.filled_amount.to_string(),
+        status: order.status.to_string(),
+        created_at: order.created_at.to_string(),
+        updated_at: order.updated_at.to_string(),
+        tx_hash: order.tx_hash,
+        order_type: order.order_type.to_string(),
+        time_in_force: order.time_in_force.to_string(),
+        expires_at: order.expires_at,
+        post_only: order.post_only,
+        reduce_only: order.reduce_only,
+        stop_price: order.stop_price.map(|price| price.to_string()),
+        max_slippage: order.max_slippage.map(|slippage| slippage.to_string()),
+    }
+}
+
+fn parse_price(value: &str) -> Result<Decimal, String> {
+    Decimal::from_str(value)
+        .ok()
+        .filter(|price| *price > Decimal::ZERO && *price < Decimal::from(100))
+        .ok_or_else(|| format!("Invalid price: {}", value))
+}
+
+fn parse_amount(value: &str) -> Result<Decimal, String> {
+    Decimal::from_str(value)
+        .ok()
+        .filter(|amount| *amount > Decimal::ZERO)
+        .ok_or_else(|| format!("Invalid amount: {}", value))
+}
+
+/// The order options, checked the same way the market contract checks them.
+struct OrderOptions {
+    order_type: OrderType,
+    time_in_force: TimeInForce,
+    price: Decimal,
+    stop_price: Option<Decimal>,
+    max_slippage: Option<Decimal>,
+}
+
+fn parse_order_options(req: &CreateOrderRequest, now: i64) -> Result<OrderOptions, String> {
+    let order_type = match req.order_type.to_lowercase().as_str() {
+        "limit" => OrderType::Limit,
+        "market" => OrderType::Market,
+        other => return Err(format!("Unknown order type: {}", other)),
+    };
+    let time_in_force = match req.time_in_force.as_deref().map(str::to_uppercase).as_deref() {
+        None if order_type == OrderType::Market => TimeInForce::Ioc,
+        None | Some("GTC") => TimeInForce::Gtc,
+        Some("IOC") => TimeInForce::Ioc,
+        Some("FOK") => TimeInForce::Fok,
+        Some("GTT") => TimeInForce::Gtt,
+        Some(other) => return Err(format!("Unknown time in force: {}", other)),
+    };
+
+    let (price, max_slippage) = match order_type {
+        OrderType::Limit => {
+            let price = req.price.as_deref().ok_or("Limit orders need a price")?;
+            (parse_price(price)?, None)
+        }
+        OrderType::Market => {
+            if matches!(time_in_force, TimeInForce::Gtc | TimeInForce::Gtt) {
+                return Err("Market orders must be IOC or FOK".to_string());
+            }
+            let max_slippage = req.max_slippage.as_deref().unwrap_or("0");
+            let max_slippage = Decimal::from_str(max_slippage)
+                .ok()
+                .filter(|slippage| *slippage >= Decimal::ZERO && *slippage < Decimal::from(100))
+                .ok_or_else(|| format!("Invalid max slippage: {}", max_slippage))?;
+            // The execution price is only known once the order reaches the book
+            (Decimal::ZERO, Some(max_slippage))
+        }
+    };
+    match (time_in_force, req.expires_at) {
+        (TimeInForce::Gtt, Some(expires_at)) if expires_at > now => {}
+        (TimeInForce::Gtt, _) => return Err("GTT orders need a future expires_at".to_string()),
+        (_, Some(_)) => return Err("expires_at is only valid for GTT orders".to_string()),
+        _ => {}
+    }
+    if req.post_only && matches!(time_in_force, TimeInForce::Ioc | TimeInForce::Fok) {
+        return Err("Post-only orders can't be IOC or FOK".to_string());
+    }
+    if req.reduce_only && req.side.to_lowercase() != "sell" {
+        return Err("Reduce-only orders must be sells".to_string());
+    }
+    let stop_price = req.stop_price.as_deref().map(parse_price).transpose()?;
+
+    Ok(OrderOptions {
+        order_type,
+        time_in_force,
+        price,
+        stop_price,
+        max_slippage,
+    })
+}
+
 #[get("/markets/{market_id}/orders")]
 pub async fn get_all_market_orders(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>) -> impl Responder {
     let market_id = market_id.into_inner();
@@ -39,21 +221,7 @@ pub async fn get_all_market_orders(conn: web::Data<DatabaseConnection>, market_i
 
     match result {
         Ok(orders) => {
-            let order_responses: Vec<OrderResponse> = orders.into_iter().map(|order| {
-                OrderResponse {
-                    id: order.id,
-                    market_id: order.market_id,
-                    user_address: order.user_address,
-                    side: order.side.to_string(),
-                    price: order.price.to_string(),
-                    amount: order.amount.to_string(),
-                    filled_amount: order.filled_amount.to_string(),
-                    status: order.status.to_string(),
-                    created_at: order.created_at.to_string(),
-                    updated_at: order.updated_at.to_string(),
-                    tx_hash: order.tx_hash,
-                }
-            }).collect();
+            let order_responses: Vec<OrderResponse> = orders.into_iter().map(order_response).collect();
             web::Json(order_responses)
         }
         Err(e) => {
@@ -70,20 +238,7 @@ pub async fn get_order_by_id(conn: web::Data<DatabaseConnection>, order_id: web:
 
     match result {
         Ok(Some(order)) => {
-            let order_response = OrderResponse {
-                id: order.id,
-                market_id: order.market_id,
-                user_address: order.user_address,
-                side: order.side.to_string(),
-                price: order.price.to_string(),
-                amount: order.amount.to_string(),
-                filled_amount: order.filled_amount.to_string(),
-                status: order.status.to_string(),
-                created_at: order.created_at.to_string(),
-                updated_at: order.updated_at.to_string(),
-                tx_hash: order.tx_hash,
-            };
-            web::Json(order_response)
+            web::Json(order_response(order))
         }
         Ok(None) => {
             web::HttpResponse::NotFound().finish()
@@ -97,24 +252,40 @@ pub async fn get_order_by_id(conn: web::Data<DatabaseConnection>, order_id: web:
 
 #[post("/markets/{market_id}/orders")]
 pub async fn create_order(conn: web::Data<DatabaseConnection>, req: web::Json<CreateOrderRequest>) -> impl Responder {
+    if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &req.market_id, true).await {
+        return response;
+    }
+    if let Some(response) = crate::handlers::markets::reject_if_trading_closed(&conn, &req.market_id).await {
+        return response;
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let options = match parse_order_options(&req, now) {
+        Ok(options) => options,
+        Err(e) => return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e)),
+    };
     let new_order = orders::ActiveModel {
         id: ActiveValue::NotSet,
         market_id: ActiveValue::Set(req.market_id.clone()),
         user_address: ActiveValue::Set(req.user_address.clone()),
+        is_yes: ActiveValue::Set(req.is_yes),
         side: ActiveValue::Set(OrderSide::from_str(&req.side).unwrap()),
-        price: ActiveValue::Set(Decimal::from_str(&req.price).unwrap()),
+        price: ActiveValue::Set(options.price),
         amount: ActiveValue::Set(Decimal::from_str(&req.amount).unwrap()),
         filled_amount: ActiveValue::Set(Decimal::from(0)),
-        status: ActiveValue::Set(OrderStatus::Open),
-        created_at: ActiveValue::Set(std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64),
-        updated_at: ActiveValue::Set(std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64),
+        status: ActiveValue::Set(if options.stop_price.is_some() { OrderStatus::Pending } else { OrderStatus::Open }),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
         tx_hash: ActiveValue::Set(None),
+        order_type: ActiveValue::Set(options.order_type),
+        time_in_force: ActiveValue::Set(options.time_in_force),
+        expires_at: ActiveValue::Set(req.expires_at),
+        post_only: ActiveValue::Set(req.post_only),
+        reduce_only: ActiveValue::Set(req.reduce_only),
+        stop_price: ActiveValue::Set(options.stop_price),
+        max_slippage: ActiveValue::Set(options.max_slippage),
     };
 
     let result = orders::Entity::insert(new_order).exec(&**conn).await;
@@ -123,20 +294,7 @@ pub async fn create_order(conn: web::Data<DatabaseConnection>, req: web::Json<Cr
         Ok(insert_result) => {
             let order_id = insert_result.last_insert_id;
             let order = orders::Entity::find_by_id(order_id).one(&**conn).await.unwrap().unwrap();
-            let order_response = OrderResponse {
-                id: order.id,
-                market_id: order.market_id,
-                user_address: order.user_address,
-                side: order.side.to_string(),
-                price: order.price.to_string(),
-                amount: order.amount.to_string(),
-                filled_amount: order.filled_amount.to_string(),
-                status: order.status.to_string(),
-                created_at: order.created_at.to_string(),
-                updated_at: order.updated_at.to_string(),
-                tx_hash: order.tx_hash,
-            };
-            web::Json(order_response)
+            web::Json(order_response(order))
         }
         Err(e) => {
             eprintln!("Error creating order: {}", e);
@@ -163,4 +321,169 @@ pub async fn delete_order(conn: web::Data<DatabaseConnection>, order_id: web::Pa
             web::HttpResponse::InternalServerError().finish()
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Changes the price or unfilled size of an open limit order. Reducing the size at the same
+/// price keeps the order's place in the queue; any other change moves it to the back.
+#[patch("/orders/{order_id}")]
+pub async fn amend_order(conn: web::Data<DatabaseConnection>, order_id: web::Path<i32>, req: web::Json<AmendOrderRequest>) -> impl Responder {
+    let order = match orders::Entity::find_by_id(order_id.into_inner()).one(&**conn).await {
+        Ok(Some(order)) => order,
+        Ok(None) => return web::HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching order: {}", e);
+            return web::HttpResponse::InternalServerError().finish();
+        }
+    };
+    if !order.user_address.eq_ignore_ascii_case(&req.user_address) {
+        return web::HttpResponse::Forbidden().json("Only the order's owner can amend it");
+    }
+    if order.status != OrderStatus::Open || order.order_type != OrderType::Limit {
+        return web::HttpResponse::Conflict().json("Only open limit orders can be amended");
+    }
+    if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &order.market_id, true).await {
+        return response;
+    }
+    if let Some(response) = crate::handlers::markets::reject_if_trading_closed(&conn, &order.market_id).await {
+        return response;
+    }
+
+    let price = match req.price.as_deref().map(parse_price).transpose() {
+        Ok(price) => price.unwrap_or(order.price),
+        Err(e) => return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e)),
+    };
+    let remaining = match parse_amount(&req.amount) {
+        Ok(amount) => amount,
+        Err(e) => return web::HttpResponse::BadRequest().json(format!("Validation error: {}", e)),
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let keeps_priority = price == order.price && remaining <= order.amount - order.filled_amount;
+
+    let mut amended: orders::ActiveModel = order.clone().into();
+    amended.price = ActiveValue::Set(price);
+    amended.amount = ActiveValue::Set(order.filled_amount + remaining);
+    amended.updated_at = ActiveValue::Set(now);
+    if !keeps_priority {
+        // The book queues orders at a price by creation time
+        amended.created_at = ActiveValue::Set(now);
+    }
+
+    match amended.update(&**conn).await {
+        Ok(order) => web::HttpResponse::Ok().json(order_response(order)),
+        Err(e) => {
+            eprintln!("Error amending order: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Places and cancels many of one user's orders in a market in one request. Each item
+/// succeeds or fails on its own; cancels run first so their funds are free for the new orders.
+#[post("/markets/{market_id}/orders/batch")]
+pub async fn batch_orders(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<BatchOrderRequest>) -> impl Responder {
+    let market_id = market_id.into_inner();
+    if !req.place.is_empty() {
+        if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &market_id, true).await {
+            return response;
+        }
+        if let Some(response) = crate::handlers::markets::reject_if_trading_closed(&conn, &market_id).await {
+            return response;
+        }
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let mut cancelled = Vec::with_capacity(req.cancel.len());
+    for &order_id in &req.cancel {
+        cancelled.push(match cancel_one(&conn, &market_id, &req.user_address, order_id, now).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error cancelling order {}: {}", order_id, e);
+                BatchItemResult::err("Internal error")
+            }
+        });
+    }
+
+    let mut placed = Vec::with_capacity(req.place.len());
+    for item in &req.place {
+        placed.push(match place_one(&conn, &market_id, &req.user_address, item, now).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error creating order: {}", e);
+                BatchItemResult::err("Internal error")
+            }
+        });
+    }
+
+    web::HttpResponse::Ok().json(BatchOrderResponse { placed, cancelled })
+}
+
+async fn cancel_one(conn: &DatabaseConnection, market_id: &str, user_address: &str, order_id: i32, now: i64) -> Result<BatchItemResult, DbErr> {
+    let Some(order) = orders::Entity::find_by_id(order_id).one(conn).await? else {
+        return Ok(BatchItemResult::err(format!("Order not found: {}", order_id)));
+    };
+    if order.market_id != market_id {
+        return Ok(BatchItemResult::err(format!("Order {} is in another market", order_id)));
+    }
+    if !order.user_address.eq_ignore_ascii_case(user_address) {
+        return Ok(BatchItemResult::err(format!("Order {} belongs to another user", order_id)));
+    }
+    if !matches!(order.status, OrderStatus::Open | OrderStatus::Pending) {
+        return Ok(BatchItemResult::err(format!("Order {} is {:?} and can no longer be cancelled", order_id, order.status)));
+    }
+    let mut cancelled: orders::ActiveModel = order.into();
+    cancelled.status = ActiveValue::Set(OrderStatus::Cancelled);
+    cancelled.updated_at = ActiveValue::Set(now);
+    let order = cancelled.update(conn).await?;
+    Ok(BatchItemResult::ok(order_response(order)))
+}
+
+async fn place_one(conn: &DatabaseConnection, market_id: &str, user_address: &str, req: &CreateOrderRequest, now: i64) -> Result<BatchItemResult, DbErr> {
+    if req.market_id != market_id {
+        return Ok(BatchItemResult::err("Order is for another market"));
+    }
+    if !req.user_address.eq_ignore_ascii_case(user_address) {
+        return Ok(BatchItemResult::err("Order is for another user"));
+    }
+    let side = match OrderSide::from_str(&req.side) {
+        Ok(side) => side,
+        Err(_) => return Ok(BatchItemResult::err(format!("Invalid side: {}", req.side))),
+    };
+    let amount = match parse_amount(&req.amount) {
+        Ok(amount) => amount,
+        Err(e) => return Ok(BatchItemResult::err(e)),
+    };
+    let options = match parse_order_options(req, now) {
+        Ok(options) => options,
+        Err(e) => return Ok(BatchItemResult::err(e)),
+    };
+
+    let new_order = orders::ActiveModel {
+        id: ActiveValue::NotSet,
+        market_id: ActiveValue::Set(req.market_id.clone()),
+        user_address: ActiveValue::Set(req.user_address.clone()),
+        is_yes: ActiveValue::Set(req.is_yes),
+        side: ActiveValue::Set(side),
+        price: ActiveValue::Set(options.price),
+        amount: ActiveValue::Set(amount),
+        filled_amount: ActiveValue::Set(Decimal::from(0)),
+        status: ActiveValue::Set(if options.stop_price.is_some() { OrderStatus::Pending } else { OrderStatus::Open }),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        tx_hash: ActiveValue::Set(None),
+        order_type: ActiveValue::Set(options.order_type),
+        time_in_force: ActiveValue::Set(options.time_in_force),
+        expires_at: ActiveValue::Set(req.expires_at),
+        post_only: ActiveValue::Set(req.post_only),
+        reduce_only: ActiveValue::Set(req.reduce_only),
+        stop_price: ActiveValue::Set(options.stop_price),
+        max_slippage: ActiveValue::Set(options.max_slippage),
+    };
+    let order = new_order.insert(conn).await?;
+    Ok(BatchItemResult::ok(order_response(order)))
+}
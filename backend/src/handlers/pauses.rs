@@ -0,0 +1,255 @@
+use actix_web::{web, HttpResponse, Responder, get, post};
+use sea_orm::{DatabaseConnection, DbErr, entity::*, query::*, ActiveValue};
+use serde::{Serialize, Deserialize};
+use validator::Validate;
+use std::env;
+
+use crate::schema::{markets, pause_events, PauseAction};
+
+#[derive(Serialize, Deserialize)]
+struct PauseEventResponse {
+    id: i32,
+    market_id: Option<String>,
+    action: String,
+    actor_address: String,
+    reason: String,
+    halted_until: Option<i64>,
+    created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct PauseRequest {
+    #[validate(length(min = 1))]
+    pauser_address: String,
+    #[serde(default)]
+    reason: String,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct HaltRequest {
+    #[validate(length(min = 1))]
+    pauser_address: String,
+    #[validate(range(min = 1))]
+    halted_until: i64,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Pausers are configured as a comma-separated `PAUSER_ADDRESSES` list.
+fn is_pauser(address: &str) -> bool {
+    env::var("PAUSER_ADDRESSES")
+        .unwrap_or_default()
+        .split(',')
+        .any(|pauser| pauser.trim().eq_ignore_ascii_case(address))
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// The factory is paused if its latest factory-wide event is a pause.
+async fn is_factory_paused(conn: &DatabaseConnection) -> Result<bool, DbErr> {
+    let latest = pause_events::Entity::find()
+        .filter(pause_events::Column::MarketId.is_null())
+        .order_by_desc(pause_events::Column::Id)
+        .one(conn)
+        .await?;
+    Ok(matches!(latest, Some(event) if event.action == PauseAction::Paused))
+}
+
+async fn log_pause_event(
+    conn: &DatabaseConnection,
+    market_id: Option<String>,
+    action: PauseAction,
+    actor_address: String,
+    reason: String,
+    halted_until: Option<i64>,
+) -> Result<(), DbErr> {
+    let event = pause_events::ActiveModel {
+        id: ActiveValue::NotSet,
+        market_id: ActiveValue::Set(market_id),
+        action: ActiveValue::Set(action),
+        actor_address: ActiveValue::Set(actor_address),
+        reason: ActiveValue::Set(reason),
+        halted_until: ActiveValue::Set(halted_until),
+        created_at: ActiveValue::Set(now()),
+    };
+    pause_events::Entity::insert(event).exec(conn).await?;
+    Ok(())
+}
+
+/// Returns the response to send if the market can't currently be used: `423 Locked` while
+/// the market or the factory is paused, or, with `check_halt`, while a circuit breaker
+/// halt is in effect.
+pub(crate) async fn reject_if_paused(conn: &DatabaseConnection, market_id: &str, check_halt: bool) -> Option<HttpResponse> {
+    let factory_paused = match is_factory_paused(conn).await {
+        Ok(paused) => paused,
+        Err(e) => {
+            eprintln!("Error checking factory pause: {}", e);
+            return Some(HttpResponse::InternalServerError().finish());
+        }
+    };
+    if factory_paused {
+        return Some(HttpResponse::Locked().json("Market is paused"));
+    }
+
+    match markets::Entity::find_by_id(market_id.to_string()).one(conn).await {
+        Ok(Some(market)) => {
+            if market.paused {
+                return Some(HttpResponse::Locked().json("Market is paused"));
+            }
+            match market.halted_until {
+                Some(halted_until) if check_halt && halted_until > now() => {
+                    Some(HttpResponse::Locked().json(format!("Trading is halted by the circuit breaker until {}", halted_until)))
+                }
+                _ => None,
+            }
+        }
+        Ok(None) => Some(HttpResponse::NotFound().json(format!("Market not found: {}", market_id))),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            Some(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+async fn set_factory_paused(conn: &DatabaseConnection, req: &PauseRequest, paused: bool) -> HttpResponse {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    if !is_pauser(&req.pauser_address) {
+        return HttpResponse::Forbidden().json("Only a pauser can pause or unpause");
+    }
+
+    let action = if paused { PauseAction::Paused } else { PauseAction::Unpaused };
+    match log_pause_event(conn, None, action, req.pauser_address.clone(), req.reason.clone(), None).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => {
+            eprintln!("Error logging pause event: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Applies `update` to a market on behalf of a pauser and logs the matching pause event.
+pub(crate) async fn update_market_halt(
+    conn: &DatabaseConnection,
+    market_id: String,
+    pauser_address: &str,
+    update: impl FnOnce(&mut markets::ActiveModel),
+    action: PauseAction,
+    reason: String,
+    halted_until: Option<i64>,
+) -> HttpResponse {
+    if !is_pauser(pauser_address) {
+        return HttpResponse::Forbidden().json("Only a pauser can pause or unpause");
+    }
+
+    let market = match markets::Entity::find_by_id(market_id.clone()).one(conn).await {
+        Ok(Some(market)) => market,
+        Ok(None) => return HttpResponse::NotFound().finish(),
+        Err(e) => {
+            eprintln!("Error fetching market: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+    let mut market: markets::ActiveModel = market.into();
+    update(&mut market);
+    market.updated_at = ActiveValue::Set(now());
+    if let Err(e) = market.update(conn).await {
+        eprintln!("Error updating market: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match log_pause_event(conn, Some(market_id), action, pauser_address.to_string(), reason, halted_until).await {
+        Ok(()) => HttpResponse::Ok().finish(),
+        Err(e) => {
+            eprintln!("Error logging pause event: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/pause")]
+pub async fn pause_factory(conn: web::Data<DatabaseConnection>, req: web::Json<PauseRequest>) -> impl Responder {
+    set_factory_paused(&conn, &req, true).await
+}
+
+#[post("/unpause")]
+pub async fn unpause_factory(conn: web::Data<DatabaseConnection>, req: web::Json<PauseRequest>) -> impl Responder {
+    set_factory_paused(&conn, &req, false).await
+}
+
+#[post("/markets/{market_id}/pause")]
+pub async fn pause_market(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<PauseRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    update_market_halt(&conn, market_id.into_inner(), &req.pauser_address, |market| {
+        market.paused = ActiveValue::Set(true);
+    }, PauseAction::Paused, req.reason.clone(), None).await
+}
+
+#[post("/markets/{market_id}/unpause")]
+pub async fn unpause_market(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<PauseRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    update_market_halt(&conn, market_id.into_inner(), &req.pauser_address, |market| {
+        market.paused = ActiveValue::Set(false);
+    }, PauseAction::Unpaused, req.reason.clone(), None).await
+}
+
+/// Records a circuit breaker halt reported by the market contract.
+#[post("/markets/{market_id}/halt")]
+pub async fn halt_market(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<HaltRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let halted_until = req.halted_until;
+    update_market_halt(&conn, market_id.into_inner(), &req.pauser_address, |market| {
+        market.halted_until = ActiveValue::Set(Some(halted_until));
+    }, PauseAction::TradingHalted, req.reason.clone(), Some(halted_until)).await
+}
+
+#[post("/markets/{market_id}/resume")]
+pub async fn resume_market(conn: web::Data<DatabaseConnection>, market_id: web::Path<String>, req: web::Json<PauseRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    update_market_halt(&conn, market_id.into_inner(), &req.pauser_address, |market| {
+        market.halted_until = ActiveValue::Set(None);
+    }, PauseAction::TradingResumed, req.reason.clone(), None).await
+}
+
+#[get("/pause-events")]
+pub async fn get_pause_events(conn: web::Data<DatabaseConnection>) -> impl Responder {
+    let result = pause_events::Entity::find()
+        .order_by_desc(pause_events::Column::Id)
+        .all(&**conn)
+        .await;
+
+    match result {
+        Ok(events) => {
+            let event_responses: Vec<PauseEventResponse> = events.into_iter().map(|event| {
+                PauseEventResponse {
+                    id: event.id,
+                    market_id: event.market_id,
+                    action: event.action.to_string(),
+                    actor_address: event.actor_address,
+                    reason: event.reason,
+                    halted_until: event.halted_until,
+                    created_at: event.created_at.to_string(),
+                }
+            }).collect();
+            web::Json(event_responses)
+        }
+        Err(e) => {
+            eprintln!("Error fetching pause events: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use actix_web::{web, HttpResponse, Responder, get, post};
+use sea_orm::{Condition, DatabaseConnection, DbErr, entity::*, QueryOrder, ActiveValue};
+use sea_orm::sea_query::{Expr, Func};
+use serde::{Serialize, Deserialize};
+use validator::Validate;
+use rust_decimal::Decimal;
+
+use crate::schema::{referrals, trades};
+
+#[derive(Serialize, Deserialize, Validate)]
+struct RegisterReferrerRequest {
+    #[validate(length(min = 1))]
+    referrer_address: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReferredUserResponse {
+    user_address: String,
+    registered_at: String,
+    linked_at: Option<String>, // None until the user's first trade
+    fees_earned: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReferralStatsResponse {
+    user_address: String,
+    referrer_address: Option<String>, // Who referred this user
+    referred_users: Vec<ReferredUserResponse>,
+    linked_count: usize,
+    total_fees_earned: String,
+}
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// The user's first trade on either side, if any. Trades keep addresses as submitted, so
+/// they are compared case-insensitively.
+async fn first_trade(conn: &DatabaseConnection, user_address: &str) -> Result<Option<trades::Model>, DbErr> {
+    let user_address = user_address.to_lowercase();
+    let lower = |column: trades::Column| Expr::expr(Func::lower(Expr::col(column)));
+    trades::Entity::find()
+        .filter(
+            Condition::any()
+                .add(lower(trades::Column::MakerAddress).eq(user_address.clone()))
+                .add(lower(trades::Column::TakerAddress).eq(user_address)),
+        )
+        .order_by_asc(trades::Column::CreatedAt)
+        .one(conn)
+        .await
+}
+
+/// Records the link once the referred user has traded. Returns the referral as stored.
+async fn link_on_first_trade(conn: &DatabaseConnection, referral: referrals::Model) -> Result<referrals::Model, DbErr> {
+    if referral.linked_at.is_some() {
+        return Ok(referral);
+    }
+    match first_trade(conn, &referral.user_address).await? {
+        Some(trade) => {
+            let mut referral: referrals::ActiveModel = referral.into();
+            referral.linked_at = ActiveValue::Set(Some(trade.created_at));
+            referral.update(conn).await
+        }
+        None => Ok(referral),
+    }
+}
+
+/// Registers who referred the user. Only possible before the user's first trade, once, and
+/// not to themselves or to someone they referred.
+#[post("/users/{user_address}/referrer")]
+pub async fn register_referrer(conn: web::Data<DatabaseConnection>, user_address: web::Path<String>, req: web::Json<RegisterReferrerRequest>) -> impl Responder {
+    if let Err(e) = req.validate() {
+        return HttpResponse::BadRequest().json(format!("Validation error: {}", e));
+    }
+    let user_address = user_address.into_inner().to_lowercase();
+    let referrer_address = req.referrer_address.to_lowercase();
+    if user_address == referrer_address {
+        return HttpResponse::BadRequest().json("Users can't refer themselves");
+    }
+
+    let checks: Result<Option<HttpResponse>, DbErr> = async {
+        if referrals::Entity::find_by_id(user_address.clone()).one(&**conn).await?.is_some() {
+            return Ok(Some(HttpResponse::Conflict().json("User already has a referrer")));
+        }
+        let referrer = referrals::Entity::find_by_id(referrer_address.clone()).one(&**conn).await?;
+        if referrer.is_some_and(|referral| referral.referrer_address == user_address) {
+            return Ok(Some(HttpResponse::BadRequest().json("Users can't refer their own referrer")));
+        }
+        if first_trade(&conn, &user_address).await?.is_some() {
+            return Ok(Some(HttpResponse::Conflict().json("Referrers must be registered before the first trade")));
+        }
+        Ok(None)
+    }.await;
+    match checks {
+        Ok(Some(response)) => return response,
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("Error checking referral: {}", e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    }
+
+    let referral = referrals::ActiveModel {
+        user_address: ActiveValue::Set(user_address),
+        referrer_address: ActiveValue::Set(referrer_address),
+        registered_at: ActiveValue::Set(now()),
+        linked_at: ActiveValue::Set(None),
+    };
+    match referrals::Entity::insert(referral).exec(&**conn).await {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(e) => {
+            eprintln!("Error registering referrer: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// The user's referrer, the users they referred and the taker fees credited to them.
+#[get("/users/{user_address}/referrals")]
+pub async fn get_referral_stats(conn: web::Data<DatabaseConnection>, user_address: web::Path<String>) -> impl Responder {
+    let user_address = user_address.into_inner().to_lowercase();
+    let result: Result<ReferralStatsResponse, DbErr> = async {
+        let referrer_address = referrals::Entity::find_by_id(user_address.clone())
+            .one(&**conn)
+            .await?
+            .map(|referral| referral.referrer_address);
+        let referred = referrals::Entity::find()
+            .filter(referrals::Column::ReferrerAddress.eq(user_address.clone()))
+            .order_by_asc(referrals::Column::RegisteredAt)
+            .all(&**conn)
+            .await?;
+
+        let mut referred_users = Vec::with_capacity(referred.len());
+        let mut total_fees_earned = Decimal::ZERO;
+        for referral in referred {
+            let referral = link_on_first_trade(&conn, referral).await?;
+            let fees_earned: Decimal = match referral.linked_at {
+                Some(_) => trades::Entity::find()
+                    .filter(trades::Column::TakerAddress.eq(referral.user_address.clone()))
+                    .all(&**conn)
+                    .await?
+                    .iter()
+                    .map(|trade| trade.referral_fee)
+                    .sum(),
+                None => Decimal::ZERO,
+            };
+            total_fees_earned += fees_earned;
+            referred_users.push(ReferredUserResponse {
+                user_address: referral.user_address,
+                registered_at: referral.registered_at.to_string(),
+                linked_at: referral.linked_at.map(|linked_at| linked_at.to_string()),
+                fees_earned: fees_earned.to_string(),
+            });
+        }
+
+        Ok(ReferralStatsResponse {
+            user_address,
+            referrer_address,
+            linked_count: referred_users.iter().filter(|user| user.linked_at.is_some()).count(),
+            referred_users,
+            total_fees_earned: total_fees_earned.to_string(),
+        })
+    }.await;
+
+    match result {
+        Ok(stats) => HttpResponse::Ok().json(stats),
+        Err(e) => {
+            eprintln!("Error fetching referral stats: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -13,8 +13,15 @@ struct TradeResponse {
     side: String,
     price: String,
     amount: String,
+    taker_fee: String,
+    maker_rebate: String,
+    creator_fee: String,
     created_at: String,
     tx_hash: String,
+    flagged: bool,
+    kind: String,
+    wash_trade: bool,
+    referral_fee: String,
 }
 
 #[get("/markets/{market_id}/trades")]
@@ -36,8 +43,15 @@ pub async fn get_all_market_trades(conn: web::Data<DatabaseConnection>, market_i
                     side: trade.side.to_string(),
                     price: trade.price.to_string(),
                     amount: trade.amount.to_string(),
+                    taker_fee: trade.taker_fee.to_string(),
+                    maker_rebate: trade.maker_rebate.to_string(),
+                    creator_fee: trade.creator_fee.to_string(),
                     created_at: trade.created_at.to_string(),
                     tx_hash: trade.tx_hash,
+                    flagged: trade.flagged,
+                    kind: trade.kind.to_string(),
+                    wash_trade: trade.wash_trade,
+                    referral_fee: trade.referral_fee.to_string(),
                 }
             }).collect();
             web::Json(trade_responses)
@@ -3,6 +3,8 @@ use sea_orm::{DatabaseConnection, DbErr, entity::*};
 use serde::{Serialize, Deserialize};
 use validator::Validate;
 use std::sync::Arc;
+use rust_decimal::Decimal;
+use hyperliquid::HyperliquidClient;
 
 use crate::schema::user_positions;
 
@@ -23,6 +25,20 @@ struct ClaimWinningsRequest {
     market_id: String,
 }
 
+#[derive(Serialize, Deserialize)]
+struct MarketClaimResponse {
+    market_id: String,
+    payout: String,
+    tx_hash: Option<String>,
+    error: Option<String>, // Set when the claim in this market failed
+}
+
+#[derive(Serialize, Deserialize)]
+struct BatchClaimResponse {
+    claims: Vec<MarketClaimResponse>,
+    total_payout: String, // Over the successful claims
+}
+
 #[get("/users/{user_address}/positions")]
 pub async fn get_all_user_positions(conn: web::Data<DatabaseConnection>, user_address: web::Path<String>) -> impl Responder {
     let user_address = user_address.into_inner();
@@ -92,6 +108,9 @@ pub async fn claim_winnings(conn: web::Data<DatabaseConnection>, hl_client: web:
     }
     let user_address = user_address.into_inner();
     let market_id = req.market_id.clone();
+    if let Some(response) = crate::handlers::pauses::reject_if_paused(&conn, &market_id, false).await {
+        return response;
+    }
 
     let claim_result = hl_client.claim_winnings(user_address.clone(), market_id.clone()).await;
 
@@ -139,4 +158,37 @@ pub async fn claim_winnings(conn: web::Data<DatabaseConnection>, hl_client: web:
             web::HttpResponse::InternalServerError().json(format!("Error claiming winnings: {}", e))
         }
     }
-} 
\ No newline at end of file
+} 
+
+/// Claims the user's winnings in every resolved, unpaused market, reporting each market's
+/// payout or failure.
+#[post("/users/{user_address}/positions/claim-all")]
+pub async fn claim_all_winnings(conn: web::Data<DatabaseConnection>, hl_client: web::Data<Arc<HyperliquidClient>>, user_address: web::Path<String>) -> impl Responder {
+    let user_address = user_address.into_inner();
+    match crate::redemption::redeem_resolved(&conn, &hl_client, Some(&user_address)).await {
+        Ok(redemptions) => {
+            let total_payout: Decimal = redemptions
+                .iter()
+                .filter(|redemption| redemption.result.is_ok())
+                .map(|redemption| redemption.payout)
+                .sum();
+            let claims = redemptions.into_iter().map(|redemption| {
+                let (tx_hash, error) = match redemption.result {
+                    Ok(tx_hash) => (Some(tx_hash), None),
+                    Err(e) => (None, Some(e)),
+                };
+                MarketClaimResponse {
+                    market_id: redemption.market_id,
+                    payout: redemption.payout.to_string(),
+                    tx_hash,
+                    error,
+                }
+            }).collect();
+            web::HttpResponse::Ok().json(BatchClaimResponse { claims, total_payout: total_payout.to_string() })
+        }
+        Err(e) => {
+            eprintln!("Error claiming winnings: {}", e);
+            web::HttpResponse::InternalServerError().json(format!("Error claiming winnings: {}", e))
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use actix_web::{web, Responder, get};
-use sea_orm::{DatabaseConnection, DbErr, entity::*};
+use sea_orm::{DatabaseConnection, DbErr, entity::*, QueryOrder, QuerySelect};
 use serde::{Serialize, Deserialize};
 
 use crate::schema::user_stats;
@@ -15,6 +15,20 @@ struct UserStatsResponse {
     updated_at: String,
 }
 
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    limit: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LeaderboardEntry {
+    rank: usize,
+    user_address: String,
+    total_trades: i32,
+    total_volume: String,
+    total_pnl: String,
+}
+
 #[get("/users/{user_address}/stats")]
 pub async fn get_user_stats(conn: web::Data<DatabaseConnection>, user_address: web::Path<String>) -> impl Responder {
     let user_address = user_address.into_inner();
@@ -41,4 +55,35 @@ pub async fn get_user_stats(conn: web::Data<DatabaseConnection>, user_address: w
             web::HttpResponse::InternalServerError().finish()
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Users ranked by traded volume. Volume from flagged wash trades has already been taken out of
+/// `user_stats` by the wash-trading job.
+#[get("/leaderboard")]
+pub async fn get_leaderboard(conn: web::Data<DatabaseConnection>, query: web::Query<LeaderboardQuery>) -> impl Responder {
+    let limit = query.limit.unwrap_or(100).min(500);
+    let result = user_stats::Entity::find()
+        .order_by_desc(user_stats::Column::TotalVolume)
+        .limit(limit)
+        .all(&**conn)
+        .await;
+
+    match result {
+        Ok(stats) => {
+            let entries: Vec<LeaderboardEntry> = stats.into_iter().enumerate().map(|(i, stats)| {
+                LeaderboardEntry {
+                    rank: i + 1,
+                    user_address: stats.user_address,
+                    total_trades: stats.total_trades,
+                    total_volume: stats.total_volume.to_string(),
+                    total_pnl: stats.total_pnl.to_string(),
+                }
+            }).collect();
+            web::HttpResponse::Ok().json(entries)
+        }
+        Err(e) => {
+            eprintln!("Error fetching leaderboard: {}", e);
+            web::HttpResponse::InternalServerError().finish()
+        }
+    }
+}
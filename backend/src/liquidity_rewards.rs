@@ -0,0 +1,149 @@
+use sea_orm::{DatabaseConnection, DbErr, entity::*, ActiveValue};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+
+use crate::schema::{liquidity_reward_programs, liquidity_rewards, markets, orders, MarketStatus, OrderSide, OrderStatus, OrderType, RewardFormula};
+
+const DEFAULT_INTERVAL_SECS: u64 = 60;
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+/// Checks every `LIQUIDITY_REWARDS_INTERVAL_SECS` seconds for programs whose next sample is due.
+pub fn spawn(conn: DatabaseConnection) {
+    let interval = env::var("LIQUIDITY_REWARDS_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECS);
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            if let Err(e) = sample_due(&conn, now()).await {
+                eprintln!("Error sampling liquidity rewards: {}", e);
+            }
+        }
+    });
+}
+
+/// Samples every funded program of an active, unpaused market whose interval has passed since
+/// its last sample.
+pub async fn sample_due(conn: &DatabaseConnection, now: i64) -> Result<(), DbErr> {
+    let programs = liquidity_reward_programs::Entity::find()
+        .filter(liquidity_reward_programs::Column::Budget.gt(Decimal::ZERO))
+        .all(conn)
+        .await?;
+    for program in programs {
+        if program.last_sampled_at.is_some_and(|sampled_at| now < sampled_at + program.sample_interval) {
+            continue;
+        }
+        let tradable = markets::Entity::find_by_id(program.market_id.clone())
+            .one(conn)
+            .await?
+            .is_some_and(|market| market.status == MarketStatus::Active && !market.paused);
+        if tradable {
+            sample(conn, program, now).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Scores the market's open orders and accrues `per_sample`, or what is left of the budget,
+/// to their owners by score.
+async fn sample(conn: &DatabaseConnection, program: liquidity_reward_programs::Model, now: i64) -> Result<(), DbErr> {
+    let open_orders = orders::Entity::find()
+        .filter(orders::Column::MarketId.eq(program.market_id.clone()))
+        .filter(orders::Column::Status.eq(OrderStatus::Open))
+        .filter(orders::Column::OrderType.eq(OrderType::Limit))
+        .all(conn)
+        .await?;
+    let scores = score(&program, &open_orders);
+    let total: Decimal = scores.values().sum();
+    let payout = program.per_sample.min(program.budget);
+
+    let mut distributed = Decimal::ZERO;
+    if total > Decimal::ZERO {
+        for (user_address, score) in scores {
+            let share = (payout * score / total).round_dp(6);
+            distributed += share;
+            let existing = liquidity_rewards::Entity::find()
+                .filter(liquidity_rewards::Column::MarketId.eq(program.market_id.clone()))
+                .filter(liquidity_rewards::Column::UserAddress.eq(user_address.clone()))
+                .one(conn)
+                .await?;
+            match existing {
+                Some(reward) => {
+                    let (total_score, accrued) = (reward.total_score + score, reward.accrued + share);
+                    let mut reward: liquidity_rewards::ActiveModel = reward.into();
+                    reward.last_score = ActiveValue::Set(score);
+                    reward.total_score = ActiveValue::Set(total_score);
+                    reward.accrued = ActiveValue::Set(accrued);
+                    reward.updated_at = ActiveValue::Set(now);
+                    reward.update(conn).await?;
+                }
+                None => {
+                    let reward = liquidity_rewards::ActiveModel {
+                        id: ActiveValue::NotSet,
+                        market_id: ActiveValue::Set(program.market_id.clone()),
+                        user_address: ActiveValue::Set(user_address),
+                        last_score: ActiveValue::Set(score),
+                        total_score: ActiveValue::Set(score),
+                        accrued: ActiveValue::Set(share),
+                        claimed: ActiveValue::Set(Decimal::ZERO),
+                        updated_at: ActiveValue::Set(now),
+                    };
+                    liquidity_rewards::Entity::insert(reward).exec(conn).await?;
+                }
+            }
+        }
+    }
+
+    let remaining = (program.budget - distributed).max(Decimal::ZERO);
+    let mut program: liquidity_reward_programs::ActiveModel = program.into();
+    program.budget = ActiveValue::Set(remaining);
+    program.last_sampled_at = ActiveValue::Set(Some(now));
+    program.update(conn).await?;
+    Ok(())
+}
+
+/// Scores per address: the unfilled size of each order within `max_spread` of the midpoint,
+/// weighted by its closeness under the program's formula. Orders count in YES terms, so a NO
+/// bid at 45 scores as a YES ask at 55. Nothing scores on a one-sided book.
+fn score(program: &liquidity_reward_programs::Model, open_orders: &[orders::Model]) -> HashMap<String, Decimal> {
+    let hundred = Decimal::from(100);
+    // (order, YES price, bids on YES)
+    let quotes: Vec<(&orders::Model, Decimal, bool)> = open_orders
+        .iter()
+        .map(|o| {
+            let buys = o.side == OrderSide::Buy;
+            if o.is_yes { (o, o.price, buys) } else { (o, hundred - o.price, !buys) }
+        })
+        .collect();
+    let best = |bid: bool| quotes.iter().filter(move |q| q.2 == bid).map(|q| q.1);
+    let (Some(best_bid), Some(best_ask)) = (best(true).max(), best(false).min()) else {
+        return HashMap::new();
+    };
+    let midpoint = (best_bid + best_ask) / Decimal::from(2);
+
+    let mut scores: HashMap<String, Decimal> = HashMap::new();
+    for (order, yes_price, _) in quotes {
+        let remaining = order.amount - order.filled_amount;
+        let distance = (yes_price - midpoint).abs();
+        if remaining <= Decimal::ZERO || remaining < program.min_size || distance >= program.max_spread {
+            continue;
+        }
+        let closeness = (program.max_spread - distance) / program.max_spread;
+        let weight = match program.formula {
+            RewardFormula::Linear => closeness,
+            RewardFormula::Quadratic => closeness * closeness,
+        };
+        *scores.entry(order.user_address.to_lowercase()).or_default() += remaining * weight;
+    }
+    scores
+}
@@ -6,6 +6,9 @@ use hyperliquid::{HyperliquidClient, HyperliquidConfig};
 
 mod schema;
 mod handlers;
+mod liquidity_rewards;
+mod redemption;
+mod wash_trading;
 
 pub struct AppState {
     conn: DatabaseConnection,
@@ -32,9 +35,13 @@ async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
 
     let conn = establish_connection().await.expect("Failed to connect to database");
-    let hl_client = establish_hyperliquid_client().await.expect("Failed to connect to hyperliquid");
+    let hl_client = Arc::new(establish_hyperliquid_client().await.expect("Failed to connect to hyperliquid"));
 
-    let app_state = web::Data::new(AppState { conn, hl_client: Arc::new(hl_client) });
+    wash_trading::spawn(conn.clone());
+    liquidity_rewards::spawn(conn.clone());
+    redemption::spawn(conn.clone(), hl_client.clone());
+
+    let app_state = web::Data::new(AppState { conn, hl_client });
 
     HttpServer::new(move || {
         App::new()
@@ -47,18 +54,44 @@ async fn main() -> std::io::Result<()> {
                     .service(handlers::markets::create_market)
                     .service(handlers::markets::update_market)
                     .service(handlers::markets::delete_market)
+                    .service(handlers::markets::get_market_metadata_history)
+                    .service(handlers::market_groups::get_all_groups)
+                    .service(handlers::market_groups::get_group_by_id)
+                    .service(handlers::market_groups::create_group)
+                    .service(handlers::market_groups::resolve_group)
+                    .service(handlers::market_groups::convert_negative_risk)
+                    .service(handlers::pauses::pause_factory)
+                    .service(handlers::pauses::unpause_factory)
+                    .service(handlers::pauses::pause_market)
+                    .service(handlers::pauses::unpause_market)
+                    .service(handlers::pauses::halt_market)
+                    .service(handlers::pauses::resume_market)
+                    .service(handlers::batch_auctions::start_batch_auction)
+                    .service(handlers::batch_auctions::end_batch_auction)
+                    .service(handlers::pauses::get_pause_events)
                     .service(handlers::user_positions::get_all_user_positions)
                     .service(handlers::user_positions::get_user_position_by_market)
                     .service(handlers::orders::get_all_market_orders)
                     .service(handlers::orders::get_order_by_id)
                     .service(handlers::orders::create_order)
                     .service(handlers::orders::delete_order)
+                    .service(handlers::orders::amend_order)
+                    .service(handlers::orders::batch_orders)
                     .service(handlers::trades::get_all_market_trades)
+                    .service(handlers::liquidity_rewards::get_market_rewards)
+                    .service(handlers::liquidity_rewards::configure_rewards)
+                    .service(handlers::liquidity_rewards::claim_rewards)
                     .service(handlers::user_stats::get_user_stats)
+                    .service(handlers::user_stats::get_leaderboard)
+                    .service(handlers::referrals::register_referrer)
+                    .service(handlers::referrals::get_referral_stats)
                     .service(handlers::markets::mint_tokens)
                     .service(handlers::markets::burn_tokens)
                     .service(handlers::markets::resolve_market)
+                    .service(handlers::markets::propose_early_resolution)
+                    .service(handlers::markets::cancel_early_resolution)
                     .service(handlers::user_positions::claim_winnings)
+                    .service(handlers::user_positions::claim_all_winnings)
             )
     })
     .bind(("127.0.0.1", 8080))?
@@ -0,0 +1,83 @@
+use sea_orm::{DatabaseConnection, DbErr, entity::*, ActiveValue};
+use rust_decimal::Decimal;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use hyperliquid::HyperliquidClient;
+
+use crate::schema::{markets, user_positions, MarketStatus};
+
+/// The outcome of redeeming one position.
+pub struct Redemption {
+    pub market_id: String,
+    pub payout: Decimal, // Winning tokens redeemed, before the redemption fee
+    pub result: Result<String, String>, // Transaction hash, or why the claim failed
+}
+
+/// Redeems resolved positions every `AUTO_REDEEM_INTERVAL_SECS` seconds. Off unless the
+/// variable is set.
+pub fn spawn(conn: DatabaseConnection, hl_client: Arc<HyperliquidClient>) {
+    let Some(interval) = env::var("AUTO_REDEEM_INTERVAL_SECS").ok().and_then(|v| v.parse().ok()) else {
+        return;
+    };
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            match redeem_resolved(&conn, &hl_client, None).await {
+                Ok(redemptions) => {
+                    for redemption in redemptions {
+                        if let Err(e) = redemption.result {
+                            eprintln!("Error auto-redeeming market {}: {}", redemption.market_id, e);
+                        }
+                    }
+                }
+                Err(e) => eprintln!("Error auto-redeeming positions: {}", e),
+            }
+        }
+    });
+}
+
+/// Claims the winning side of every position in a resolved, unpaused market, for one user or
+/// for everyone. A failed claim is reported and doesn't stop the rest.
+pub async fn redeem_resolved(conn: &DatabaseConnection, hl_client: &HyperliquidClient, user_address: Option<&str>) -> Result<Vec<Redemption>, DbErr> {
+    let markets = markets::Entity::find()
+        .filter(markets::Column::Status.eq(MarketStatus::Resolved))
+        .filter(markets::Column::Paused.eq(false))
+        .all(conn)
+        .await?;
+
+    let mut redemptions = Vec::new();
+    for market in markets {
+        let Some(outcome) = market.resolved_outcome else {
+            continue;
+        };
+        let winning = if outcome { user_positions::Column::YesTokens } else { user_positions::Column::NoTokens };
+        let mut query = user_positions::Entity::find()
+            .filter(user_positions::Column::MarketId.eq(market.id.clone()))
+            .filter(winning.gt(Decimal::ZERO));
+        if let Some(user_address) = user_address {
+            query = query.filter(user_positions::Column::UserAddress.eq(user_address));
+        }
+
+        for position in query.all(conn).await? {
+            let payout = if outcome { position.yes_tokens } else { position.no_tokens };
+            let result = hl_client
+                .claim_winnings(position.user_address.clone(), market.id.clone())
+                .await
+                .map(|tx_hash| tx_hash.to_string())
+                .map_err(|e| e.to_string());
+            if result.is_ok() {
+                let mut position: user_positions::ActiveModel = position.into();
+                if outcome {
+                    position.yes_tokens = ActiveValue::Set(Decimal::ZERO);
+                } else {
+                    position.no_tokens = ActiveValue::Set(Decimal::ZERO);
+                }
+                position.update(conn).await?;
+            }
+            redemptions.push(Redemption { market_id: market.id.clone(), payout, result });
+        }
+    }
+    Ok(redemptions)
+}
@@ -9,6 +9,7 @@ pub struct Model {
     pub expiry_timestamp: i64,
     pub oracle_id: String,
     pub collateral_token: String,
+    pub resolution_source: String,
     pub status: MarketStatus,
     pub yes_token_address: String,
     pub no_token_address: String,
@@ -18,6 +19,23 @@ pub struct Model {
     pub total_volume: Decimal,
     pub current_price: Decimal,
     pub creator_address: String,
+    pub description: String,
+    pub rules: String,
+    pub resolution_source_urls: Vec<String>,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub image_url: Option<String>,
+    pub group_id: Option<String>,
+    pub parent_market_id: Option<String>,
+    pub parent_required_outcome: Option<bool>,
+    pub paused: bool,
+    pub halted_until: Option<i64>, // Circuit breaker halt, as a Unix timestamp
+    pub trading_close_timestamp: Option<i64>, // No trading from this time on; None trades until resolution
+    pub resolution_timestamp: Option<i64>, // Earliest regular resolution; None means at expiry
+    pub proposed_outcome: Option<bool>, // Pending early resolution proposed by the oracle
+    pub event_timestamp: Option<i64>, // When the proposed outcome actually became known
+    pub batch_auction_interval: Option<i64>, // Seconds between batch clearings; None matches continuously
+    pub batch_auction_ends_at: Option<i64>, // When batch auction mode hands over to continuous matching
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -58,6 +76,7 @@ pub struct Order {
     pub id: i32,
     pub market_id: String,
     pub user_address: String,
+    pub is_yes: bool, // Which outcome's book the order rests on
     pub side: OrderSide,
     pub price: Decimal,
     pub amount: Decimal,
@@ -66,6 +85,13 @@ pub struct Order {
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
     pub tx_hash: Option<String>,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    pub expires_at: Option<i64>, // Good-till-time expiry, as a Unix timestamp
+    pub post_only: bool,
+    pub reduce_only: bool,
+    pub stop_price: Option<Decimal>, // Held as Pending until the last trade price reaches it
+    pub max_slippage: Option<Decimal>, // Market orders only, in cents from the best price
     #[sea_orm(relation = "ManyToOne", from = "Column::MarketId", to = "super::markets::Column::Id")]
     pub market: RelationDef,
 }
@@ -89,8 +115,15 @@ pub struct Trade {
     pub side: OrderSide,
     pub price: Decimal,
     pub amount: Decimal,
+    pub taker_fee: Decimal,
+    pub maker_rebate: Decimal,
+    pub creator_fee: Decimal,
     pub created_at: DateTimeWithTimeZone,
     pub tx_hash: String,
+    pub flagged: bool, // Executed after the event time of an early resolution
+    pub kind: TradeKind,
+    pub wash_trade: bool, // Self-trade or circular trading; excluded from volume and leaderboard
+    pub referral_fee: Decimal, // Share of the taker fee credited to the taker's referrer
     #[sea_orm(relation = "ManyToOne", from = "Column::MarketId", to = "super::markets::Column::Id")]
     pub market: RelationDef,
 }
@@ -103,6 +136,69 @@ pub enum TradeRelation {
 
 impl ActiveModelBehavior for Trade {}
 
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "market_metadata_revisions")]
+pub struct MarketMetadataRevision {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub market_id: String,
+    pub editor_address: String,
+    pub description: String,
+    pub rules: String,
+    pub resolution_source: String,
+    pub resolution_source_urls: Vec<String>,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub image_url: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    #[sea_orm(relation = "ManyToOne", from = "Column::MarketId", to = "super::markets::Column::Id")]
+    pub market: RelationDef,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum MarketMetadataRevisionRelation {
+    #[sea_orm(belongs_to = "super::markets::Entity", from = "Column::MarketId", to = "super::markets::Column::Id")]
+    Market,
+}
+
+impl ActiveModelBehavior for MarketMetadataRevision {}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "market_groups")]
+pub struct MarketGroup {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub title: String,
+    pub creator_address: String,
+    pub winning_market_id: Option<String>,
+    pub converted_amount: Decimal,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum MarketGroupRelation {}
+
+impl ActiveModelBehavior for MarketGroup {}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "pause_events")]
+pub struct PauseEvent {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub market_id: Option<String>, // None for factory-wide pauses
+    pub action: PauseAction,
+    pub actor_address: String,
+    pub reason: String,
+    pub halted_until: Option<i64>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum PauseEventRelation {}
+
+impl ActiveModelBehavior for PauseEvent {}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "user_stats")]
 pub struct UserStats {
@@ -121,6 +217,75 @@ pub enum UserStatsRelation {}
 
 impl ActiveModelBehavior for UserStats {}
 
+/// Who referred a user. Registered before the user's first trade and linked by it.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "referrals")]
+pub struct Referral {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_address: String,
+    pub referrer_address: String,
+    pub registered_at: DateTimeWithTimeZone,
+    pub linked_at: Option<DateTimeWithTimeZone>, // Time of the user's first trade
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum ReferralRelation {}
+
+impl ActiveModelBehavior for Referral {}
+
+/// A market's liquidity mining program. Open orders near the midpoint are scored every
+/// `sample_interval` seconds and `per_sample` of the budget is split between them by score.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "liquidity_reward_programs")]
+pub struct LiquidityRewardProgram {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub market_id: String,
+    pub budget: Decimal, // Not yet accrued to anyone
+    pub per_sample: Decimal,
+    pub sample_interval: i64,
+    pub max_spread: Decimal, // Cents either side of the midpoint
+    pub min_size: Decimal,
+    pub formula: RewardFormula,
+    pub last_sampled_at: Option<i64>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(relation = "ManyToOne", from = "Column::MarketId", to = "super::markets::Column::Id")]
+    pub market: RelationDef,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum LiquidityRewardProgramRelation {
+    #[sea_orm(belongs_to = "super::markets::Entity", from = "Column::MarketId", to = "super::markets::Column::Id")]
+    Market,
+}
+
+impl ActiveModelBehavior for LiquidityRewardProgram {}
+
+/// One address's liquidity rewards in one market.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "liquidity_rewards")]
+pub struct LiquidityReward {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub market_id: String,
+    pub user_address: String,
+    pub last_score: Decimal, // From the latest sample
+    pub total_score: Decimal,
+    pub accrued: Decimal, // Not yet claimed
+    pub claimed: Decimal,
+    pub updated_at: DateTimeWithTimeZone,
+    #[sea_orm(relation = "ManyToOne", from = "Column::MarketId", to = "super::markets::Column::Id")]
+    pub market: RelationDef,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum LiquidityRewardRelation {
+    #[sea_orm(belongs_to = "super::markets::Entity", from = "Column::MarketId", to = "super::markets::Column::Id")]
+    Market,
+}
+
+impl ActiveModelBehavior for LiquidityReward {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "String(None)")]
 pub enum MarketStatus {
@@ -130,6 +295,10 @@ pub enum MarketStatus {
     Expired,
     #[sea_orm(string_value = "Resolved")]
     Resolved,
+    #[sea_orm(string_value = "Locked")]
+    Locked,
+    #[sea_orm(string_value = "Voided")]
+    Voided,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
@@ -150,4 +319,69 @@ pub enum OrderStatus {
     Filled,
     #[sea_orm(string_value = "Cancelled")]
     Cancelled,
-} 
\ No newline at end of file
+    #[sea_orm(string_value = "Pending")]
+    Pending, // Stop order waiting for its trigger
+    #[sea_orm(string_value = "Expired")]
+    Expired,
+}
+
+/// How a trade settled on the unified YES/NO book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum TradeKind {
+    #[sea_orm(string_value = "Transfer")]
+    Transfer, // Tokens moved from seller to buyer
+    #[sea_orm(string_value = "Mint")]
+    Mint, // A YES buy crossed a NO buy and a complete set was minted
+    #[sea_orm(string_value = "Burn")]
+    Burn, // A YES sell crossed a NO sell and a complete set was burned
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum OrderType {
+    #[sea_orm(string_value = "Limit")]
+    Limit,
+    #[sea_orm(string_value = "Market")]
+    Market,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum TimeInForce {
+    #[sea_orm(string_value = "GTC")]
+    Gtc,
+    #[sea_orm(string_value = "IOC")]
+    Ioc,
+    #[sea_orm(string_value = "FOK")]
+    Fok,
+    #[sea_orm(string_value = "GTT")]
+    Gtt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum PauseAction {
+    #[sea_orm(string_value = "Paused")]
+    Paused,
+    #[sea_orm(string_value = "Unpaused")]
+    Unpaused,
+    #[sea_orm(string_value = "TradingHalted")]
+    TradingHalted,
+    #[sea_orm(string_value = "TradingResumed")]
+    TradingResumed,
+    #[sea_orm(string_value = "BatchAuctionStarted")]
+    BatchAuctionStarted,
+    #[sea_orm(string_value = "BatchAuctionEnded")]
+    BatchAuctionEnded,
+}
+
+/// How an order's distance from the midpoint discounts its size in liquidity rewards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+pub enum RewardFormula {
+    #[sea_orm(string_value = "Linear")]
+    Linear, // Weight falls linearly to zero at the max spread
+    #[sea_orm(string_value = "Quadratic")]
+    Quadratic, // Weight falls with the square of the distance
+}
@@ -0,0 +1,329 @@
+use sea_orm::{DatabaseConnection, DbErr, entity::*, sea_query::Expr, ActiveValue};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::time::Duration;
+
+use crate::schema::{markets, trades, user_stats, OrderSide, TradeKind};
+
+const DEFAULT_INTERVAL_SECS: u64 = 300;
+const DEFAULT_LOOKBACK_SECS: i64 = 24 * 60 * 60;
+// A ring counts as circular when no member's net position moved by more than this share of
+// the volume traded inside it
+const NET_FLOW_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 1);
+// Two addresses trading back and forth is also what two market makers look like, so a pair only
+// counts when each trade is reversed within this window and the pair's trades make up at least
+// this share of what either address traded in the market
+const PAIR_WINDOW_SECS: i64 = 10 * 60;
+const PAIR_VOLUME_SHARE: Decimal = Decimal::from_parts(8, 0, 0, false, 1);
+
+fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// Runs the wash-trading scan every `WASH_TRADING_INTERVAL_SECS` seconds over the trades of the
+/// last `WASH_TRADING_LOOKBACK_SECS` seconds.
+pub fn spawn(conn: DatabaseConnection) {
+    let interval = env_or("WASH_TRADING_INTERVAL_SECS", DEFAULT_INTERVAL_SECS);
+    let lookback = env_or("WASH_TRADING_LOOKBACK_SECS", DEFAULT_LOOKBACK_SECS);
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(Duration::from_secs(interval));
+        loop {
+            ticker.tick().await;
+            match scan(&conn, now() - lookback).await {
+                Ok(0) => {}
+                Ok(count) => println!("Flagged {} wash trades", count),
+                Err(e) => eprintln!("Error scanning for wash trades: {}", e),
+            }
+        }
+    });
+}
+
+/// Flags self-trades and circular trading since `since`, and takes the newly flagged trades out
+/// of market volume and user stats. Returns how many trades were newly flagged.
+pub async fn scan(conn: &DatabaseConnection, since: i64) -> Result<usize, DbErr> {
+    let recent = trades::Entity::find()
+        .filter(trades::Column::CreatedAt.gte(since))
+        .all(conn)
+        .await?;
+
+    let mut by_market: HashMap<String, Vec<trades::Model>> = HashMap::new();
+    for trade in recent {
+        by_market.entry(trade.market_id.clone()).or_default().push(trade);
+    }
+
+    let mut newly_flagged = Vec::new();
+    for market_trades in by_market.values() {
+        let suspicious = suspicious_trades(market_trades);
+        newly_flagged.extend(
+            market_trades.iter().filter(|t| !t.wash_trade && suspicious.contains(&t.id)).cloned(),
+        );
+    }
+    if newly_flagged.is_empty() {
+        return Ok(0);
+    }
+
+    trades::Entity::update_many()
+        .col_expr(trades::Column::WashTrade, Expr::value(true))
+        .filter(trades::Column::Id.is_in(newly_flagged.iter().map(|t| t.id)))
+        .exec(conn)
+        .await?;
+    exclude_from_metrics(conn, &newly_flagged).await?;
+    Ok(newly_flagged.len())
+}
+
+/// Seller and buyer of a trade that moved tokens between two addresses. Mints and burns have
+/// two buyers or two sellers, so they only count as wash trades when both sides are the same.
+fn transfer(trade: &trades::Model) -> Option<(String, String)> {
+    if trade.kind != TradeKind::Transfer {
+        return None;
+    }
+    let maker = trade.maker_address.to_lowercase();
+    let taker = trade.taker_address.to_lowercase();
+    match trade.side {
+        OrderSide::Buy => Some((maker, taker)),
+        OrderSide::Sell => Some((taker, maker)),
+    }
+}
+
+/// Ids of trades in one market that are self-trades or part of a ring of addresses that trade
+/// tokens around and end up roughly where they started. Rings of two addresses must also pass
+/// `is_round_tripping_pair`.
+fn suspicious_trades(market_trades: &[trades::Model]) -> HashSet<i32> {
+    let mut flagged: HashSet<i32> = market_trades
+        .iter()
+        .filter(|t| t.maker_address.eq_ignore_ascii_case(&t.taker_address))
+        .map(|t| t.id)
+        .collect();
+
+    let mut graph: HashMap<String, HashSet<String>> = HashMap::new();
+    for (seller, buyer) in market_trades.iter().filter_map(transfer) {
+        if seller != buyer {
+            graph.entry(seller).or_default().insert(buyer.clone());
+            graph.entry(buyer).or_default();
+        }
+    }
+
+    for ring in strongly_connected(&graph).into_iter().filter(|c| c.len() > 1) {
+        let inside: Vec<&trades::Model> = market_trades
+            .iter()
+            .filter(|t| transfer(t).is_some_and(|(s, b)| ring.contains(&s) && ring.contains(&b)))
+            .collect();
+        let mut net: HashMap<&str, Decimal> = HashMap::new();
+        let mut gross = Decimal::ZERO;
+        for trade in &inside {
+            let (seller, buyer) = transfer(trade).unwrap();
+            *net.entry(ring.get(&seller).unwrap().as_str()).or_default() -= trade.amount;
+            *net.entry(ring.get(&buyer).unwrap().as_str()).or_default() += trade.amount;
+            gross += trade.amount;
+        }
+        let circular = net.values().all(|n| n.abs() <= gross * NET_FLOW_TOLERANCE);
+        if circular && (ring.len() > 2 || is_round_tripping_pair(market_trades, &ring, &inside, gross)) {
+            flagged.extend(inside.iter().map(|t| t.id));
+        }
+    }
+    flagged
+}
+
+/// Whether the trades `inside` a two-address ring are quick round trips that make up most of
+/// both addresses' volume in the market, rather than two busy traders meeting now and then.
+fn is_round_tripping_pair(
+    market_trades: &[trades::Model],
+    ring: &HashSet<String>,
+    inside: &[&trades::Model],
+    gross: Decimal,
+) -> bool {
+    let reversed_quickly = inside.iter().all(|trade| {
+        let (seller, buyer) = transfer(trade).unwrap();
+        inside.iter().any(|other| {
+            transfer(other) == Some((buyer.clone(), seller.clone()))
+                && (other.created_at.timestamp() - trade.created_at.timestamp()).abs() <= PAIR_WINDOW_SECS
+        })
+    });
+    reversed_quickly
+        && ring.iter().all(|address| {
+            let volume: Decimal = market_trades
+                .iter()
+                .filter(|t| t.maker_address.eq_ignore_ascii_case(address) || t.taker_address.eq_ignore_ascii_case(address))
+                .map(|t| t.amount)
+                .sum();
+            gross >= volume * PAIR_VOLUME_SHARE
+        })
+}
+
+/// Tarjan's algorithm over the seller-to-buyer graph.
+fn strongly_connected(graph: &HashMap<String, HashSet<String>>) -> Vec<HashSet<String>> {
+    struct State<'a> {
+        graph: &'a HashMap<String, HashSet<String>>,
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: HashSet<&'a str>,
+        components: Vec<HashSet<String>>,
+    }
+
+    fn visit<'a>(state: &mut State<'a>, node: &'a str) {
+        let next = state.index.len();
+        state.index.insert(node, next);
+        state.low.insert(node, next);
+        state.stack.push(node);
+        state.on_stack.insert(node);
+
+        let graph = state.graph;
+        for neighbour in &graph[node] {
+            let neighbour = neighbour.as_str();
+            if !state.index.contains_key(neighbour) {
+                visit(state, neighbour);
+                let low = state.low[node].min(state.low[neighbour]);
+                state.low.insert(node, low);
+            } else if state.on_stack.contains(neighbour) {
+                let low = state.low[node].min(state.index[neighbour]);
+                state.low.insert(node, low);
+            }
+        }
+
+        if state.low[node] == state.index[node] {
+            let mut component = HashSet::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                component.insert(member.to_string());
+                if member == node {
+                    break;
+                }
+            }
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        graph,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    for node in graph.keys() {
+        if !state.index.contains_key(node.as_str()) {
+            visit(&mut state, node);
+        }
+    }
+    state.components
+}
+
+/// Subtracts the notional and trade count of `flagged` from market volume and from the stats of
+/// both parties.
+async fn exclude_from_metrics(conn: &DatabaseConnection, flagged: &[trades::Model]) -> Result<(), DbErr> {
+    let mut market_volume: HashMap<&str, Decimal> = HashMap::new();
+    let mut user_volume: HashMap<String, (Decimal, i32)> = HashMap::new();
+    for trade in flagged {
+        let notional = trade.price * trade.amount;
+        *market_volume.entry(&trade.market_id).or_default() += notional;
+        let mut parties = vec![&trade.maker_address];
+        if !trade.maker_address.eq_ignore_ascii_case(&trade.taker_address) {
+            parties.push(&trade.taker_address);
+        }
+        for party in parties {
+            let entry = user_volume.entry(party.clone()).or_default();
+            entry.0 += notional;
+            entry.1 += 1;
+        }
+    }
+
+    for (market_id, volume) in market_volume {
+        if let Some(market) = markets::Entity::find_by_id(market_id.to_string()).one(conn).await? {
+            let remaining = (market.total_volume - volume).max(Decimal::ZERO);
+            let mut market: markets::ActiveModel = market.into();
+            market.total_volume = ActiveValue::Set(remaining);
+            market.update(conn).await?;
+        }
+    }
+    for (user_address, (volume, count)) in user_volume {
+        if let Some(stats) = user_stats::Entity::find_by_id(user_address).one(conn).await? {
+            let remaining_volume = (stats.total_volume - volume).max(Decimal::ZERO);
+            let remaining_trades = (stats.total_trades - count).max(0);
+            let mut stats: user_stats::ActiveModel = stats.into();
+            stats.total_volume = ActiveValue::Set(remaining_volume);
+            stats.total_trades = ActiveValue::Set(remaining_trades);
+            stats.update(conn).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::prelude::DateTimeUtc;
+
+    fn trade(id: i32, seller: &str, buyer: &str, amount: i64, secs: i64) -> trades::Model {
+        trades::Model {
+            id,
+            market_id: "0xmarket".to_string(),
+            maker_address: seller.to_string(),
+            taker_address: buyer.to_string(),
+            side: OrderSide::Sell,
+            price: Decimal::new(50, 2),
+            amount: Decimal::from(amount),
+            taker_fee: Decimal::ZERO,
+            maker_rebate: Decimal::ZERO,
+            creator_fee: Decimal::ZERO,
+            created_at: DateTimeUtc::from_timestamp(secs, 0).unwrap().fixed_offset(),
+            tx_hash: String::new(),
+            flagged: false,
+            kind: TradeKind::Transfer,
+            wash_trade: false,
+            referral_fee: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_flags_self_trades_and_rings() {
+        let trades = vec![
+            trade(1, "0xa", "0xa", 10, 0),
+            trade(2, "0xa", "0xb", 10, 0),
+            trade(3, "0xb", "0xc", 10, 3_600),
+            trade(4, "0xc", "0xa", 10, 7_200),
+        ];
+        assert_eq!(suspicious_trades(&trades), HashSet::from([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_flags_pairs_that_round_trip_quickly() {
+        let trades = vec![
+            trade(1, "0xa", "0xb", 10, 0),
+            trade(2, "0xb", "0xa", 10, 30),
+            trade(3, "0xa", "0xb", 5, 60),
+            trade(4, "0xb", "0xa", 5, 90),
+        ];
+        assert_eq!(suspicious_trades(&trades), HashSet::from([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_ignores_pairs_of_busy_traders() {
+        // Both addresses mostly trade with others and only meet occasionally
+        let mut trades = vec![
+            trade(1, "0xa", "0xb", 10, 0),
+            trade(2, "0xb", "0xa", 10, 30),
+        ];
+        for id in 3..13 {
+            trades.push(trade(id, "0xa", "0xc", 10, id as i64 * 60));
+            trades.push(trade(id + 10, "0xd", "0xb", 10, id as i64 * 60));
+        }
+        assert!(suspicious_trades(&trades).is_empty());
+
+        // A balanced pair whose trades are hours apart
+        let trades = vec![
+            trade(1, "0xa", "0xb", 10, 0),
+            trade(2, "0xb", "0xa", 10, 4 * 3_600),
+        ];
+        assert!(suspicious_trades(&trades).is_empty());
+    }
+}
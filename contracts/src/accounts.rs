@@ -0,0 +1,63 @@
+use ethers::types::{Address, U256};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Free collateral per user, in one collateral token.
+///
+/// Each market starts with accounts of its own. Markets sharing the factory's accounts for
+/// their token (portfolio margin) draw on one balance: a deposit through any of them funds
+/// orders in all of them, and winnings are credited as soon as a market resolves.
+#[derive(Debug, Default)]
+pub struct CollateralAccounts {
+    balances: RwLock<HashMap<Address, U256>>,
+}
+
+impl CollateralAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn balance(&self, user: Address) -> U256 {
+        self.balances.read().unwrap().get(&user).copied().unwrap_or_default()
+    }
+
+    pub fn credit(&self, user: Address, amount: U256) {
+        *self.balances.write().unwrap().entry(user).or_default() += amount;
+    }
+
+    /// Takes up to `amount` from the user's balance; callers check the balance first.
+    pub fn debit(&self, user: Address, amount: U256) {
+        let mut balances = self.balances.write().unwrap();
+        let balance = balances.entry(user).or_default();
+        *balance = balance.saturating_sub(amount);
+    }
+
+    /// Takes `amount` if the user has it. Returns whether it did.
+    pub fn try_debit(&self, user: Address, amount: U256) -> bool {
+        let mut balances = self.balances.write().unwrap();
+        match balances.get_mut(&user) {
+            Some(balance) if *balance >= amount => {
+                *balance -= amount;
+                true
+            }
+            _ => amount.is_zero(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debit_checks_balance() {
+        let accounts = CollateralAccounts::new();
+        let user = Address::random();
+        accounts.credit(user, U256::from(100));
+        assert!(!accounts.try_debit(user, U256::from(101)));
+        assert!(accounts.try_debit(user, U256::from(60)));
+        accounts.debit(user, U256::from(50));
+        assert!(accounts.balance(user).is_zero());
+        assert!(accounts.try_debit(Address::random(), U256::zero()));
+    }
+}
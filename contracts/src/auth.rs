@@ -1,6 +1,14 @@
-use ethers::types::Address;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, Signature};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use std::sync::RwLock;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Mutex, RwLock};
+
+/// Default lifetime of a signed request, in seconds.
+pub const DEFAULT_NONCE_EXPIRY_WINDOW: u64 = 300;
 
 #[derive(Error, Debug)]
 pub enum AuthError {
@@ -12,24 +20,184 @@ pub enum AuthError {
     InvalidWallet,
     #[error("Not connected")]
     NotConnected,
+    #[error("Nonce already used")]
+    NonceReused,
+    #[error("Nonce was never issued")]
+    UnknownNonce,
+    #[error("Signed request is outside the expiry window")]
+    RequestExpired,
+    #[error("Malformed signed request")]
+    MalformedRequest,
+    #[error("Nonce storage error: {0}")]
+    StorageError(String),
+}
+
+/// The payload covered by a signature. Serialized as JSON to form the signed message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRequest {
+    pub action: String,
+    pub signer: Address,
+    pub nonce: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NonceState {
+    /// Highest nonce handed out per address.
+    issued: HashMap<Address, u64>,
+    /// Highest nonce accepted per address. Anything at or below it is a replay.
+    consumed: HashMap<Address, u64>,
+}
+
+/// Issues monotonic per-address nonces and rejects reused or stale ones.
+///
+/// Nonces must be consumed in increasing order. When created with a storage path the
+/// state is written through to disk on every change, so a restart cannot reopen a
+/// window for replaying previously accepted requests.
+#[derive(Debug)]
+pub struct NonceManager {
+    state: Mutex<NonceState>,
+    expiry_window: u64,
+    storage_path: Option<PathBuf>,
+}
+
+impl NonceManager {
+    pub fn new(expiry_window: u64) -> Self {
+        Self {
+            state: Mutex::new(NonceState::default()),
+            expiry_window,
+            storage_path: None,
+        }
+    }
+
+    /// Loads nonce state from `path` if it exists and persists every change back to it.
+    pub fn persistent(path: impl AsRef<Path>, expiry_window: u64) -> Result<Self, AuthError> {
+        let path = path.as_ref().to_path_buf();
+        let state = if path.exists() {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| AuthError::StorageError(e.to_string()))?;
+            serde_json::from_str(&contents)
+                .map_err(|e| AuthError::StorageError(e.to_string()))?
+        } else {
+            NonceState::default()
+        };
+
+        Ok(Self {
+            state: Mutex::new(state),
+            expiry_window,
+            storage_path: Some(path),
+        })
+    }
+
+    pub fn expiry_window(&self) -> u64 {
+        self.expiry_window
+    }
+
+    pub fn issue_nonce(&self, address: Address) -> Result<u64, AuthError> {
+        let mut state = self.state.lock().unwrap();
+        let last_issued = state.issued.get(&address).copied().unwrap_or(0);
+        let last_consumed = state.consumed.get(&address).copied().unwrap_or(0);
+        let nonce = last_issued.max(last_consumed) + 1;
+        self.record(&mut state, |state| &mut state.issued, address, nonce)?;
+        Ok(nonce)
+    }
+
+    /// Accepts `nonce` for `address` if it was issued, has not been used, and `timestamp`
+    /// falls within the expiry window around `now`.
+    pub fn consume_nonce(
+        &self,
+        address: Address,
+        nonce: u64,
+        timestamp: u64,
+        now: u64,
+    ) -> Result<(), AuthError> {
+        if timestamp.saturating_add(self.expiry_window) < now || timestamp > now.saturating_add(self.expiry_window) {
+            return Err(AuthError::RequestExpired);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if nonce <= state.consumed.get(&address).copied().unwrap_or(0) {
+            return Err(AuthError::NonceReused);
+        }
+        if nonce > state.issued.get(&address).copied().unwrap_or(0) {
+            return Err(AuthError::UnknownNonce);
+        }
+
+        self.record(&mut state, |state| &mut state.consumed, address, nonce)
+    }
+
+    /// Sets `address`'s entry in one of the maps and writes the state through. If the write
+    /// fails the old entry is put back, so memory never runs ahead of disk.
+    fn record(
+        &self,
+        state: &mut NonceState,
+        map: fn(&mut NonceState) -> &mut HashMap<Address, u64>,
+        address: Address,
+        nonce: u64,
+    ) -> Result<(), AuthError> {
+        let previous = map(state).insert(address, nonce);
+        let persisted = self.persist(state);
+        if persisted.is_err() {
+            match previous {
+                Some(previous) => map(state).insert(address, previous),
+                None => map(state).remove(&address),
+            };
+        }
+        persisted
+    }
+
+    fn persist(&self, state: &NonceState) -> Result<(), AuthError> {
+        let Some(path) = &self.storage_path else {
+            return Ok(());
+        };
+
+        // Write to a sibling file and rename so a crash never leaves a truncated store.
+        let contents = serde_json::to_string(state)
+            .map_err(|e| AuthError::StorageError(e.to_string()))?;
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, contents)
+            .map_err(|e| AuthError::StorageError(e.to_string()))?;
+        std::fs::rename(&tmp_path, path)
+            .map_err(|e| AuthError::StorageError(e.to_string()))
+    }
+}
+
+impl Default for NonceManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_NONCE_EXPIRY_WINDOW)
+    }
 }
 
 #[derive(Debug)]
 pub struct AuthManager {
     current_address: RwLock<Option<Address>>,
+    wallet: RwLock<Option<LocalWallet>>,
+    nonce_manager: NonceManager,
 }
 
 impl AuthManager {
     pub async fn new(_rpc_url: &str) -> Result<Self, AuthError> {
         Ok(Self {
             current_address: RwLock::new(None),
+            wallet: RwLock::new(None),
+            nonce_manager: NonceManager::default(),
         })
     }
 
-    pub async fn connect_wallet(&self, _private_key: &str) -> Result<(), AuthError> {
-        // In a real implementation, this would validate the private key and set up the wallet
-        // For now, we'll just set a dummy address
-        *self.current_address.write().unwrap() = Some(Address::zero());
+    pub fn with_nonce_manager(mut self, nonce_manager: NonceManager) -> Self {
+        self.nonce_manager = nonce_manager;
+        self
+    }
+
+    pub fn nonce_manager(&self) -> &NonceManager {
+        &self.nonce_manager
+    }
+
+    pub async fn connect_wallet(&self, private_key: &str) -> Result<(), AuthError> {
+        let wallet = LocalWallet::from_str(private_key.trim_start_matches("0x"))
+            .map_err(|_| AuthError::InvalidWallet)?;
+        *self.current_address.write().unwrap() = Some(wallet.address());
+        *self.wallet.write().unwrap() = Some(wallet);
         Ok(())
     }
 
@@ -40,16 +208,70 @@ impl AuthManager {
             .ok_or(AuthError::NotConnected)
     }
 
-    pub async fn create_signed_request(&self, _action: &str) -> Result<(String, String), AuthError> {
-        // In a real implementation, this would create and sign a request
-        // For now, we'll just return dummy values
-        Ok(("dummy_message".to_string(), "dummy_signature".to_string()))
+    pub fn get_wallet(&self) -> Result<LocalWallet, AuthError> {
+        self.wallet
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or(AuthError::NotConnected)
+    }
+
+    /// Signs `action` with a freshly issued nonce. Returns the JSON-encoded
+    /// `SignedRequest` and the hex-encoded signature over it.
+    pub async fn create_signed_request(&self, action: &str) -> Result<(String, String), AuthError> {
+        let wallet = self.get_wallet()?;
+
+        let request = SignedRequest {
+            action: action.to_string(),
+            signer: wallet.address(),
+            nonce: self.nonce_manager.issue_nonce(wallet.address())?,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        };
+        let message = serde_json::to_string(&request).map_err(|_| AuthError::MalformedRequest)?;
+        let signature = wallet
+            .sign_message(message.as_bytes())
+            .await
+            .map_err(|_| AuthError::InvalidSignature)?;
+
+        Ok((message, signature.to_string()))
+    }
+
+    /// Checks the signature on a request produced by `create_signed_request` and consumes
+    /// its nonce, so the same message and signature can never be accepted twice.
+    pub fn verify_signed_request(&self, message: &str, signature: &str) -> Result<SignedRequest, AuthError> {
+        let request: SignedRequest = serde_json::from_str(message)
+            .map_err(|_| AuthError::MalformedRequest)?;
+        let signature = Signature::from_str(signature).map_err(|_| AuthError::InvalidSignature)?;
+        signature
+            .verify(message, request.signer)
+            .map_err(|_| AuthError::InvalidSignature)?;
+
+        self.nonce_manager.consume_nonce(
+            request.signer,
+            request.nonce,
+            request.timestamp,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        )?;
+
+        Ok(request)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ethers::core::k256::ecdsa::SigningKey;
+
+    fn random_private_key() -> String {
+        let wallet: LocalWallet = SigningKey::random(&mut rand::thread_rng()).into();
+        hex::encode(wallet.signer().to_bytes())
+    }
 
     #[tokio::test]
     async fn test_auth_manager() {
@@ -59,11 +281,124 @@ mod tests {
         assert!(auth_manager.get_current_address().is_err());
 
         // Test connecting wallet
-        auth_manager.connect_wallet("dummy_private_key").await.unwrap();
+        auth_manager.connect_wallet(&random_private_key()).await.unwrap();
         assert!(auth_manager.get_current_address().is_ok());
 
         // Test creating signed request
         let result = auth_manager.create_signed_request("test_action").await;
         assert!(result.is_ok());
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_signed_request_cannot_be_replayed() {
+        let auth_manager = AuthManager::new("http://localhost:8545").await.unwrap();
+        auth_manager.connect_wallet(&random_private_key()).await.unwrap();
+
+        let (message, signature) = auth_manager.create_signed_request("mint").await.unwrap();
+        let request = auth_manager.verify_signed_request(&message, &signature).unwrap();
+        assert_eq!(request.action, "mint");
+        assert_eq!(request.signer, auth_manager.get_current_address().unwrap());
+
+        let replay = auth_manager.verify_signed_request(&message, &signature);
+        assert!(matches!(replay, Err(AuthError::NonceReused)));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_request_rejected() {
+        let auth_manager = AuthManager::new("http://localhost:8545").await.unwrap();
+        auth_manager.connect_wallet(&random_private_key()).await.unwrap();
+
+        let (message, signature) = auth_manager.create_signed_request("claim").await.unwrap();
+        let tampered = message.replace("claim", "mint");
+
+        let result = auth_manager.verify_signed_request(&tampered, &signature);
+        assert!(matches!(result, Err(AuthError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_nonces_are_monotonic_per_address() {
+        let nonces = NonceManager::default();
+        let alice = Address::random();
+        let bob = Address::random();
+
+        assert_eq!(nonces.issue_nonce(alice).unwrap(), 1);
+        assert_eq!(nonces.issue_nonce(alice).unwrap(), 2);
+        assert_eq!(nonces.issue_nonce(bob).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_consume_nonce_rejects_reuse_and_unknown() {
+        let nonces = NonceManager::new(60);
+        let address = Address::random();
+        let first = nonces.issue_nonce(address).unwrap();
+        let second = nonces.issue_nonce(address).unwrap();
+
+        assert!(matches!(
+            nonces.consume_nonce(address, second + 1, 1_000, 1_000),
+            Err(AuthError::UnknownNonce)
+        ));
+        nonces.consume_nonce(address, second, 1_000, 1_000).unwrap();
+        assert!(matches!(
+            nonces.consume_nonce(address, second, 1_000, 1_000),
+            Err(AuthError::NonceReused)
+        ));
+        // Consuming a later nonce retires every earlier one.
+        assert!(matches!(
+            nonces.consume_nonce(address, first, 1_000, 1_000),
+            Err(AuthError::NonceReused)
+        ));
+    }
+
+    #[test]
+    fn test_consume_nonce_enforces_expiry_window() {
+        let nonces = NonceManager::new(60);
+        let address = Address::random();
+        let nonce = nonces.issue_nonce(address).unwrap();
+
+        assert!(matches!(
+            nonces.consume_nonce(address, nonce, 1_000, 1_061),
+            Err(AuthError::RequestExpired)
+        ));
+        assert!(matches!(
+            nonces.consume_nonce(address, nonce, 1_100, 1_000),
+            Err(AuthError::RequestExpired)
+        ));
+        // A timestamp near the end of time must not overflow the window check.
+        assert!(matches!(
+            nonces.consume_nonce(address, nonce, u64::MAX, 1_000),
+            Err(AuthError::RequestExpired)
+        ));
+        nonces.consume_nonce(address, nonce, 1_000, 1_060).unwrap();
+    }
+
+    #[test]
+    fn test_failed_persist_leaves_state_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("store");
+        let address = Address::random();
+
+        let nonces = NonceManager::persistent(store.join("nonces.json"), 60).unwrap();
+        assert!(matches!(nonces.issue_nonce(address), Err(AuthError::StorageError(_))));
+        std::fs::create_dir(&store).unwrap();
+        assert_eq!(nonces.issue_nonce(address).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_nonce_state_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nonces.json");
+        let address = Address::random();
+
+        let nonces = NonceManager::persistent(&path, 60).unwrap();
+        let nonce = nonces.issue_nonce(address).unwrap();
+        nonces.consume_nonce(address, nonce, 1_000, 1_000).unwrap();
+        drop(nonces);
+
+        let restarted = NonceManager::persistent(&path, 60).unwrap();
+        assert!(matches!(
+            restarted.consume_nonce(address, nonce, 1_000, 1_000),
+            Err(AuthError::NonceReused)
+        ));
+        assert_eq!(restarted.issue_nonce(address).unwrap(), nonce + 1);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::order_book::{BookOrder, Fill, FillKind, OrderBook, Released};
+use crate::venue::OrderSide;
+use serde::{Deserialize, Serialize};
+
+/// Frequent batch auction mode. Orders rest without matching and are cleared together at a
+/// single price every `interval` seconds, so being first doesn't pay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchAuction {
+    pub interval: u64,
+    pub ends_at: Option<u64>, // Continuous matching resumes after the first clearing from this time on
+}
+
+/// The result of clearing one batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Clearing {
+    pub price: u64, // Uniform YES price; NO trades at `100 - price`
+    pub volume: u64,
+    pub fills: Vec<(bool, Fill)>, // With the outcome of the taker's book
+    pub released: Vec<Released>, // Crossed orders of the same owner, taken off without trading
+}
+
+/// A resting order in terms of YES: bids for NO are YES asks and asks for NO are YES bids.
+struct Entry {
+    is_yes: bool,
+    order: BookOrder,
+    yes_price: u64,
+}
+
+impl Entry {
+    fn new(is_yes: bool, order: &BookOrder) -> Self {
+        let yes_price = if is_yes { order.price } else { 100 - order.price };
+        Self { is_yes, order: order.clone(), yes_price }
+    }
+}
+
+/// Clears the crossing orders of both books at the price that maximizes matched volume.
+/// Ties go to the smallest imbalance between demand and supply, then to the middle of the
+/// remaining price range.
+///
+/// Orders at better prices fill first, oldest first at equal prices. Each fill pairs a
+/// buyer of YES (a YES bid or NO ask) with a seller (a YES ask or NO bid); the later of the
+/// two orders counts as the taker for fees. The books are left as they are for the caller
+/// to settle against. Returns `None` if nothing crosses.
+pub fn clear(yes_book: &OrderBook, no_book: &OrderBook) -> Option<Clearing> {
+    let mut demand: Vec<Entry> = yes_book.bids().iter().map(|o| Entry::new(true, o))
+        .chain(no_book.asks().iter().map(|o| Entry::new(false, o)))
+        .collect();
+    let mut supply: Vec<Entry> = yes_book.asks().iter().map(|o| Entry::new(true, o))
+        .chain(no_book.bids().iter().map(|o| Entry::new(false, o)))
+        .collect();
+
+    // (volume, imbalance) at each candidate price
+    let depth = |price: u64| {
+        let bid: u64 = demand.iter().filter(|e| e.yes_price >= price).map(|e| e.order.remaining).sum();
+        let ask: u64 = supply.iter().filter(|e| e.yes_price <= price).map(|e| e.order.remaining).sum();
+        (bid.min(ask), bid.abs_diff(ask))
+    };
+    let (volume, imbalance) = (1..100)
+        .map(depth)
+        .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))?;
+    if volume == 0 {
+        return None;
+    }
+    let best: Vec<u64> = (1..100).filter(|&price| depth(price) == (volume, imbalance)).collect();
+    let price = (best[0] + best[best.len() - 1]) / 2;
+
+    demand.retain(|e| e.yes_price >= price);
+    supply.retain(|e| e.yes_price <= price);
+    demand.sort_by(|a, b| b.yes_price.cmp(&a.yes_price).then(a.order.timestamp.cmp(&b.order.timestamp)));
+    supply.sort_by(|a, b| a.yes_price.cmp(&b.yes_price).then(a.order.timestamp.cmp(&b.order.timestamp)));
+
+    let mut clearing = Clearing { price, ..Default::default() };
+    let (mut d, mut s) = (0, 0);
+    let mut left = volume;
+    while left > 0 && d < demand.len() && s < supply.len() {
+        let size = left.min(demand[d].order.remaining).min(supply[s].order.remaining);
+        let (buyer, seller) = (&demand[d], &supply[s]);
+
+        if buyer.order.owner == seller.order.owner {
+            for entry in [buyer, seller] {
+                clearing.released.push(Released {
+                    order: BookOrder { remaining: size, ..entry.order.clone() },
+                    complement: !entry.is_yes,
+                });
+            }
+        } else {
+            let (maker, taker) = if (buyer.order.timestamp, buyer.order.order_id) <= (seller.order.timestamp, seller.order.order_id) {
+                (buyer, seller)
+            } else {
+                (seller, buyer)
+            };
+            let kind = match (maker.is_yes == taker.is_yes, taker.order.side) {
+                (true, _) => FillKind::Transfer,
+                (false, OrderSide::Buy) => FillKind::Mint,
+                (false, OrderSide::Sell) => FillKind::Burn,
+            };
+            clearing.fills.push((taker.is_yes, Fill {
+                maker_order_id: maker.order.order_id,
+                taker_order_id: taker.order.order_id,
+                maker: maker.order.owner,
+                taker: taker.order.owner,
+                taker_side: taker.order.side,
+                price: if maker.is_yes { price } else { 100 - price },
+                size,
+                kind,
+                taker_fee_bps: taker.order.taker_fee_bps,
+            }));
+            clearing.volume += size;
+        }
+
+        demand[d].order.remaining -= size;
+        supply[s].order.remaining -= size;
+        left -= size;
+        if demand[d].order.remaining == 0 {
+            d += 1;
+        }
+        if supply[s].order.remaining == 0 {
+            s += 1;
+        }
+    }
+    Some(clearing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Address;
+
+    fn order(order_id: u64, owner: u64, side: OrderSide, price: u64, size: u64) -> BookOrder {
+        BookOrder {
+            order_id,
+            owner: Address::from_low_u64_be(owner),
+            side,
+            price,
+            remaining: size,
+            timestamp: order_id,
+            expires_at: None,
+            post_only: false,
+            self_trade_prevention: Default::default(),
+            taker_fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_uniform_price_clearing() {
+        let mut yes_book = OrderBook::new();
+        let mut no_book = OrderBook::new();
+        yes_book.rest(order(1, 1, OrderSide::Buy, 60, 10));
+        yes_book.rest(order(2, 2, OrderSide::Buy, 55, 10));
+        yes_book.rest(order(3, 3, OrderSide::Sell, 50, 5));
+        yes_book.rest(order(4, 4, OrderSide::Sell, 58, 10));
+        // A NO bid at 45 sells YES at 55
+        no_book.rest(order(5, 5, OrderSide::Buy, 45, 5));
+
+        let clearing = clear(&yes_book, &no_book).unwrap();
+        // 10 trades between 55 and 57; 56 leaves the smallest imbalance
+        assert_eq!((clearing.price, clearing.volume), (56, 10));
+        assert!(clearing.fills.iter().all(|(is_yes, fill)| fill.taker_price() == if *is_yes { 56 } else { 44 }));
+        let mint = clearing.fills.iter().find(|(_, fill)| fill.kind == FillKind::Mint).unwrap();
+        assert_eq!((mint.1.maker_order_id, mint.1.taker_order_id, mint.1.size), (1, 5, 5));
+
+        // The 55 bid and the 58 ask left over don't cross
+        yes_book.cancel(1);
+        yes_book.cancel(3);
+        no_book.cancel(5);
+        assert!(clear(&yes_book, &no_book).is_none());
+    }
+
+    #[test]
+    fn test_same_owner_is_released() {
+        let mut yes_book = OrderBook::new();
+        let no_book = OrderBook::new();
+        yes_book.rest(order(1, 1, OrderSide::Buy, 60, 10));
+        yes_book.rest(order(2, 1, OrderSide::Sell, 50, 4));
+        yes_book.rest(order(3, 2, OrderSide::Sell, 50, 6));
+
+        let clearing = clear(&yes_book, &no_book).unwrap();
+        assert_eq!(clearing.volume, 6);
+        let released: Vec<(u64, u64)> = clearing.released.iter().map(|r| (r.order.order_id, r.order.remaining)).collect();
+        assert_eq!(released, vec![(1, 4), (2, 4)]);
+        assert_eq!(clearing.fills[0].1.taker_order_id, 3);
+    }
+}
@@ -1,13 +1,21 @@
 use clap::{Parser, Subcommand};
 use hypermarket::{
-    market::{MarketContractState},
+    claims::claim_all,
+    market::{Market, MarketContractState, MarketMetadata, MarketStatus, ParentCondition},
     market_factory::{MarketFactoryState},
+    order_types::{NewOrder, OrderType, SelfTradePrevention, TimeInForce},
+    templates::{self, MarketSpec, MarketTemplate},
     auth::AuthManager,
+    batch_auction::BatchAuction,
     events::EventLogger,
+    hyperliquid_client::HyperliquidClient,
+    venue::{OrderSide, SimulatedVenue, VenueClient},
     MarketContract,
     MarketFactory,
 };
 use ethers::types::{Address, U256};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::str::FromStr;
 
@@ -22,9 +30,19 @@ struct Cli {
 
     #[arg(long, env = "HYPERLIQUID_PRIVATE_KEY")]
     private_key: String,
+
+    /// Use an in-memory simulated venue instead of Hyperliquid
+    #[arg(long)]
+    simulated: bool,
+
+    /// JSON file naming each market's spot-deployed outcome tokens on Hyperliquid, as
+    /// `{ "<market id>": { "yes": "...", "no": "..." } }`
+    #[arg(long, env = "HYPERLIQUID_OUTCOME_TOKENS")]
+    outcome_tokens: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // Parsed once at startup
 enum Commands {
     /// Create a new market
     CreateMarket {
@@ -39,6 +57,60 @@ enum Commands {
         /// Collateral token
         #[arg(long, default_value = "USDC")]
         collateral: String,
+        /// Where the outcome will be determined from, e.g. a data source URL
+        #[arg(long)]
+        resolution_source: String,
+        /// Longer description of the market
+        #[arg(long, default_value = "")]
+        description: String,
+        /// Resolution rules
+        #[arg(long, default_value = "")]
+        rules: String,
+        /// Supporting resolution source URL (repeatable)
+        #[arg(long = "source-url")]
+        source_urls: Vec<String>,
+        /// Market category
+        #[arg(long, default_value = "")]
+        category: String,
+        /// Tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Image URL
+        #[arg(long)]
+        image_url: Option<String>,
+        /// Salt to distinguish markets with the same creator, question and expiry
+        #[arg(long, default_value_t = 0)]
+        salt: u64,
+        /// Make the market conditional on this parent market's outcome
+        #[arg(long)]
+        parent_market: Option<String>,
+        /// Parent outcome that activates the market: YES or NO
+        #[arg(long, default_value = "YES")]
+        parent_outcome: String,
+        /// Timestamp after which trading stops; defaults to trading until resolution
+        #[arg(long)]
+        trading_close: Option<u64>,
+        /// Earliest timestamp the oracle may resolve at; defaults to the expiry
+        #[arg(long)]
+        resolution_time: Option<u64>,
+        /// Open in batch auction mode, clearing every this many seconds
+        #[arg(long)]
+        auction_interval: Option<u64>,
+        /// Timestamp at which the opening auction hands over to continuous matching
+        #[arg(long, requires = "auction_interval")]
+        auction_until: Option<u64>,
+    },
+    /// Create markets from a template over its parameter grid or a parameter file
+    CreateMarkets {
+        /// Template JSON file
+        #[arg(long)]
+        template: PathBuf,
+        /// CSV (with header row) or JSON file of parameter sets; defaults to the template's grid
+        #[arg(long)]
+        params: Option<PathBuf>,
+        /// Validate every instance without creating anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// List all markets
     ListMarkets,
@@ -63,14 +135,38 @@ enum Commands {
         /// Market ID
         #[arg(long)]
         market_id: String,
+        /// Outcome token to trade: YES or NO
+        #[arg(long, default_value = "YES")]
+        outcome: String,
         /// Buy or Sell
         #[arg(long)]
         side: String,
-        /// Price in cents
+        /// Limit price in cents; omit for a market order
         #[arg(long)]
-        price: u64,
+        price: Option<u64>,
         /// Amount of tokens
         amount: u64,
+        /// Time in force: GTC, IOC, FOK or GTT; market orders default to IOC
+        #[arg(long)]
+        tif: Option<String>,
+        /// Expiry timestamp for GTT orders
+        #[arg(long)]
+        expires_at: Option<u64>,
+        /// Worst price a market order may trade at, in cents away from the best price
+        #[arg(long, default_value_t = 5)]
+        max_slippage: u64,
+        /// Reject the order if it would take liquidity
+        #[arg(long)]
+        post_only: bool,
+        /// Only sell tokens already held
+        #[arg(long)]
+        reduce_only: bool,
+        /// Hold the order until the last trade price reaches this price
+        #[arg(long)]
+        stop_price: Option<u64>,
+        /// On matching your own order: cancel-newest, cancel-oldest, cancel-both or decrement
+        #[arg(long, default_value = "cancel-newest")]
+        self_trade: String,
     },
     /// Claim winnings
     ClaimWinnings {
@@ -78,6 +174,53 @@ enum Commands {
         #[arg(long)]
         market_id: String,
     },
+    /// Claim winnings in every resolved market
+    ClaimAll,
+    /// Redeem every holder's winnings in a resolved market
+    RedeemAll {
+        /// Market ID
+        #[arg(long)]
+        market_id: String,
+    },
+    /// Pause one market, or the whole factory if no market is given
+    Pause {
+        /// Market ID
+        #[arg(long)]
+        market_id: Option<String>,
+        /// Why the market is being paused
+        #[arg(long)]
+        reason: String,
+    },
+    /// Unpause one market, or the whole factory if no market is given
+    Unpause {
+        /// Market ID
+        #[arg(long)]
+        market_id: Option<String>,
+    },
+    /// Register who referred you; only possible before your first trade
+    RegisterReferrer {
+        /// Referrer address
+        #[arg(long)]
+        referrer: String,
+    },
+}
+
+/// A market's contract sharing the factory's pauses, referrals, fees, custody and
+/// collateral accounts.
+fn build_contract(
+    factory: &mut MarketFactoryState,
+    market: Market,
+    auth_manager: &Arc<AuthManager>,
+    event_logger: &Arc<EventLogger>,
+    venue: &Arc<dyn VenueClient>,
+) -> MarketContractState {
+    let accounts = factory.accounts(&market.collateral_token);
+    MarketContractState::new(market, auth_manager.clone(), event_logger.clone(), venue.clone())
+        .with_pause_controller(factory.pause_controller())
+        .with_referrals(factory.referrals())
+        .with_fee_controller(factory.fee_controller())
+        .with_custody(factory.treasury())
+        .with_accounts(accounts)
 }
 
 #[tokio::main]
@@ -98,27 +241,109 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let auth_manager = Arc::new(AuthManager::new(&cli.api_url).await?);
     auth_manager.connect_wallet(&cli.private_key).await?;
     let event_logger = Arc::new(EventLogger::new(true, false, None));
+    let venue: Arc<dyn VenueClient> = if cli.simulated {
+        Arc::new(SimulatedVenue::new())
+    } else {
+        let outcome_tokens = match &cli.outcome_tokens {
+            Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            None => HashMap::new(),
+        };
+        Arc::new(
+            HyperliquidClient::connect(auth_manager.clone(), &cli.api_url)
+                .await?
+                .with_outcome_tokens(outcome_tokens),
+        )
+    };
 
     // Create market factory
     let mut factory = MarketFactoryState::new(
-        &cli.api_url,
+        venue.clone(),
         auth_manager.clone(),
         event_logger.clone(),
         U256::from(100),
     ).await?;
 
     match cli.command {
-        Commands::CreateMarket { question, expiry, oracle, collateral } => {
+        Commands::CreateMarket {
+            question,
+            expiry,
+            oracle,
+            collateral,
+            resolution_source,
+            description,
+            rules,
+            source_urls,
+            category,
+            tags,
+            image_url,
+            salt,
+            parent_market,
+            parent_outcome,
+            trading_close,
+            resolution_time,
+            auction_interval,
+            auction_until,
+        } => {
             let oracle_addr = Address::from_str(&oracle)?;
-            let market_id = factory.create_market(
+            let metadata = MarketMetadata {
+                description,
+                rules,
+                resolution_source,
+                resolution_source_urls: source_urls,
+                category,
+                tags,
+                image_url,
+            };
+            let required_outcome = match parent_outcome.to_uppercase().as_str() {
+                "YES" => true,
+                "NO" => false,
+                _ => return Err("Parent outcome must be YES or NO".into()),
+            };
+            let parent_condition = parent_market.map(|market_id| ParentCondition {
+                market_id,
+                required_outcome,
+            });
+            let market_id = factory.create_market_from_spec(MarketSpec {
                 question,
-                expiry,
-                oracle_addr,
-                collateral,
-            ).await?;
+                expiry_timestamp: expiry,
+                oracle_id: oracle_addr,
+                collateral_token: collateral,
+                metadata,
+                salt,
+                parent_condition,
+                trading_close_timestamp: trading_close,
+                resolution_timestamp: resolution_time,
+                batch_auction: auction_interval.map(|interval| BatchAuction {
+                    interval,
+                    ends_at: auction_until,
+                }),
+            }).await?;
             println!("Created market: {}", market_id);
         }
 
+        Commands::CreateMarkets { template, params, dry_run } => {
+            let template = MarketTemplate::load(&template)?;
+            let params = match params {
+                Some(path) => templates::load_params(&path)?,
+                None => template.grid_params(),
+            };
+
+            let results = factory.create_markets_from_template(&template, &params, dry_run).await?;
+            let mut failed = 0;
+            for result in &results {
+                let label = result.question.clone().unwrap_or_else(|| format!("{:?}", result.params));
+                match &result.outcome {
+                    Ok(()) if dry_run => println!("OK      {}", label),
+                    Ok(()) => println!("CREATED {} ({})", label, result.market_id.as_deref().unwrap_or_default()),
+                    Err(e) => {
+                        failed += 1;
+                        println!("FAILED  {}: {}", label, e);
+                    }
+                }
+            }
+            println!("{} of {} instances {}", results.len() - failed, results.len(), if dry_run { "valid" } else { "created" });
+        }
+
         Commands::ListMarkets => {
             let markets = factory.list_markets();
             for (id, market) in markets {
@@ -134,53 +359,176 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let market = factory.get_market(market_id.clone())
                 .ok_or("Market not found")?;
             
-            let mut market_contract = MarketContractState::new(
-                market,
-                auth_manager.clone(),
-                event_logger.clone(),
-                Default::default(),
-            );
+            let mut market_contract =
+                build_contract(&mut factory, market, &auth_manager, &event_logger, &venue);
 
-            market_contract.mint_tokens(amount).await?;
-            println!("Deposited {} USDC as collateral", amount);
+            market_contract.deposit_collateral(U256::from(amount)).await?;
+            println!("Deposited {} {} as collateral", amount, market_contract.market.collateral_token);
         }
 
         Commands::MintTokens { market_id, amount } => {
             let market = factory.get_market(market_id.clone())
                 .ok_or("Market not found")?;
             
-            let mut market_contract = MarketContractState::new(
-                market,
-                auth_manager.clone(),
-                event_logger.clone(),
-                Default::default(),
-            );
+            let mut market_contract =
+                build_contract(&mut factory, market, &auth_manager, &event_logger, &venue);
 
             market_contract.mint_tokens(amount).await?;
             println!("Minted {} YES/NO tokens", amount);
         }
 
-        Commands::PlaceOrder { market_id, side: _, price: _, amount: _ } => {
+        Commands::PlaceOrder {
+            market_id,
+            outcome,
+            side,
+            price,
+            amount,
+            tif,
+            expires_at,
+            max_slippage,
+            post_only,
+            reduce_only,
+            stop_price,
+            self_trade,
+        } => {
             let market = factory.get_market(market_id.clone())
                 .ok_or("Market not found")?;
-            
-            println!("Order placement not yet implemented for market: {}", market.question);
+
+            let is_yes = match outcome.to_uppercase().as_str() {
+                "YES" => true,
+                "NO" => false,
+                _ => return Err("Outcome must be YES or NO".into()),
+            };
+            let side = match side.to_lowercase().as_str() {
+                "buy" => OrderSide::Buy,
+                "sell" => OrderSide::Sell,
+                _ => return Err("Side must be Buy or Sell".into()),
+            };
+            let order_type = match price {
+                Some(price) => OrderType::Limit { price },
+                None => OrderType::Market { max_slippage },
+            };
+            let time_in_force = match (tif.as_deref().map(str::to_uppercase).as_deref(), expires_at) {
+                (None, _) if price.is_none() => TimeInForce::Ioc,
+                (None | Some("GTC"), None) => TimeInForce::Gtc,
+                (Some("IOC"), None) => TimeInForce::Ioc,
+                (Some("FOK"), None) => TimeInForce::Fok,
+                (None | Some("GTT"), Some(expires_at)) => TimeInForce::Gtt { expires_at },
+                _ => return Err("Time in force must be GTC, IOC, FOK or GTT (with --expires-at)".into()),
+            };
+            let self_trade_prevention = match self_trade.to_lowercase().as_str() {
+                "cancel-newest" => SelfTradePrevention::CancelNewest,
+                "cancel-oldest" => SelfTradePrevention::CancelOldest,
+                "cancel-both" => SelfTradePrevention::CancelBoth,
+                "decrement" => SelfTradePrevention::Decrement,
+                _ => return Err("Self-trade prevention must be cancel-newest, cancel-oldest, cancel-both or decrement".into()),
+            };
+            let order = NewOrder {
+                is_yes,
+                side,
+                amount,
+                order_type,
+                time_in_force,
+                post_only,
+                reduce_only,
+                stop_price,
+                self_trade_prevention,
+            };
+
+            let mut market_contract =
+                build_contract(&mut factory, market, &auth_manager, &event_logger, &venue);
+
+            let placed = market_contract.submit_order(order)?;
+            println!(
+                "Placed order {}: {} filled, {} resting",
+                placed.order_id, placed.filled_size, placed.resting_size
+            );
         }
 
         Commands::ClaimWinnings { market_id } => {
             let market = factory.get_market(market_id.clone())
                 .ok_or("Market not found")?;
             
-            let mut market_contract = MarketContractState::new(
-                market,
-                auth_manager.clone(),
-                event_logger.clone(),
-                Default::default(),
-            );
+            let mut market_contract =
+                build_contract(&mut factory, market, &auth_manager, &event_logger, &venue);
 
             let winnings = market_contract.claim_winnings().await?;
             println!("Claimed {} USDC in winnings", winnings);
         }
+
+        Commands::ClaimAll => {
+            let mut market_contracts = Vec::new();
+            for (_, market) in factory.list_markets() {
+                if market.status != MarketStatus::Resolved {
+                    continue;
+                }
+                market_contracts.push(build_contract(
+                    &mut factory,
+                    market,
+                    &auth_manager,
+                    &event_logger,
+                    &venue,
+                ));
+            }
+
+            let claims = claim_all(&mut market_contracts).await;
+            let mut total = U256::zero();
+            for claim in &claims {
+                match &claim.result {
+                    Ok(winnings) => {
+                        total += *winnings;
+                        println!("Market {}: claimed {}", claim.market_id, winnings);
+                    }
+                    Err(e) => println!("Market {}: failed: {}", claim.market_id, e),
+                }
+            }
+            println!("Claimed {} USDC in winnings from {} markets", total, claims.len());
+        }
+
+        Commands::RedeemAll { market_id } => {
+            let market = factory.get_market(market_id.clone())
+                .ok_or("Market not found")?;
+
+            let mut market_contract =
+                build_contract(&mut factory, market, &auth_manager, &event_logger, &venue);
+
+            let payouts = market_contract.redeem_all()?;
+            for (holder, payout) in &payouts {
+                println!("Redeemed {} USDC for {:?}", payout, holder);
+            }
+            println!("Redeemed winnings for {} holders", payouts.len());
+        }
+
+        Commands::Pause { market_id, reason } => {
+            match market_id {
+                Some(market_id) => {
+                    factory.pause_market(&market_id, &reason).await?;
+                    println!("Paused market {}", market_id);
+                }
+                None => {
+                    factory.pause(&reason).await?;
+                    println!("Paused all markets");
+                }
+            }
+        }
+
+        Commands::Unpause { market_id } => {
+            match market_id {
+                Some(market_id) => {
+                    factory.unpause_market(&market_id).await?;
+                    println!("Unpaused market {}", market_id);
+                }
+                None => {
+                    factory.unpause().await?;
+                    println!("Unpaused all markets");
+                }
+            }
+        }
+
+        Commands::RegisterReferrer { referrer } => {
+            factory.register_referrer(Address::from_str(&referrer)?).await?;
+            println!("Registered referrer {}", referrer);
+        }
     }
 
     Ok(())
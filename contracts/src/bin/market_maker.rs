@@ -0,0 +1,253 @@
+use clap::Parser;
+use hypermarket::{
+    auth::AuthManager,
+    hyperliquid_client::HyperliquidClient,
+    market_data::{MarketDataEvent, MarketDataStream, Subscription},
+    market_maker::{FairValue, MarketMaker, QuoteConfig},
+    venue::{SimulatedVenue, VenueClient},
+};
+use ethers::types::{Address, U256};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Quotes two-sided liquidity on the configured markets.
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    #[arg(long, env = "HYPERLIQUID_API_URL")]
+    api_url: String,
+
+    #[arg(long, env = "HYPERLIQUID_PRIVATE_KEY")]
+    private_key: String,
+
+    /// Use an in-memory simulated venue instead of Hyperliquid
+    #[arg(long)]
+    simulated: bool,
+
+    /// USDC the bot starts with on the simulated venue
+    #[arg(long, default_value = "1000000")]
+    simulated_collateral: u64,
+
+    /// YES tokens of each market the bot starts with on the simulated venue
+    #[arg(long, default_value = "10000")]
+    simulated_tokens: u64,
+
+    /// JSON file naming each market's spot-deployed outcome tokens on Hyperliquid, as
+    /// `{ "<market id>": { "yes": "...", "no": "..." } }`
+    #[arg(long, env = "HYPERLIQUID_OUTCOME_TOKENS")]
+    outcome_tokens: Option<PathBuf>,
+
+    /// JSON file listing the markets to quote
+    #[arg(long)]
+    config: PathBuf,
+
+    /// Seconds between requotes
+    #[arg(long, default_value = "5")]
+    interval: u64,
+}
+
+/// For example:
+///
+/// ```json
+/// { "markets": [{
+///     "market_id": "0x...",
+///     "fair_value": { "source": "external", "url": "https://...", "pointer": "/probability" },
+///     "quote": { "spread": 4, "size": 100, "max_inventory": 1000, "skew": 3 }
+/// }] }
+/// ```
+#[derive(Deserialize)]
+struct BotConfig {
+    markets: Vec<MarketConfig>,
+}
+
+#[derive(Deserialize)]
+struct MarketConfig {
+    market_id: String,
+    fair_value: FairValue,
+    quote: QuoteConfig,
+}
+
+/// A market's fair value source, with any price feed subscription it needs.
+struct FairValueFeed {
+    source: FairValue,
+    trades: Option<MarketDataStream>,
+    last_price: Option<f64>,
+}
+
+impl FairValueFeed {
+    fn new(source: FairValue, hyperliquid: Option<&HyperliquidClient>) -> Result<Self, Box<dyn std::error::Error>> {
+        let trades = match &source {
+            FairValue::PriceFeed { coin, .. } => {
+                let client = hyperliquid.ok_or("Price feed fair values need the Hyperliquid venue")?;
+                Some(client.subscribe(Subscription::Trades { coin: coin.clone() })?)
+            }
+            _ => None,
+        };
+        Ok(Self { source, trades, last_price: None })
+    }
+
+    /// The current fair value, or `None` if it isn't known yet.
+    async fn current(&mut self, http: &reqwest::Client) -> Result<Option<u64>, Box<dyn std::error::Error>> {
+        match &self.source {
+            FairValue::Static { price } => Ok(Some(*price)),
+            FairValue::PriceFeed { strike, scale, .. } => {
+                if let Some(trades) = self.trades.as_mut() {
+                    while let Ok(event) = trades.try_recv() {
+                        if let MarketDataEvent::Trades(trades) = event {
+                            self.last_price = trades.last().map(|trade| trade.px).or(self.last_price);
+                        }
+                    }
+                }
+                Ok(self.last_price.and_then(|price| FairValue::from_underlying(price, *strike, *scale)))
+            }
+            FairValue::External { url, pointer } => {
+                let document: serde_json::Value = http.get(url).send().await?.json().await?;
+                Ok(document
+                    .pointer(pointer)
+                    .and_then(|value| value.as_f64())
+                    .and_then(FairValue::from_probability))
+            }
+        }
+    }
+}
+
+/// The simulated venue starts out empty, so the bot is funded there before it quotes.
+struct SimulatedFunding {
+    venue: SimulatedVenue,
+    collateral: U256,
+    tokens: U256, // Per market
+}
+
+/// Sets up a bot and its fair value feed for each configured market.
+async fn start_bots(
+    markets: Vec<MarketConfig>,
+    owner: Address,
+    venue: Arc<dyn VenueClient>,
+    funding: Option<&SimulatedFunding>,
+    hyperliquid: Option<&HyperliquidClient>,
+) -> Result<Vec<(MarketMaker, FairValueFeed)>, Box<dyn std::error::Error>> {
+    if let Some(funding) = funding {
+        funding.venue.mint(owner, "USDC", funding.collateral);
+    }
+    let mut bots = Vec::new();
+    for market in markets {
+        // Creates the pair on the simulated venue; looks up the listed pair on Hyperliquid
+        let (yes_token, _) = venue.create_market_pair(&market.market_id, "USDC").await?;
+        if let Some(funding) = funding {
+            funding.venue.mint(owner, &yes_token, funding.tokens);
+        }
+        let feed = FairValueFeed::new(market.fair_value, hyperliquid)?;
+        bots.push((MarketMaker::new(&market.market_id, &yes_token, owner, market.quote, venue.clone()), feed));
+    }
+    Ok(bots)
+}
+
+/// Requotes every market once, pulling a market's quotes while its fair value is unknown.
+async fn quote_all(bots: &mut [(MarketMaker, FairValueFeed)], http: &reqwest::Client) {
+    for (bot, feed) in bots.iter_mut() {
+        let result = match feed.current(http).await {
+            Ok(Some(fair)) => bot.step(fair).await.map(|_| ()),
+            // Don't quote blind
+            Ok(None) => bot.cancel_all().await,
+            Err(e) => {
+                log::warn!("Fair value for {} unavailable: {}", bot.market_id, e);
+                bot.cancel_all().await
+            }
+        };
+        if let Err(e) = result {
+            log::warn!("Quoting {} failed: {}", bot.market_id, e);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    dotenv::dotenv().ok();
+
+    let cli = Cli::parse();
+    let config: BotConfig = serde_json::from_str(&std::fs::read_to_string(&cli.config)?)?;
+
+    let auth_manager = Arc::new(AuthManager::new(&cli.api_url).await?);
+    auth_manager.connect_wallet(&cli.private_key).await?;
+    let owner = auth_manager.get_current_address()?;
+
+    let hyperliquid = if cli.simulated {
+        None
+    } else {
+        let outcome_tokens = match &cli.outcome_tokens {
+            Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            None => HashMap::new(),
+        };
+        Some(
+            HyperliquidClient::connect(auth_manager.clone(), &cli.api_url)
+                .await?
+                .with_outcome_tokens(outcome_tokens),
+        )
+    };
+    let (venue, funding): (Arc<dyn VenueClient>, _) = match &hyperliquid {
+        Some(client) => (Arc::new(client.clone()), None),
+        None => {
+            let simulated = SimulatedVenue::new();
+            let funding = SimulatedFunding {
+                venue: simulated.clone(),
+                collateral: U256::from(cli.simulated_collateral),
+                tokens: U256::from(cli.simulated_tokens),
+            };
+            (Arc::new(simulated), Some(funding))
+        }
+    };
+
+    let mut bots = start_bots(config.markets, owner, venue, funding.as_ref(), hyperliquid.as_ref()).await?;
+    log::info!("Quoting {} markets as {:?}", bots.len(), owner);
+
+    let http = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(Duration::from_secs(cli.interval.max(1)));
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+        quote_all(&mut bots, &http).await;
+    }
+
+    for (bot, _) in bots.iter_mut() {
+        if let Err(e) = bot.cancel_all().await {
+            log::warn!("Cancelling quotes on {} failed: {}", bot.market_id, e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_quotes_on_funded_simulated_venue() {
+        let config: BotConfig = serde_json::from_str(r#"{ "markets": [{
+            "market_id": "0xmarket",
+            "fair_value": { "source": "static", "price": 50 },
+            "quote": { "spread": 4, "size": 10, "max_inventory": 20, "skew": 4 }
+        }] }"#).unwrap();
+        let owner = Address::random();
+        let simulated = SimulatedVenue::new();
+        let funding = SimulatedFunding {
+            venue: simulated.clone(),
+            collateral: U256::from(1_000),
+            tokens: U256::from(100),
+        };
+
+        let mut bots = start_bots(config.markets, owner, Arc::new(simulated), Some(&funding), None)
+            .await
+            .unwrap();
+        quote_all(&mut bots, &reqwest::Client::new()).await;
+
+        // Both quotes rest instead of being rejected for lack of funds
+        let quotes = bots[0].0.open_quotes();
+        assert_eq!((quotes[0].price, quotes[1].price), (48, 52));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::fees::BPS_DENOMINATOR;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Halts trading for `halt_seconds` when the price moves more than `max_move_bps`
+/// (relative to the earlier price) within `window_seconds`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitBreaker {
+    pub max_move_bps: u64,
+    pub window_seconds: u64,
+    pub halt_seconds: u64,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            max_move_bps: 2_000,
+            window_seconds: 300,
+            halt_seconds: 600,
+        }
+    }
+}
+
+/// The price move that tripped a breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trip {
+    pub reference_price: u64,
+    pub price: u64,
+    pub move_bps: u64,
+}
+
+/// Recent trade prices for one market.
+#[derive(Debug, Clone, Default)]
+pub struct PriceWindow {
+    prices: VecDeque<(u64, u64)>, // (timestamp, price), oldest first
+}
+
+impl PriceWindow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a trade price and returns the largest move against any price still inside
+    /// the window if it exceeds the breaker's limit.
+    pub fn record(&mut self, breaker: &CircuitBreaker, timestamp: u64, price: u64) -> Option<Trip> {
+        while let Some(&(recorded_at, _)) = self.prices.front() {
+            if recorded_at + breaker.window_seconds >= timestamp {
+                break;
+            }
+            self.prices.pop_front();
+        }
+
+        let trip = self
+            .prices
+            .iter()
+            .filter(|(_, reference_price)| *reference_price > 0)
+            .map(|&(_, reference_price)| Trip {
+                reference_price,
+                price,
+                move_bps: reference_price.abs_diff(price) * BPS_DENOMINATOR / reference_price,
+            })
+            .max_by_key(|trip| trip.move_bps)
+            .filter(|trip| trip.move_bps > breaker.max_move_bps);
+
+        self.prices.push_back((timestamp, price));
+        trip
+    }
+
+    /// Forgets recorded prices, so trading resumes against a fresh reference.
+    pub fn clear(&mut self) {
+        self.prices.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trips_only_within_window() {
+        let breaker = CircuitBreaker {
+            max_move_bps: 2_000,
+            window_seconds: 60,
+            halt_seconds: 300,
+        };
+        let mut window = PriceWindow::new();
+
+        assert_eq!(window.record(&breaker, 0, 50), None);
+        assert_eq!(window.record(&breaker, 30, 58), None); // 16%
+        let trip = window.record(&breaker, 50, 65).unwrap(); // 30% over 50s
+        assert_eq!((trip.reference_price, trip.move_bps), (50, 3_000));
+
+        window.clear();
+        assert_eq!(window.record(&breaker, 100, 40), None);
+        assert_eq!(window.record(&breaker, 200, 60), None); // 40 is outside the window
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    market::{MarketContractState, MarketError},
+    MarketContract,
+};
+use ethers::types::U256;
+
+/// One market's part in a batch claim.
+#[derive(Debug)]
+pub struct MarketClaim {
+    pub market_id: String,
+    pub result: Result<U256, MarketError>, // Net payout
+}
+
+/// Claims the caller's winnings in every resolved market where they hold winning tokens.
+/// Markets with nothing to claim are skipped; a failing market doesn't stop the others.
+pub async fn claim_all(markets: &mut [MarketContractState]) -> Vec<MarketClaim> {
+    let mut claims = Vec::new();
+    for market in markets.iter_mut() {
+        let claimable = market
+            .auth_manager
+            .get_current_address()
+            .map(|caller| market.claimable(caller));
+        if claimable.is_ok_and(|claimable| claimable.is_zero()) {
+            continue;
+        }
+        claims.push(MarketClaim {
+            market_id: market.market.id.clone(),
+            result: market.claim_winnings().await,
+        });
+    }
+    claims
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::AuthManager,
+        market::tests::{connected_auth, funded_contract, test_market},
+        pause::PauseController,
+    };
+    use std::sync::Arc;
+
+    async fn minted_market(id: &str, auth_manager: Arc<AuthManager>, pauses: Arc<PauseController>) -> MarketContractState {
+        let user = auth_manager.get_current_address().unwrap();
+        let mut contract = funded_contract(test_market(id, user), auth_manager, &[user])
+            .with_pause_controller(pauses);
+        contract.deposit_collateral(U256::from(100)).await.unwrap();
+        contract.mint_tokens(100).await.unwrap();
+        contract
+    }
+
+    #[tokio::test]
+    async fn test_claim_all_reports_each_market() {
+        let (auth_manager, user) = connected_auth().await;
+        let pauses = Arc::new(PauseController::new(user));
+
+        let mut markets = Vec::new();
+        for id in ["won", "paused", "open", "redeemed"] {
+            markets.push(minted_market(id, auth_manager.clone(), pauses.clone()).await);
+        }
+        markets[0].resolve(true).await.unwrap();
+        markets[1].resolve(false).await.unwrap();
+        pauses.set_market_paused("paused", true);
+        markets[3].resolve(false).await.unwrap();
+        assert_eq!(markets[3].redeem_all().unwrap(), vec![(user, U256::from(100))]);
+
+        // Unresolved and already redeemed markets have nothing to claim
+        let claims = claim_all(&mut markets).await;
+        assert_eq!(claims.len(), 2);
+        assert_eq!(claims[0].market_id, "won");
+        assert_eq!(claims[0].result.as_ref().unwrap(), &U256::from(100));
+        assert!(matches!(claims[1].result, Err(MarketError::MarketPaused)));
+        assert_eq!(markets[0].accounts.balance(user), U256::from(100));
+    }
+}
@@ -1,4 +1,8 @@
-use ethers::types::{Address, H256};
+use crate::market::{MarketMetadata, ParentCondition};
+use crate::order_book::FillKind;
+use crate::liquidity_rewards::RewardProgram;
+use crate::order_types::SelfTradePrevention;
+use ethers::types::{Address, H256, U256};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +15,87 @@ pub enum MarketEvent {
         oracle_id: Address,
         yes_token: String,
         no_token: String,
+        metadata: MarketMetadata,
+        parent_condition: Option<ParentCondition>,
+        timestamp: u64,
+    },
+    MarketActivated {
+        market_id: String,
+        parent_market_id: String,
+        timestamp: u64,
+    },
+    MarketVoided {
+        market_id: String,
+        parent_market_id: String,
+        refunded: U256, // Collateral returned for complete sets
+        timestamp: u64,
+    },
+    /// `market_id` is `None` when the whole factory is paused.
+    Paused {
+        market_id: Option<String>,
+        pauser: Address,
+        reason: String,
+        timestamp: u64,
+    },
+    Unpaused {
+        market_id: Option<String>,
+        pauser: Address,
+        timestamp: u64,
+    },
+    TradingHalted {
+        market_id: String,
+        reference_price: u64,
+        price: u64,
+        move_bps: u64,
+        halted_until: u64,
+        timestamp: u64,
+    },
+    TradingResumed {
+        market_id: String,
+        timestamp: u64,
+    },
+    EarlyResolutionProposed {
+        market_id: String,
+        oracle: Address,
+        outcome: bool,
+        event_timestamp: u64,
+        timestamp: u64,
+    },
+    EarlyResolutionCancelled {
+        market_id: String,
+        oracle: Address,
+        timestamp: u64,
+    },
+    TradesFlagged {
+        market_id: String,
+        event_timestamp: u64,
+        trade_ids: Vec<u64>,
+        timestamp: u64,
+    },
+    GroupCreated {
+        group_id: String,
+        title: String,
+        market_ids: Vec<String>,
+        creator: Address,
+        timestamp: u64,
+    },
+    GroupResolved {
+        group_id: String,
+        winning_market_id: String,
+        timestamp: u64,
+    },
+    NegativeRiskConverted {
+        group_id: String,
+        user: Address,
+        target_market_id: String,
+        amount: u64,
+        collateral_released: U256,
+        timestamp: u64,
+    },
+    MetadataUpdated {
+        market_id: String,
+        editor: Address,
+        metadata: MarketMetadata,
         timestamp: u64,
     },
     TokensMinted {
@@ -37,7 +122,8 @@ pub enum MarketEvent {
     WinningsClaimed {
         market_id: String,
         user: Address,
-        amount: u64,
+        amount: U256,
+        fee: U256,
         timestamp: u64,
         tx_hash: H256,
     },
@@ -50,6 +136,109 @@ pub enum MarketEvent {
         timestamp: u64,
         tx_hash: H256,
     },
+    TradeExecuted {
+        market_id: String,
+        trade_id: u64,
+        maker: Address,
+        taker: Address,
+        is_yes: bool,
+        side: String,
+        price: u64, // In the taker's outcome
+        amount: u64,
+        kind: FillKind,
+        taker_fee: U256,
+        maker_rebate: U256,
+        creator_fee: U256,
+        timestamp: u64,
+    },
+    SelfTradePrevented {
+        market_id: String,
+        user: Address,
+        order_id: u64,
+        mode: SelfTradePrevention,
+        cancelled_size: u64, // Taken off the incoming order
+        resting_order_ids: Vec<u64>,
+        resting_size: u64, // Taken off the user's resting orders
+        timestamp: u64,
+    },
+    BatchAuctionStarted {
+        market_id: String,
+        interval: u64,
+        ends_at: Option<u64>,
+        timestamp: u64,
+    },
+    BatchAuctionCleared {
+        market_id: String,
+        clearing_price: u64, // YES price; NO traded at `100 - clearing_price`
+        volume: u64,
+        trade_ids: Vec<u64>,
+        timestamp: u64,
+    },
+    BatchAuctionEnded {
+        market_id: String,
+        timestamp: u64,
+    },
+    /// `program` is `None` when the program ended and its budget was refunded.
+    ReferralRegistered {
+        user: Address,
+        referrer: Address,
+        timestamp: u64,
+    },
+    /// The referred user's first trade, from which the referrer shares their taker fees.
+    ReferralLinked {
+        market_id: String,
+        user: Address,
+        referrer: Address,
+        timestamp: u64,
+    },
+    ReferralFeeCredited {
+        market_id: String,
+        referrer: Address,
+        user: Address,
+        trade_id: u64,
+        amount: U256,
+        timestamp: u64,
+    },
+    LiquidityRewardsConfigured {
+        market_id: String,
+        program: Option<RewardProgram>,
+        timestamp: u64,
+    },
+    LiquidityRewardsFunded {
+        market_id: String,
+        funder: Address,
+        amount: U256,
+        budget: U256, // Unspent budget after funding
+        timestamp: u64,
+    },
+    LiquidityRewardsSampled {
+        market_id: String,
+        midpoint: Option<u64>, // YES midpoint in half cents; None for a one-sided book
+        scores: Vec<(Address, u64)>,
+        distributed: U256,
+        timestamp: u64,
+    },
+    LiquidityRewardsClaimed {
+        market_id: String,
+        user: Address,
+        amount: U256,
+        timestamp: u64,
+    },
+    StopOrderPlaced {
+        market_id: String,
+        user: Address,
+        order_id: u64,
+        stop_price: u64,
+        timestamp: u64,
+    },
+    StopOrderTriggered {
+        market_id: String,
+        user: Address,
+        order_id: u64,
+        last_price: u64,
+        error: Option<String>, // Set if the triggered order could not be executed
+        timestamp: u64,
+    },
     OrderCancelled {
         market_id: String,
         user: Address,
@@ -57,6 +246,15 @@ pub enum MarketEvent {
         timestamp: u64,
         tx_hash: H256,
     },
+    OrderAmended {
+        market_id: String,
+        user: Address,
+        order_id: u64,
+        price: u64,
+        remaining: u64,
+        kept_priority: bool, // False if the order moved to the back of the queue
+        timestamp: u64,
+    },
     OrdersCancelled {
         market_id: String,
         user: Address,
@@ -69,21 +267,36 @@ pub enum MarketEvent {
     CollateralDeposited {
         market_id: String,
         user: Address,
-        amount: u64,
+        amount: U256,
         timestamp: u64,
         tx_hash: H256,
     },
     CollateralWithdrawn {
         market_id: String,
         user: Address,
-        amount: u64,
+        amount: U256,
         timestamp: u64,
         tx_hash: H256,
     },
     OracleAdded {
         oracle_id: Address,
         timestamp: u64,
-    }
+    },
+    ListingFeeCollected {
+        market_id: String,
+        creator: Address,
+        token: String,
+        amount: U256,
+        timestamp: u64,
+        tx_hash: H256,
+    },
+    FeesWithdrawn {
+        token: String,
+        recipient: Address,
+        amount: U256,
+        timestamp: u64,
+        tx_hash: H256,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,162 @@
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Trading and settlement fees for a market, in basis points.
+///
+/// The taker fee is charged on the notional of every fill and the maker rebate is paid
+/// out of it. Whatever is left, together with the redemption fee charged on winnings,
+/// is split between the market creator (`creator_share_bps`) and the treasury.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub taker_fee_bps: u64,
+    pub maker_rebate_bps: u64,
+    pub redemption_fee_bps: u64,
+    pub creator_share_bps: u64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            taker_fee_bps: 30,
+            maker_rebate_bps: 10,
+            redemption_fee_bps: 50,
+            creator_share_bps: 2_000,
+        }
+    }
+}
+
+/// How the fee on a single fill is distributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FillFees {
+    pub taker_fee: U256,
+    pub maker_rebate: U256,
+    pub creator_fee: U256,
+    pub treasury_fee: U256,
+}
+
+/// How the fee on a redemption is distributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RedemptionFees {
+    pub fee: U256,
+    pub creator_fee: U256,
+    pub treasury_fee: U256,
+}
+
+impl FeeSchedule {
+    pub fn zero() -> Self {
+        Self {
+            taker_fee_bps: 0,
+            maker_rebate_bps: 0,
+            redemption_fee_bps: 0,
+            creator_share_bps: 0,
+        }
+    }
+
+    /// Rebates are funded by the taker fee, so they can never exceed it.
+    pub fn is_valid(&self) -> bool {
+        self.taker_fee_bps <= BPS_DENOMINATOR
+            && self.redemption_fee_bps <= BPS_DENOMINATOR
+            && self.creator_share_bps <= BPS_DENOMINATOR
+            && self.maker_rebate_bps <= self.taker_fee_bps
+    }
+
+    pub fn fill_fees(&self, notional: U256) -> FillFees {
+        self.split_taker_fee(apply_bps(notional, self.taker_fee_bps), notional)
+    }
+
+    /// Splits a taker fee that was worked out separately, e.g. rounded over a whole order.
+    /// The maker rebate on `notional` is capped at the fee that funds it.
+    pub fn split_taker_fee(&self, taker_fee: U256, notional: U256) -> FillFees {
+        let maker_rebate = apply_bps(notional, self.maker_rebate_bps).min(taker_fee);
+        let net = taker_fee - maker_rebate;
+        let creator_fee = apply_bps(net, self.creator_share_bps);
+        FillFees {
+            taker_fee,
+            maker_rebate,
+            creator_fee,
+            treasury_fee: net - creator_fee,
+        }
+    }
+
+    pub fn redemption_fees(&self, payout: U256) -> RedemptionFees {
+        let fee = apply_bps(payout, self.redemption_fee_bps);
+        let creator_fee = apply_bps(fee, self.creator_share_bps);
+        RedemptionFees {
+            fee,
+            creator_fee,
+            treasury_fee: fee - creator_fee,
+        }
+    }
+}
+
+/// Fee settings shared between the factory and its markets.
+///
+/// Holds the treasury account markets sweep their protocol fees to, and the admin's
+/// per-market schedule overrides so a change also reaches markets already trading.
+#[derive(Debug)]
+pub struct FeeController {
+    treasury: Address,
+    schedules: RwLock<HashMap<String, FeeSchedule>>,
+}
+
+impl FeeController {
+    pub fn new(treasury: Address) -> Self {
+        Self {
+            treasury,
+            schedules: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn treasury(&self) -> Address {
+        self.treasury
+    }
+
+    pub fn schedule(&self, market_id: &str) -> Option<FeeSchedule> {
+        self.schedules.read().unwrap().get(market_id).copied()
+    }
+
+    pub fn set_schedule(&self, market_id: &str, schedule: FeeSchedule) {
+        self.schedules.write().unwrap().insert(market_id.to_string(), schedule);
+    }
+}
+
+/// Rounds down, so fees never exceed the configured rate.
+pub fn apply_bps(amount: U256, bps: u64) -> U256 {
+    amount * U256::from(bps) / U256::from(BPS_DENOMINATOR)
+}
+
+/// Rounds up, for fees a payer could otherwise avoid by splitting into small amounts.
+pub fn apply_bps_up(amount: U256, bps: u64) -> U256 {
+    (amount * U256::from(bps) + U256::from(BPS_DENOMINATOR - 1)) / U256::from(BPS_DENOMINATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_fees_split() {
+        let schedule = FeeSchedule::default();
+        let fees = schedule.fill_fees(U256::from(100_000));
+
+        assert_eq!(fees.taker_fee, U256::from(300));
+        assert_eq!(fees.maker_rebate, U256::from(100));
+        assert_eq!(fees.creator_fee, U256::from(40));
+        assert_eq!(fees.treasury_fee, U256::from(160));
+    }
+
+    #[test]
+    fn test_rebate_cannot_exceed_taker_fee() {
+        let schedule = FeeSchedule {
+            taker_fee_bps: 10,
+            maker_rebate_bps: 20,
+            ..FeeSchedule::default()
+        };
+        assert!(!schedule.is_valid());
+        assert!(FeeSchedule::default().is_valid());
+    }
+}
@@ -1,52 +1,417 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use ethers::types::{U256, H256};
+use async_trait::async_trait;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::keccak256;
+use hyperliquid_rust_sdk::{
+    BaseUrl, ClientCancelRequest, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient,
+    ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
+};
 use crate::auth::AuthManager;
+use crate::market_data::{MarketDataClient, MarketDataConfig, MarketDataStream, Subscription};
+use crate::venue::{OrderRequest, OrderSide, PlacedOrder, VenueClient, VenueError, VenueFill};
+use crate::venue_policy::{weights, VenuePolicy, VenuePolicyConfig};
+use serde::{Deserialize, Serialize};
 use tokio::runtime::Runtime;
 
-#[derive(Debug, Clone)]
+/// The spot token names a market's outcome tokens were deployed under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutcomeTokens {
+    pub yes: String,
+    pub no: String,
+}
+
+#[derive(Clone)]
 pub struct HyperliquidClient {
     auth_manager: Arc<AuthManager>,
+    info: Option<Arc<InfoClient>>,
+    exchange: Option<Arc<ExchangeClient>>,
+    market_data: Option<MarketDataClient>,
+    policy: Arc<VenuePolicy>,
+    outcome_tokens: HashMap<String, OutcomeTokens>, // Per market id
+}
+
+impl std::fmt::Debug for HyperliquidClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HyperliquidClient")
+            .field("auth_manager", &self.auth_manager)
+            .field("connected", &self.exchange.is_some())
+            .field("policy", &self.policy)
+            .field("outcome_tokens", &self.outcome_tokens)
+            .finish()
+    }
 }
 
 impl Default for HyperliquidClient {
     fn default() -> Self {
         let rt = Runtime::new().unwrap();
-        Self {
-            auth_manager: Arc::new(rt.block_on(AuthManager::new("http://localhost:8545")).unwrap()),
-        }
+        Self::new(Arc::new(rt.block_on(AuthManager::new("http://localhost:8545")).unwrap()))
     }
 }
 
 impl HyperliquidClient {
+    /// Creates a client that is not yet connected to the exchange. Every venue call
+    /// fails until `connect` is used instead.
     pub fn new(auth_manager: Arc<AuthManager>) -> Self {
         Self {
             auth_manager,
+            info: None,
+            exchange: None,
+            market_data: None,
+            policy: Arc::new(VenuePolicy::default()),
+            outcome_tokens: HashMap::new(),
+        }
+    }
+
+    /// Connects the info and exchange APIs at `api_url`, signing with the wallet held by
+    /// `auth_manager`.
+    pub async fn connect(auth_manager: Arc<AuthManager>, api_url: &str) -> Result<Self, VenueError> {
+        let wallet = auth_manager.get_wallet()
+            .map_err(|e| VenueError::Auth(e.to_string()))?;
+        let info = InfoClient::new(None, Some(base_url_for(api_url)))
+            .await
+            .map_err(classify_sdk_error)?;
+        let exchange = ExchangeClient::new(None, wallet, Some(base_url_for(api_url)), None, None)
+            .await
+            .map_err(classify_sdk_error)?;
+
+        Ok(Self {
+            info: Some(Arc::new(info)),
+            exchange: Some(Arc::new(exchange)),
+            market_data: Some(MarketDataClient::connect(ws_url_for(api_url), MarketDataConfig::default())),
+            ..Self::new(auth_manager)
+        })
+    }
+
+    /// Replaces the default timeout, retry and rate limit policy.
+    pub fn with_policy(mut self, config: VenuePolicyConfig) -> Self {
+        self.policy = Arc::new(VenuePolicy::new(config));
+        self
+    }
+
+    /// Names the spot tokens each market's outcome tokens were deployed under, keyed by
+    /// market id. `create_market_pair` only knows the markets named here.
+    pub fn with_outcome_tokens(mut self, outcome_tokens: HashMap<String, OutcomeTokens>) -> Self {
+        self.outcome_tokens = outcome_tokens;
+        self
+    }
+
+    /// Subscribes to a websocket channel. Updates arrive on the returned stream, which
+    /// survives reconnects; see `MarketDataClient`.
+    pub fn subscribe(&self, subscription: Subscription) -> Result<MarketDataStream, VenueError> {
+        self.market_data
+            .as_ref()
+            .ok_or_else(not_connected)?
+            .subscribe(subscription)
+            .map_err(VenueError::Network)
+    }
+
+    fn info(&self) -> Result<&InfoClient, VenueError> {
+        self.info.as_deref().ok_or_else(not_connected)
+    }
+
+    fn exchange(&self) -> Result<&ExchangeClient, VenueError> {
+        self.exchange.as_deref().ok_or_else(not_connected)
+    }
+
+    /// Only the connected wallet can act on the exchange.
+    fn ensure_owner(&self, owner: Address) -> Result<(), VenueError> {
+        let caller = self.auth_manager.get_current_address()
+            .map_err(|e| VenueError::Auth(e.to_string()))?;
+        if caller != owner {
+            return Err(VenueError::Auth(format!("Connected wallet {:?} cannot act for {:?}", caller, owner)));
         }
+        Ok(())
+    }
+
+    /// Sends spot collateral from `from`, which must be the connected wallet, to `to`. Spot
+    /// transfers name the token as `NAME:token_id`, so the id is looked up in the spot meta.
+    async fn spot_send(&self, from: Address, to: Address, token: &str, amount: U256) -> Result<H256, VenueError> {
+        self.ensure_owner(from)?;
+
+        let meta = self.policy
+            .execute(weights::INFO, true, || async {
+                self.info()?.spot_meta().await.map_err(classify_sdk_error)
+            })
+            .await?;
+        let token_id = meta.tokens
+            .iter()
+            .find(|info| info.name == token)
+            .map(|info| format!("{}:{:?}", info.name, info.token_id))
+            .ok_or_else(|| VenueError::Rejected(format!("Token {} is not listed on Hyperliquid", token)))?;
+        let destination = format!("{:?}", to);
+        let amount = collateral_amount(amount)?.to_string();
+
+        self.policy
+            .execute(weights::EXCHANGE_ACTION, false, || async {
+                let response = self.exchange()?
+                    .spot_transfer(&amount, &destination, &token_id, None)
+                    .await
+                    .map_err(classify_sdk_error)?;
+                first_status(response).map(|_| ())
+            })
+            .await?;
+
+        Ok(action_id(&format!("{:?}:{:?}:{}:{}", from, to, token, amount)))
+    }
+}
+
+/// Hyperliquid actions have no transaction hash; identify the action instead.
+fn action_id(action: &str) -> H256 {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    H256::from(keccak256(format!("{}:{}", action, timestamp)))
+}
+
+fn not_connected() -> VenueError {
+    VenueError::Auth("Hyperliquid client is not connected".to_string())
+}
+
+fn base_url_for(api_url: &str) -> BaseUrl {
+    if api_url.contains("testnet") {
+        BaseUrl::Testnet
+    } else if api_url.contains("localhost") || api_url.contains("127.0.0.1") {
+        BaseUrl::Localhost
+    } else {
+        BaseUrl::Mainnet
     }
+}
+
+fn ws_url_for(api_url: &str) -> String {
+    format!("{}/ws", api_url.trim_end_matches('/').replacen("http", "ws", 1))
+}
 
-    pub async fn create_market_pair(
+fn classify_sdk_error(error: hyperliquid_rust_sdk::Error) -> VenueError {
+    use hyperliquid_rust_sdk::Error as SdkError;
+
+    match error {
+        SdkError::ClientRequest { status_code: 429, .. } => VenueError::RateLimited,
+        SdkError::ClientRequest { status_code: 401 | 403, error_message, .. } => VenueError::Auth(error_message),
+        SdkError::ClientRequest { error_message, .. } => VenueError::Rejected(error_message),
+        SdkError::ServerRequest { error_message, .. } => VenueError::Transient(error_message),
+        SdkError::GenericRequest(message) => VenueError::Network(message),
+        other => VenueError::Rejected(other.to_string()),
+    }
+}
+
+/// The exchange reports action failures as plain strings.
+fn classify_exchange_error(message: String) -> VenueError {
+    let lowercase = message.to_lowercase();
+    if lowercase.contains("rate limit") {
+        VenueError::RateLimited
+    } else if lowercase.contains("does not exist") || lowercase.contains("signature") {
+        VenueError::Auth(message)
+    } else {
+        VenueError::Rejected(message)
+    }
+}
+
+fn first_status(response: ExchangeResponseStatus) -> Result<Option<ExchangeDataStatus>, VenueError> {
+    match response {
+        ExchangeResponseStatus::Ok(response) => Ok(response
+            .data
+            .and_then(|data| data.statuses.into_iter().next())),
+        ExchangeResponseStatus::Err(e) => Err(classify_exchange_error(e)),
+    }
+}
+
+/// Collateral amounts are whole units of the collateral token, what one winning outcome token
+/// redeems for. The exchange takes them as a float (or its decimal string for spot transfers),
+/// which is only exact up to 2^53.
+fn collateral_amount(amount: U256) -> Result<f64, VenueError> {
+    if amount > U256::from(1u64 << f64::MANTISSA_DIGITS) {
+        return Err(VenueError::Rejected(format!("Collateral amount too large: {}", amount)));
+    }
+    Ok(amount.low_u64() as f64)
+}
+
+fn cents_to_px(cents: u64) -> f64 {
+    cents as f64 / 100.0
+}
+
+fn px_to_cents(px: &str) -> Result<u64, VenueError> {
+    let px: f64 = px.parse().map_err(|_| VenueError::InvalidResponse(format!("Invalid price: {}", px)))?;
+    Ok((px * 100.0).round() as u64)
+}
+
+fn parse_size(sz: &str) -> Result<u64, VenueError> {
+    let sz: f64 = sz.parse().map_err(|_| VenueError::InvalidResponse(format!("Invalid size: {}", sz)))?;
+    Ok(sz.floor() as u64)
+}
+
+#[async_trait]
+impl VenueClient for HyperliquidClient {
+    /// Outcome tokens are deployed through Hyperliquid's spot deploy process, so this
+    /// resolves the already-listed YES/NO spot pairs against the collateral token. The
+    /// tokens must have been named with `with_outcome_tokens`.
+    async fn create_market_pair(
         &self,
         market_id: &str,
-        _collateral_token: &str,
-    ) -> Result<(String, String), String> {
-        // Create YES token market
-        let yes_token = format!("{}_YES", market_id);
+        collateral_token: &str,
+    ) -> Result<(String, String), VenueError> {
+        let tokens = self.outcome_tokens.get(market_id).ok_or_else(|| {
+            VenueError::Rejected(format!("No outcome tokens registered for market {}", market_id))
+        })?;
+        let meta = self.policy
+            .execute(weights::INFO, true, || async {
+                self.info()?.spot_meta().await.map_err(classify_sdk_error)
+            })
+            .await?;
+        let token_index = |name: &str| {
+            meta.tokens
+                .iter()
+                .find(|token| token.name == name)
+                .map(|token| token.index)
+                .ok_or_else(|| VenueError::Rejected(format!("Token {} is not listed on Hyperliquid", name)))
+        };
+        let collateral_index = token_index(collateral_token)?;
+        let pair_name = |name: &str| -> Result<String, VenueError> {
+            let index = token_index(name)?;
+            meta.universe
+                .iter()
+                .find(|pair| pair.tokens == [index, collateral_index])
+                .map(|pair| pair.name.clone())
+                .ok_or_else(|| VenueError::Rejected(format!("No {}/{} spot pair on Hyperliquid", name, collateral_token)))
+        };
 
-        // Create NO token market
-        let no_token = format!("{}_NO", market_id);
+        Ok((pair_name(&tokens.yes)?, pair_name(&tokens.no)?))
+    }
 
-        Ok((yes_token, no_token))
+    /// Sends the collateral to the custody wallet, so the connected wallet must be the owner.
+    async fn deposit_collateral(
+        &self,
+        owner: Address,
+        custody: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError> {
+        self.spot_send(owner, custody, token, amount).await
     }
 
-    pub async fn deposit_collateral(
+    /// Sends the collateral back from the custody wallet, so the connected wallet must be
+    /// the custody wallet.
+    async fn withdraw_collateral(
         &self,
-        _token: &str,
-        _amount: U256,
-    ) -> Result<H256, String> {
-        let _caller = self.auth_manager.get_current_address()
-            .map_err(|e| e.to_string())?;
+        custody: Address,
+        owner: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError> {
+        self.spot_send(custody, owner, token, amount).await
+    }
 
-        // Simplified for now - would integrate with actual Hyperliquid API
-        Ok(H256::zero())
+    async fn transfer_collateral(
+        &self,
+        from: Address,
+        to: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError> {
+        self.spot_send(from, to, token, amount).await
+    }
+
+    async fn place_order(&self, order: OrderRequest) -> Result<PlacedOrder, VenueError> {
+        self.ensure_owner(order.owner)?;
+
+        let response = self.policy
+            .execute(weights::EXCHANGE_ACTION, false, || async {
+                self.exchange()?
+                    .order(
+                        ClientOrderRequest {
+                            asset: order.token.clone(),
+                            is_buy: order.side == OrderSide::Buy,
+                            reduce_only: false,
+                            limit_px: cents_to_px(order.price),
+                            sz: order.size as f64,
+                            cloid: None,
+                            order_type: ClientOrder::Limit(ClientLimit {
+                                tif: "Gtc".to_string(),
+                            }),
+                        },
+                        None,
+                    )
+                    .await
+                    .map_err(classify_sdk_error)
+            })
+            .await?;
+
+        match first_status(response)? {
+            Some(ExchangeDataStatus::Resting(resting)) => Ok(PlacedOrder {
+                order_id: resting.oid,
+                filled_size: 0,
+                resting_size: order.size,
+            }),
+            Some(ExchangeDataStatus::Filled(filled)) => {
+                let filled_size = parse_size(&filled.total_sz)?;
+                Ok(PlacedOrder {
+                    order_id: filled.oid,
+                    filled_size,
+                    resting_size: order.size.saturating_sub(filled_size),
+                })
+            }
+            Some(ExchangeDataStatus::Error(e)) => Err(classify_exchange_error(e)),
+            other => Err(VenueError::UnknownOutcome(format!("Unexpected order status: {:?}", other))),
+        }
     }
-} 
\ No newline at end of file
+
+    async fn cancel_order(&self, owner: Address, token: &str, order_id: u64) -> Result<(), VenueError> {
+        self.ensure_owner(owner)?;
+
+        let response = self.policy
+            .execute(weights::EXCHANGE_ACTION, true, || async {
+                self.exchange()?
+                    .cancel(
+                        ClientCancelRequest {
+                            asset: token.to_string(),
+                            oid: order_id,
+                        },
+                        None,
+                    )
+                    .await
+                    .map_err(classify_sdk_error)
+            })
+            .await?;
+
+        match first_status(response)? {
+            Some(ExchangeDataStatus::Error(e)) => Err(classify_exchange_error(e)),
+            _ => Ok(()),
+        }
+    }
+
+    async fn balance(&self, owner: Address, token: &str) -> Result<U256, VenueError> {
+        let balances = self.policy
+            .execute(weights::LIGHT_INFO, true, || async {
+                self.info()?.user_token_balances(owner).await.map_err(classify_sdk_error)
+            })
+            .await?;
+
+        match balances.balances.iter().find(|balance| balance.coin == token) {
+            Some(balance) => Ok(U256::from(parse_size(&balance.total)?)),
+            None => Ok(U256::zero()),
+        }
+    }
+
+    async fn fills(&self, owner: Address) -> Result<Vec<VenueFill>, VenueError> {
+        let fills = self.policy
+            .execute(weights::INFO, true, || async {
+                self.info()?.user_fills(owner).await.map_err(classify_sdk_error)
+            })
+            .await?;
+
+        fills
+            .into_iter()
+            .map(|fill| {
+                Ok(VenueFill {
+                    order_id: fill.oid,
+                    token: fill.coin,
+                    side: if fill.side == "B" { OrderSide::Buy } else { OrderSide::Sell },
+                    price: px_to_cents(&fill.px)?,
+                    size: parse_size(&fill.sz)?,
+                    timestamp: fill.time / 1000,
+                })
+            })
+            .collect()
+    }
+}
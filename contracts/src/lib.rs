@@ -1,23 +1,60 @@
+pub mod accounts;
 pub mod auth;
+pub mod batch_auction;
+pub mod circuit_breaker;
+pub mod claims;
 pub mod events;
+pub mod fees;
 pub mod hyperliquid_client;
+pub mod limits;
+pub mod liquidity_rewards;
 pub mod market;
+pub mod market_data;
 pub mod market_factory;
+pub mod market_group;
+pub mod market_maker;
 pub mod oracle;
+pub mod order_book;
+pub mod order_types;
+pub mod pause;
+pub mod referrals;
+pub mod venue;
+pub mod templates;
+pub mod validation;
+pub mod venue_policy;
 
-pub use auth::{AuthError, AuthManager};
+pub use accounts::CollateralAccounts;
+pub use auth::{AuthError, AuthManager, NonceManager, SignedRequest};
+pub use batch_auction::{BatchAuction, Clearing};
+pub use claims::{claim_all, MarketClaim};
+pub use circuit_breaker::{CircuitBreaker, PriceWindow, Trip};
 pub use events::{EventEmitter, EventLogger, MarketEvent, OracleEvent};
-pub use hyperliquid_client::HyperliquidClient;
-pub use market::{Market, MarketError, MarketStatus};
+pub use fees::{FeeController, FeeSchedule};
+pub use hyperliquid_client::{HyperliquidClient, OutcomeTokens};
+pub use limits::{ExposureLimits, LimitExceeded};
+pub use liquidity_rewards::{RewardLedger, RewardProgram, ScoringFormula};
+pub use market::{EarlyResolution, Market, MarketError, MarketMetadata, MarketStatus, MetadataRevision, ParentCondition, TradeRecord};
+pub use market_data::{MarketDataClient, MarketDataConfig, MarketDataEvent, Subscription};
 pub use market_factory::{MarketFactory, MarketFactoryError, MarketFactoryEvent, MarketFactoryState};
+pub use market_group::{GroupError, MarketGroup};
+pub use market_maker::{FairValue, MarketMaker, Quote, QuoteConfig};
 pub use oracle::{OracleError, OracleManager, OracleManagerState};
+pub use order_book::{BookOrder, Fill, FillKind, MatchResult, OrderBook, Released, UnifiedBook};
+pub use order_types::{NewOrder, OrderType, SelfTradePrevention, StopOrder, TimeInForce};
+pub use pause::PauseController;
+pub use referrals::{Referral, ReferralError, ReferralRegistry};
+pub use templates::{ExpiryRule, MarketSpec, MarketTemplate, TemplateError, TemplateParams};
+pub use validation::{MarketProposal, ValidationRules, Violation};
+pub use venue::{OrderRequest, OrderSide, PlacedOrder, SimulatedVenue, VenueClient, VenueError, VenueFill};
+pub use venue_policy::{VenuePolicy, VenuePolicyConfig};
 
 use async_trait::async_trait;
+use ethers::types::U256;
 
 #[async_trait]
 pub trait MarketContract {
     async fn mint_tokens(&mut self, amount: u64) -> Result<(), MarketError>;
     async fn burn_tokens(&mut self, yes_amount: u64, no_amount: u64) -> Result<(), MarketError>;
     async fn resolve(&mut self, outcome: bool) -> Result<(), MarketError>;
-    async fn claim_winnings(&mut self) -> Result<u64, MarketError>;
+    async fn claim_winnings(&mut self) -> Result<U256, MarketError>;
 } 
\ No newline at end of file
@@ -0,0 +1,52 @@
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Risk caps for a single market. `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ExposureLimits {
+    /// Outstanding complete sets, i.e. the larger of the YES and NO supplies.
+    pub max_open_interest: Option<U256>,
+    /// Tokens of either outcome one address may hold, counting open buy orders.
+    pub max_position: Option<U256>,
+    /// Collateral locked in the market across all users: open buy orders' escrow and
+    /// outstanding complete sets.
+    pub max_total_collateral: Option<U256>,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LimitExceeded {
+    #[error("Open interest would be {resulting}, above the cap of {limit}")]
+    OpenInterest { limit: U256, resulting: U256 },
+    #[error("Position would be {resulting}, above the per-address cap of {limit}")]
+    Position { limit: U256, resulting: U256 },
+    #[error("Market collateral would be {resulting}, above the cap of {limit}")]
+    TotalCollateral { limit: U256, resulting: U256 },
+}
+
+impl ExposureLimits {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn check_open_interest(&self, resulting: U256) -> Result<(), LimitExceeded> {
+        match self.max_open_interest {
+            Some(limit) if resulting > limit => Err(LimitExceeded::OpenInterest { limit, resulting }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_position(&self, resulting: U256) -> Result<(), LimitExceeded> {
+        match self.max_position {
+            Some(limit) if resulting > limit => Err(LimitExceeded::Position { limit, resulting }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_total_collateral(&self, resulting: U256) -> Result<(), LimitExceeded> {
+        match self.max_total_collateral {
+            Some(limit) if resulting > limit => Err(LimitExceeded::TotalCollateral { limit, resulting }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::fees::BPS_DENOMINATOR;
+use crate::order_book::OrderBook;
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How a resting order's distance from the midpoint discounts its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringFormula {
+    /// Weight falls linearly to zero at `max_spread`.
+    Linear,
+    /// Weight falls with the square of the distance, favouring tight quotes.
+    #[default]
+    Quadratic,
+}
+
+/// Liquidity mining for one market. Every `sample_interval` seconds the resting orders within
+/// `max_spread` of the midpoint are scored, and `per_sample` of the funded budget is split
+/// between their owners by score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardProgram {
+    pub per_sample: u64, // Collateral paid out per sample
+    pub sample_interval: u64,
+    pub max_spread: u64, // Cents either side of the midpoint
+    pub min_size: u64, // Smaller orders don't score
+    #[serde(default)]
+    pub formula: ScoringFormula,
+}
+
+impl RewardProgram {
+    pub fn is_valid(&self) -> bool {
+        self.per_sample > 0 && self.sample_interval > 0 && self.max_spread > 0 && self.max_spread < 50
+    }
+
+    /// Scores every owner's resting orders on both books. Orders count in YES terms, so a NO
+    /// bid at 45 scores as a YES ask at 55. Returns the YES midpoint, in half cents, and
+    /// the scores, or `None` if either side of the book is empty.
+    pub fn score(&self, yes_book: &OrderBook, no_book: &OrderBook) -> Option<(u64, HashMap<Address, u64>)> {
+        let bids = yes_book.bids().iter().map(|o| (o, o.price))
+            .chain(no_book.asks().iter().map(|o| (o, 100 - o.price)));
+        let asks = yes_book.asks().iter().map(|o| (o, o.price))
+            .chain(no_book.bids().iter().map(|o| (o, 100 - o.price)));
+        let best_bid = bids.clone().map(|(_, price)| price).max()?;
+        let best_ask = asks.clone().map(|(_, price)| price).min()?;
+        // Doubled so the midpoint stays whole
+        let midpoint = best_bid + best_ask;
+
+        let mut scores = HashMap::new();
+        for (order, yes_price) in bids.chain(asks) {
+            if order.remaining < self.min_size {
+                continue;
+            }
+            let weight = self.weight(midpoint.abs_diff(2 * yes_price));
+            if weight > 0 {
+                // At most `remaining`, as the weight is at most BPS_DENOMINATOR
+                let score = u128::from(order.remaining) * u128::from(weight) / u128::from(BPS_DENOMINATOR);
+                let total = scores.entry(order.owner).or_default();
+                *total = total.saturating_add(score as u64);
+            }
+        }
+        scores.retain(|_, score| *score > 0);
+        Some((midpoint, scores))
+    }
+
+    /// Weight in basis points of an order `distance` half cents from the midpoint.
+    fn weight(&self, distance: u64) -> u64 {
+        let max_distance = 2 * self.max_spread;
+        let Some(closeness) = max_distance.checked_sub(distance) else { return 0 };
+        match self.formula {
+            ScoringFormula::Linear => closeness * BPS_DENOMINATOR / max_distance,
+            ScoringFormula::Quadratic => closeness * closeness * BPS_DENOMINATOR / (max_distance * max_distance),
+        }
+    }
+}
+
+/// Budget and accrued rewards of a market's program.
+#[derive(Debug, Clone, Default)]
+pub struct RewardLedger {
+    pub budget: U256, // Funded and not yet accrued to anyone
+    pub funders: HashMap<Address, U256>, // Refunded pro rata if the program ends early
+    pub scores: HashMap<Address, u64>, // From the last sample
+    pub accrued: HashMap<Address, U256>, // Not yet claimed
+    pub last_sampled_at: Option<u64>,
+}
+
+impl RewardLedger {
+    /// Splits `amount`, or what is left of the budget, by `scores`. Rounding dust stays in the
+    /// budget. Returns how much accrued.
+    pub fn distribute(&mut self, amount: u64, scores: HashMap<Address, u64>) -> U256 {
+        let amount = self.budget.min(U256::from(amount));
+        let total = scores.values().fold(U256::zero(), |total, &score| total + score);
+        let mut distributed = U256::zero();
+        if !total.is_zero() {
+            for (&owner, &score) in &scores {
+                let share = amount * U256::from(score) / total;
+                *self.accrued.entry(owner).or_default() += share;
+                distributed += share;
+            }
+        }
+        self.budget -= distributed;
+        self.scores = scores;
+        distributed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_book::BookOrder;
+    use crate::venue::OrderSide;
+
+    fn order(order_id: u64, owner: u64, side: OrderSide, price: u64, size: u64) -> BookOrder {
+        BookOrder {
+            order_id,
+            owner: Address::from_low_u64_be(owner),
+            side,
+            price,
+            remaining: size,
+            timestamp: order_id,
+            expires_at: None,
+            post_only: false,
+            self_trade_prevention: Default::default(),
+            taker_fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_scores_by_size_and_distance() {
+        let mut yes_book = OrderBook::new();
+        let mut no_book = OrderBook::new();
+        yes_book.rest(order(1, 1, OrderSide::Buy, 49, 100));
+        yes_book.rest(order(2, 2, OrderSide::Sell, 51, 100));
+        yes_book.rest(order(3, 2, OrderSide::Buy, 47, 100));
+        // Out of range at 10 cents from the midpoint
+        yes_book.rest(order(4, 3, OrderSide::Buy, 40, 1_000));
+        // A NO bid at 47 is a YES ask at 53
+        no_book.rest(order(5, 3, OrderSide::Buy, 47, 100));
+
+        let mut program = RewardProgram {
+            per_sample: 100,
+            sample_interval: 60,
+            max_spread: 4,
+            min_size: 10,
+            formula: ScoringFormula::Linear,
+        };
+        let (midpoint, scores) = program.score(&yes_book, &no_book).unwrap();
+        assert_eq!(midpoint, 100);
+        // 1 cent away weighs 3/4, 3 cents away 1/4
+        assert_eq!(scores[&Address::from_low_u64_be(1)], 75);
+        assert_eq!(scores[&Address::from_low_u64_be(2)], 100);
+        assert_eq!(scores[&Address::from_low_u64_be(3)], 25);
+
+        program.formula = ScoringFormula::Quadratic;
+        let (_, scores) = program.score(&yes_book, &no_book).unwrap();
+        assert_eq!(scores[&Address::from_low_u64_be(1)], 56);
+
+        let mut ledger = RewardLedger { budget: U256::from(150), ..Default::default() };
+        let (_, scores) = program.score(&yes_book, &no_book).unwrap();
+        assert_eq!(ledger.distribute(100, scores.clone()), U256::from(99));
+        assert_eq!(ledger.accrued[&Address::from_low_u64_be(2)], U256::from(50));
+        // Only what is left of the budget is paid out, less rounding dust
+        assert_eq!(ledger.distribute(100, scores), U256::from(50));
+        assert_eq!(ledger.budget, U256::from(1));
+    }
+
+    #[test]
+    fn test_huge_orders_do_not_overflow() {
+        let mut yes_book = OrderBook::new();
+        let no_book = OrderBook::new();
+        yes_book.rest(order(1, 1, OrderSide::Buy, 50, u64::MAX));
+        yes_book.rest(order(2, 1, OrderSide::Sell, 50, u64::MAX));
+        let program = RewardProgram {
+            per_sample: 100,
+            sample_interval: 60,
+            max_spread: 4,
+            min_size: 10,
+            formula: ScoringFormula::Linear,
+        };
+
+        let (_, scores) = program.score(&yes_book, &no_book).unwrap();
+        assert_eq!(scores[&Address::from_low_u64_be(1)], u64::MAX);
+
+        let mut ledger = RewardLedger { budget: U256::from(100), ..Default::default() };
+        let both = HashMap::from([(Address::from_low_u64_be(1), u64::MAX), (Address::from_low_u64_be(2), u64::MAX)]);
+        assert_eq!(ledger.distribute(100, both), U256::from(100));
+    }
+}
@@ -1,13 +1,25 @@
 use crate::{
+    accounts::CollateralAccounts,
     auth::{AuthManager, AuthError},
-    events::EventEmitter,
-    hyperliquid_client::HyperliquidClient,
+    batch_auction::{self, BatchAuction, Clearing},
+    circuit_breaker::{CircuitBreaker, PriceWindow},
+    events::{EventEmitter, MarketEvent},
+    fees::{apply_bps_up, FeeController, FeeSchedule},
+    limits::{ExposureLimits, LimitExceeded},
+    liquidity_rewards::{RewardLedger, RewardProgram},
+    market_group::MarketGroup,
+    order_book::{BookOrder, Fill, FillKind, MatchResult, OrderBook, Released, UnifiedBook},
+    order_types::{NewOrder, OrderType, StopOrder, TimeInForce},
+    pause::PauseController,
+    referrals::ReferralRegistry,
+    venue::{OrderSide, PlacedOrder, VenueClient, VenueError},
     MarketContract,
 };
 use async_trait::async_trait;
-use ethers::types::{Address, U256};
+use ethers::types::{Address, H256, U256};
+use ethers::utils::keccak256;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -33,7 +45,7 @@ pub enum MarketError {
     #[error("Authentication error: {0}")]
     AuthError(#[from] AuthError),
     #[error("API error: {0}")]
-    ApiError(String),
+    ApiError(#[from] VenueError),
     #[error("Insufficient collateral")]
     InsufficientCollateral,
     #[error("Invalid collateral token")]
@@ -50,10 +62,49 @@ pub enum MarketError {
     MarketSettlementFailed,
     #[error("Invalid signature")]
     InvalidSignature,
+    #[error("Market metadata cannot be edited after the first trade")]
+    MetadataLocked,
+    #[error("Grouped markets resolve through their group")]
+    GroupedMarket,
+    #[error("Market is paused")]
+    MarketPaused,
+    #[error("Trading is halted by the circuit breaker until {0}")]
+    TradingHalted(u64),
+    #[error("Exposure limit exceeded: {0}")]
+    LimitExceeded(#[from] LimitExceeded),
+    #[error("Trading is closed")]
+    TradingClosed,
+    #[error("No early resolution has been proposed")]
+    NoResolutionProposal,
+    #[error("Outcome does not match the proposed resolution")]
+    ProposalMismatch,
+    #[error("Event time must not be in the future")]
+    InvalidEventTime,
+    #[error("Post-only order would take liquidity")]
+    PostOnlyWouldCross,
+    #[error("Fill-or-kill order cannot be filled completely")]
+    FillOrKillUnfilled,
+    #[error("No liquidity on the opposite side of the book")]
+    NoLiquidity,
+    #[error("Only resting limit orders are accepted during a batch auction")]
+    BatchAuctionRestingOnly,
+    #[error("No batch auction is due to clear")]
+    BatchAuctionNotDue,
+    #[error("Order not found")]
+    OrderNotFound,
+    #[error("Market has no liquidity reward program")]
+    NoRewardProgram,
+    #[error("Market has no treasury")]
+    NoTreasury,
+    #[error("Market has no custody account")]
+    NoCustody,
+    #[error("No liquidity reward sample is due")]
+    RewardSampleNotDue,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Market {
+    pub id: String,
     pub question: String,
     pub expiry_timestamp: u64,
     pub oracle_id: String,
@@ -62,6 +113,113 @@ pub struct Market {
     pub yes_token_address: String,
     pub no_token_address: String,
     pub resolved_outcome: Option<bool>,
+    pub creator: Address,
+    pub fee_schedule: FeeSchedule,
+    #[serde(flatten)]
+    pub metadata: MarketMetadata,
+    pub metadata_history: Vec<MetadataRevision>, // Superseded metadata, oldest first
+    pub group_id: Option<String>, // Set when the market is part of a mutually exclusive group
+    #[serde(default)]
+    pub parent_condition: Option<ParentCondition>,
+    #[serde(default)]
+    pub circuit_breaker: Option<CircuitBreaker>,
+    #[serde(default)]
+    pub limits: ExposureLimits,
+    #[serde(default)]
+    pub trading_close_timestamp: Option<u64>, // No trading from this time on; None trades until resolution
+    #[serde(default)]
+    pub resolution_timestamp: Option<u64>, // Earliest regular resolution; None means at expiry
+    #[serde(default)]
+    pub early_resolution: Option<EarlyResolution>,
+    #[serde(default)]
+    pub batch_auction: Option<BatchAuction>,
+    #[serde(default)]
+    pub liquidity_rewards: Option<RewardProgram>,
+}
+
+impl Market {
+    /// When the oracle may resolve the market without an early resolution proposal.
+    pub fn resolution_time(&self) -> u64 {
+        self.resolution_timestamp.unwrap_or(self.expiry_timestamp)
+    }
+
+    /// Whether the oracle may resolve the market to `outcome` at `now`: it must be active or
+    /// expired, and past its resolution time unless an early resolution to `outcome` was
+    /// proposed. Callers check the oracle and pauses.
+    pub fn check_resolvable(&self, outcome: bool, now: u64) -> Result<(), MarketError> {
+        match self.status {
+            MarketStatus::Active | MarketStatus::Expired => {}
+            MarketStatus::Resolved => return Err(MarketError::MarketAlreadyResolved),
+            MarketStatus::Locked | MarketStatus::Voided => return Err(MarketError::MarketNotActive),
+        }
+        match &self.early_resolution {
+            Some(proposal) if proposal.outcome != outcome => Err(MarketError::ProposalMismatch),
+            Some(_) => Ok(()),
+            None if now < self.resolution_time() => Err(MarketError::MarketNotExpired),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An oracle's proposal to resolve before the scheduled resolution time, e.g. because the
+/// event already happened. Trading stops as soon as it is proposed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EarlyResolution {
+    pub outcome: bool,
+    pub event_timestamp: u64, // When the outcome actually became known
+    pub proposed_at: u64,
+}
+
+/// A fill as recorded by the market, kept so trades can be flagged after the fact.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TradeRecord {
+    pub trade_id: u64,
+    pub maker: Address,
+    pub taker: Address,
+    pub is_yes: bool,
+    pub price: u64, // In the taker's outcome (`is_yes`)
+    pub amount: u64,
+    pub timestamp: u64,
+    pub flagged: bool, // Executed at or after the event time of an early resolution
+}
+
+/// Makes a market conditional on another market's outcome, as in "If A resolves YES,
+/// will B happen?". The market stays locked until the parent resolves.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ParentCondition {
+    pub market_id: String,
+    pub required_outcome: bool,
+}
+
+impl ParentCondition {
+    /// The status a locked market moves to once its parent resolves to `outcome`.
+    pub fn status_after(&self, outcome: bool) -> MarketStatus {
+        if outcome == self.required_outcome {
+            MarketStatus::Active
+        } else {
+            MarketStatus::Voided
+        }
+    }
+}
+
+/// Descriptive information shown alongside the question.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MarketMetadata {
+    pub description: String,
+    pub rules: String, // Long-form resolution rules
+    pub resolution_source: String, // Where the outcome is determined, required by default validation rules
+    pub resolution_source_urls: Vec<String>,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub image_url: Option<String>,
+}
+
+/// Metadata as it was before an edit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MetadataRevision {
+    pub metadata: MarketMetadata,
+    pub replaced_by: Address,
+    pub replaced_at: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -69,6 +227,8 @@ pub enum MarketStatus {
     Active,
     Expired,
     Resolved,
+    Locked, // Conditional market waiting on its parent; complete sets can be minted but not traded
+    Voided, // The parent resolved against the condition and collateral was refunded
 }
 
 #[derive(Debug, Clone)]
@@ -78,11 +238,31 @@ pub struct MarketContractState {
     pub yes_token_supply: U256,
     pub no_token_supply: U256,
     pub user_balances: HashMap<Address, (U256, U256)>, // (yes_tokens, no_tokens)
-    pub collateral_balances: HashMap<Address, U256>, // User collateral balances
-    pub total_collateral: U256, // Total collateral in the market
+    pub accounts: Arc<CollateralAccounts>, // Free collateral; shared between markets under portfolio margin
+    pub portfolio_margin: bool, // Set by `with_accounts`; winnings are credited at resolution
+    pub total_collateral: U256, // Total collateral deposited through this market
+    pub custody: Option<Address>, // Venue account holding deposited collateral; set by `with_custody`
+    pub yes_book: OrderBook,
+    pub no_book: OrderBook,
+    pub next_order_id: u64,
+    pub order_escrow: HashMap<u64, U256>, // Hundredths of collateral locked by open buy orders, fees included
+    pub treasury_fees: U256, // Fees accrued to the protocol treasury, not yet swept
+    pub rounding_dust: U256, // Hundredths of collateral left over from paying out whole units
+    pub creator_fees: U256, // Fees accrued to the market creator
+    pub referral_fees: HashMap<Address, U256>, // Referrers' shares of taker fees, not yet claimed
+    pub first_trade_at: Option<u64>,
+    pub price_window: PriceWindow, // Recent YES-equivalent trade prices for the circuit breaker
+    pub halted_until: Option<u64>, // Set while a circuit breaker halt is in effect
+    pub trades: Vec<TradeRecord>,
+    pub stop_orders: Vec<StopOrder>, // Waiting for the last trade price to reach their trigger
+    pub batch_closes_at: Option<u64>, // When the current batch clears, while in batch auction mode
+    pub rewards: RewardLedger, // Liquidity mining budget and accruals
+    pub pause_controller: Arc<PauseController>,
+    pub referrals: Option<Arc<ReferralRegistry>>,
+    pub fee_controller: Option<Arc<FeeController>>,
     pub auth_manager: Arc<AuthManager>,
     pub event_emitter: Arc<dyn EventEmitter>,
-    pub client: HyperliquidClient,
+    pub client: Arc<dyn VenueClient>,
 }
 
 impl MarketContractState {
@@ -90,41 +270,2349 @@ impl MarketContractState {
         market: Market,
         auth_manager: Arc<AuthManager>,
         event_emitter: Arc<dyn EventEmitter>,
-        client: HyperliquidClient,
+        client: Arc<dyn VenueClient>,
     ) -> Self {
+        let pause_controller = Arc::new(PauseController::new(market.creator));
+        let batch_closes_at = market.batch_auction.map(|auction| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                + auction.interval
+        });
         Self {
             market,
             yes_token_supply: U256::zero(),
             no_token_supply: U256::zero(),
             user_balances: Default::default(),
-            collateral_balances: Default::default(),
+            accounts: Arc::new(CollateralAccounts::new()),
+            portfolio_margin: false,
             total_collateral: U256::zero(),
+            custody: None,
+            yes_book: OrderBook::new(),
+            no_book: OrderBook::new(),
+            next_order_id: 0,
+            order_escrow: HashMap::new(),
+            treasury_fees: U256::zero(),
+            rounding_dust: U256::zero(),
+            creator_fees: U256::zero(),
+            referral_fees: HashMap::new(),
+            first_trade_at: None,
+            price_window: PriceWindow::new(),
+            halted_until: None,
+            trades: Vec::new(),
+            stop_orders: Vec::new(),
+            batch_closes_at,
+            rewards: RewardLedger::default(),
+            pause_controller,
+            referrals: None,
+            fee_controller: None,
             auth_manager,
             event_emitter,
             client,
         }
     }
+
+    /// Shares the factory's pause switches and pauser role. Without it only the market
+    /// creator can pause the market.
+    pub fn with_pause_controller(mut self, pause_controller: Arc<PauseController>) -> Self {
+        self.pause_controller = pause_controller;
+        self
+    }
+
+    /// Draws on the factory's accounts for the market's collateral token instead of the
+    /// market's own, so one balance funds orders in every market sharing them. Winnings are
+    /// then credited to it when the market resolves, without claiming.
+    pub fn with_accounts(mut self, accounts: Arc<CollateralAccounts>) -> Self {
+        self.accounts = accounts;
+        self.portfolio_margin = true;
+        self
+    }
+
+    /// Holds deposited collateral in `custody` on the venue, e.g. the factory's treasury.
+    /// Deposits move collateral there from the user's wallet and withdrawals pay it back, so
+    /// every balance in `accounts` is backed by venue funds. Without it deposits are rejected.
+    pub fn with_custody(mut self, custody: Address) -> Self {
+        self.custody = Some(custody);
+        self
+    }
+
+    /// Shares the factory's referral links. Without it no referral fees are paid.
+    pub fn with_referrals(mut self, referrals: Arc<ReferralRegistry>) -> Self {
+        self.referrals = Some(referrals);
+        self
+    }
+
+    /// Shares the factory's fee schedule overrides and treasury. Without it the market keeps
+    /// the schedule it was created with and its protocol fees cannot be swept.
+    pub fn with_fee_controller(mut self, fee_controller: Arc<FeeController>) -> Self {
+        self.fee_controller = Some(fee_controller);
+        self
+    }
+
+    /// The schedule fills and redemptions are charged at.
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        self.fee_controller
+            .as_ref()
+            .and_then(|fee_controller| fee_controller.schedule(&self.market.id))
+            .unwrap_or(self.market.fee_schedule)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_controller.is_paused(&self.market.id)
+    }
+
+    /// Rejects minting, trading and claiming until unpaused. Pauser only.
+    pub fn pause(&self, reason: &str) -> Result<(), MarketError> {
+        let caller = self.ensure_pauser()?;
+        self.pause_controller.set_market_paused(&self.market.id, true);
+        self.event_emitter.emit_market_event(MarketEvent::Paused {
+            market_id: Some(self.market.id.clone()),
+            pauser: caller,
+            reason: reason.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+        Ok(())
+    }
+
+    pub fn unpause(&self) -> Result<(), MarketError> {
+        let caller = self.ensure_pauser()?;
+        self.pause_controller.set_market_paused(&self.market.id, false);
+        self.event_emitter.emit_market_event(MarketEvent::Unpaused {
+            market_id: Some(self.market.id.clone()),
+            pauser: caller,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+        Ok(())
+    }
+
+    /// Lifts a circuit breaker halt before it expires. Pauser only.
+    pub fn resume_trading(&mut self) -> Result<(), MarketError> {
+        self.ensure_pauser()?;
+        if self.halted_until.take().is_some() {
+            self.event_emitter.emit_market_event(MarketEvent::TradingResumed {
+                market_id: self.market.id.clone(),
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Proposes resolving to `outcome` before the scheduled resolution time because the
+    /// event already happened at `event_timestamp`. Oracle only.
+    ///
+    /// Trading stops immediately. Trades executed at or after the event time are flagged and
+    /// their ids returned. The oracle then finalizes with `resolve`, or withdraws the proposal.
+    pub fn propose_early_resolution(&mut self, outcome: bool, event_timestamp: u64) -> Result<Vec<u64>, MarketError> {
+        if self.market.status != MarketStatus::Active {
+            return Err(MarketError::MarketNotActive);
+        }
+        if self.market.group_id.is_some() {
+            return Err(MarketError::GroupedMarket);
+        }
+        let oracle = self.ensure_oracle()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if event_timestamp > now {
+            return Err(MarketError::InvalidEventTime);
+        }
+
+        self.market.early_resolution = Some(EarlyResolution {
+            outcome,
+            event_timestamp,
+            proposed_at: now,
+        });
+        self.event_emitter.emit_market_event(MarketEvent::EarlyResolutionProposed {
+            market_id: self.market.id.clone(),
+            oracle,
+            outcome,
+            event_timestamp,
+            timestamp: now,
+        });
+
+        let mut flagged = Vec::new();
+        for trade in self.trades.iter_mut().filter(|trade| trade.timestamp >= event_timestamp) {
+            trade.flagged = true;
+            flagged.push(trade.trade_id);
+        }
+        if !flagged.is_empty() {
+            self.event_emitter.emit_market_event(MarketEvent::TradesFlagged {
+                market_id: self.market.id.clone(),
+                event_timestamp,
+                trade_ids: flagged.clone(),
+                timestamp: now,
+            });
+        }
+        Ok(flagged)
+    }
+
+    /// Withdraws a pending early resolution proposal, clearing its trade flags and
+    /// reopening trading. Oracle only.
+    pub fn cancel_early_resolution(&mut self) -> Result<(), MarketError> {
+        let oracle = self.ensure_oracle()?;
+        if self.market.early_resolution.take().is_none() {
+            return Err(MarketError::NoResolutionProposal);
+        }
+        for trade in &mut self.trades {
+            trade.flagged = false;
+        }
+        self.event_emitter.emit_market_event(MarketEvent::EarlyResolutionCancelled {
+            market_id: self.market.id.clone(),
+            oracle,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+        Ok(())
+    }
+
+    /// Trades executed after the true event time of the pending early resolution.
+    pub fn flagged_trades(&self) -> Vec<&TradeRecord> {
+        self.trades.iter().filter(|trade| trade.flagged).collect()
+    }
+
+    /// Switches to frequent batch auctions, e.g. for the minutes around a news event. Orders
+    /// rest without matching and clear together every `interval` seconds; after the first
+    /// clearing at or past `ends_at`, continuous matching resumes. Pauser only.
+    pub fn start_batch_auction(&mut self, interval: u64, ends_at: Option<u64>) -> Result<(), MarketError> {
+        self.ensure_pauser()?;
+        if interval == 0 {
+            return Err(MarketError::InvalidAmount);
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.market.batch_auction = Some(BatchAuction { interval, ends_at });
+        // A batch already collecting orders keeps its closing time
+        self.batch_closes_at.get_or_insert(timestamp + interval);
+        self.event_emitter.emit_market_event(MarketEvent::BatchAuctionStarted {
+            market_id: self.market.id.clone(),
+            interval,
+            ends_at,
+            timestamp,
+        });
+        Ok(())
+    }
+
+    /// Clears the current batch now and returns to continuous matching. Pauser only.
+    pub fn end_batch_auction(&mut self) -> Result<Option<u64>, MarketError> {
+        self.ensure_pauser()?;
+        if self.batch_closes_at.is_none() {
+            return Err(MarketError::BatchAuctionNotDue);
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.market.batch_auction = None;
+        Ok(self.run_batch(timestamp))
+    }
+
+    /// Clears the current batch once its interval has passed, which anyone may trigger.
+    /// Submitting an order does the same. Returns the YES clearing price, or `None` if
+    /// nothing crossed.
+    pub fn clear_batch(&mut self) -> Result<Option<u64>, MarketError> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if self.batch_closes_at.is_none_or(|closes_at| timestamp < closes_at) {
+            return Err(MarketError::BatchAuctionNotDue);
+        }
+        self.ensure_not_paused()?;
+        self.ensure_trading_open(timestamp)?;
+        Ok(self.run_batch(timestamp))
+    }
+
+    /// Starts, changes or ends (`None`) the market's liquidity reward program. Ending it
+    /// refunds the unspent budget to its funders; rewards already accrued stay claimable.
+    /// Creator only.
+    pub fn set_liquidity_rewards(&mut self, program: Option<RewardProgram>) -> Result<(), MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        if caller != self.market.creator {
+            return Err(MarketError::Unauthorized);
+        }
+        if program.is_some_and(|program| !program.is_valid()) {
+            return Err(MarketError::InvalidAmount);
+        }
+
+        self.market.liquidity_rewards = program;
+        if program.is_none() {
+            let budget = std::mem::take(&mut self.rewards.budget);
+            let funders = std::mem::take(&mut self.rewards.funders);
+            let funded = funders.values().fold(U256::zero(), |total, amount| total + amount);
+            if !funded.is_zero() {
+                for (funder, amount) in funders {
+                    self.accounts.credit(funder, budget * amount / funded);
+                }
+            }
+        }
+        self.event_emitter.emit_market_event(MarketEvent::LiquidityRewardsConfigured {
+            market_id: self.market.id.clone(),
+            program,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+        Ok(())
+    }
+
+    /// Adds `amount` of the caller's collateral to the liquidity reward budget.
+    pub fn fund_liquidity_rewards(&mut self, amount: U256) -> Result<(), MarketError> {
+        if self.market.liquidity_rewards.is_none() {
+            return Err(MarketError::NoRewardProgram);
+        }
+        if amount.is_zero() {
+            return Err(MarketError::InvalidAmount);
+        }
+        let caller = self.auth_manager.get_current_address()?;
+        if !self.accounts.try_debit(caller, amount) {
+            return Err(MarketError::InsufficientCollateral);
+        }
+        *self.rewards.funders.entry(caller).or_default() += amount;
+        self.rewards.budget += amount;
+        self.event_emitter.emit_market_event(MarketEvent::LiquidityRewardsFunded {
+            market_id: self.market.id.clone(),
+            funder: caller,
+            amount,
+            budget: self.rewards.budget,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+        Ok(())
+    }
+
+    /// Scores the resting orders near the midpoint and accrues this sample's rewards, which
+    /// anyone may trigger once per `sample_interval`. Returns the amount accrued.
+    pub fn sample_liquidity_rewards(&mut self) -> Result<U256, MarketError> {
+        let program = self.market.liquidity_rewards.ok_or(MarketError::NoRewardProgram)?;
+        if self.market.status != MarketStatus::Active {
+            return Err(MarketError::MarketNotActive);
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if self.rewards.last_sampled_at.is_some_and(|sampled_at| timestamp < sampled_at + program.sample_interval) {
+            return Err(MarketError::RewardSampleNotDue);
+        }
+        self.ensure_not_paused()?;
+        self.ensure_trading_open(timestamp)?;
+        self.expire_orders(timestamp);
+
+        let (midpoint, scores) = match program.score(&self.yes_book, &self.no_book) {
+            Some((midpoint, scores)) => (Some(midpoint), scores),
+            None => (None, HashMap::new()),
+        };
+        let distributed = self.rewards.distribute(program.per_sample, scores);
+        self.rewards.last_sampled_at = Some(timestamp);
+        self.event_emitter.emit_market_event(MarketEvent::LiquidityRewardsSampled {
+            market_id: self.market.id.clone(),
+            midpoint,
+            scores: self.rewards.scores.iter().map(|(&owner, &score)| (owner, score)).collect(),
+            distributed,
+            timestamp,
+        });
+        Ok(distributed)
+    }
+
+    /// Moves the caller's accrued liquidity rewards into their collateral balance.
+    pub fn claim_liquidity_rewards(&mut self) -> Result<U256, MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        self.ensure_not_paused()?;
+        let amount = self.rewards.accrued.remove(&caller).unwrap_or_default();
+        if !amount.is_zero() {
+            self.accounts.credit(caller, amount);
+            self.event_emitter.emit_market_event(MarketEvent::LiquidityRewardsClaimed {
+                market_id: self.market.id.clone(),
+                user: caller,
+                amount,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs(),
+            });
+        }
+        Ok(amount)
+    }
+
+    pub async fn deposit_collateral(&mut self, amount: U256) -> Result<H256, MarketError> {
+        if amount.is_zero() {
+            return Err(MarketError::InvalidAmount);
+        }
+
+        let custody = self.custody.ok_or(MarketError::NoCustody)?;
+        let caller = self.auth_manager.get_current_address()?;
+        let tx_hash = self.client
+            .deposit_collateral(caller, custody, &self.market.collateral_token, amount)
+            .await?;
+
+        self.accounts.credit(caller, amount);
+        self.total_collateral += amount;
+
+        self.event_emitter.emit_market_event(MarketEvent::CollateralDeposited {
+            market_id: self.market.id.clone(),
+            user: caller,
+            amount,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash,
+        });
+
+        Ok(tx_hash)
+    }
+
+    pub async fn withdraw_collateral(&mut self, amount: U256) -> Result<H256, MarketError> {
+        if amount.is_zero() {
+            return Err(MarketError::InvalidAmount);
+        }
+
+        let custody = self.custody.ok_or(MarketError::NoCustody)?;
+        let caller = self.auth_manager.get_current_address()?;
+        // Debit first so a concurrent withdrawal through another market can't spend it too
+        if !self.accounts.try_debit(caller, amount) {
+            return Err(MarketError::WithdrawalExceedsBalance);
+        }
+
+        let tx_hash = match self.client
+            .withdraw_collateral(custody, caller, &self.market.collateral_token, amount)
+            .await
+        {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                self.accounts.credit(caller, amount);
+                return Err(e.into());
+            }
+        };
+
+        // Under portfolio margin the deposit may have come through another market
+        self.total_collateral = self.total_collateral.saturating_sub(amount);
+
+        self.event_emitter.emit_market_event(MarketEvent::CollateralWithdrawn {
+            market_id: self.market.id.clone(),
+            user: caller,
+            amount,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash,
+        });
+
+        Ok(tx_hash)
+    }
+
+    /// Places a good-till-cancelled limit order on the YES or NO book. See `submit_order`.
+    pub fn place_order(
+        &mut self,
+        is_yes: bool,
+        side: OrderSide,
+        price: u64,
+        amount: u64,
+    ) -> Result<PlacedOrder, MarketError> {
+        self.submit_order(NewOrder::limit(is_yes, side, price, amount))
+    }
+
+    /// Submits an order to the YES or NO book and settles any fills immediately.
+    ///
+    /// Buy orders lock `price * amount / 100` collateral, rounded up, plus the taker fee on it,
+    /// and sell orders lock the tokens until they fill or are cancelled. The taker pays the
+    /// taker fee in force when its order was placed, rounded up over the order's fills, and
+    /// the maker receives the rebate.
+    ///
+    /// Stop orders are held back without locking funds, and return with nothing filled or
+    /// resting. They trigger once the last trade price of their outcome reaches the stop
+    /// price, after which they execute like any other order.
+    pub fn submit_order(&mut self, order: NewOrder) -> Result<PlacedOrder, MarketError> {
+        let timestamp = self.ensure_can_trade(&order)?;
+        let caller = self.auth_manager.get_current_address()?;
+        let order_id = self.next_order_id;
+
+        let placed = match order.stop_price {
+            Some(stop_price) => {
+                self.stop_orders.push(StopOrder {
+                    order_id,
+                    owner: caller,
+                    order,
+                    placed_at: timestamp,
+                });
+                self.event_emitter.emit_market_event(MarketEvent::StopOrderPlaced {
+                    market_id: self.market.id.clone(),
+                    user: caller,
+                    order_id,
+                    stop_price,
+                    timestamp,
+                });
+                PlacedOrder {
+                    order_id,
+                    filled_size: 0,
+                    resting_size: 0,
+                }
+            }
+            None => self.execute_order(caller, order_id, order, timestamp, U256::zero())?,
+        };
+        self.next_order_id += 1;
+
+        self.trigger_stops(timestamp);
+        Ok(placed)
+    }
+
+    /// Cancels a resting or pending stop order of the caller's and returns its locked funds.
+    pub fn cancel_order(&mut self, order_id: u64) -> Result<(), MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        if let Some(index) = self.stop_orders.iter().position(|stop| stop.order_id == order_id) {
+            if self.stop_orders[index].owner != caller {
+                return Err(MarketError::Unauthorized);
+            }
+            self.stop_orders.remove(index);
+        } else {
+            let is_yes = match (self.yes_book.get(order_id), self.no_book.get(order_id)) {
+                (Some(order), _) | (_, Some(order)) if order.owner != caller => {
+                    return Err(MarketError::Unauthorized)
+                }
+                (Some(_), _) => true,
+                (_, Some(_)) => false,
+                (None, None) => return Err(MarketError::OrderCancellationFailed),
+            };
+
+            let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+            let order = book.cancel(order_id).ok_or(MarketError::OrderCancellationFailed)?;
+            self.refund_order(is_yes, &order);
+        }
+
+        self.event_emitter.emit_market_event(MarketEvent::OrderCancelled {
+            market_id: self.market.id.clone(),
+            user: caller,
+            order_id: order_id.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash: self.action_hash(&format!("cancel:{}", order_id)),
+        });
+
+        Ok(())
+    }
+
+    /// Changes the price or size of one of the caller's resting orders. Reducing the size
+    /// at the same price keeps the order's place in the queue. Any other change replaces
+    /// it under the same id at the back of the queue, where it may match like a new order
+    /// with the original's post-only and self-trade prevention settings; if the replacement
+    /// is rejected the original order stays as it was.
+    pub fn amend_order(&mut self, order_id: u64, price: Option<u64>, amount: u64) -> Result<PlacedOrder, MarketError> {
+        if amount == 0 {
+            return Err(MarketError::InvalidAmount);
+        }
+        let caller = self.auth_manager.get_current_address()?;
+        let (is_yes, resting) = self.own_book_order(caller, order_id)?;
+        let price = price.unwrap_or(resting.price);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let kept_priority = price == resting.price && amount <= resting.remaining;
+        let placed = if kept_priority {
+            let removed = resting.remaining - amount;
+            let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+            book.take(order_id, removed);
+            self.refund_portion(is_yes, &BookOrder { remaining: removed, ..resting });
+            PlacedOrder {
+                order_id,
+                filled_size: 0,
+                resting_size: amount,
+            }
+        } else {
+            let time_in_force = match resting.expires_at {
+                Some(expires_at) => TimeInForce::Gtt { expires_at },
+                None => TimeInForce::Gtc,
+            };
+            let order = NewOrder {
+                time_in_force,
+                post_only: resting.post_only,
+                self_trade_prevention: resting.self_trade_prevention,
+                ..NewOrder::limit(is_yes, resting.side, price, amount)
+            };
+            self.replace_order(caller, order_id, order_id, order)?
+        };
+
+        self.event_emitter.emit_market_event(MarketEvent::OrderAmended {
+            market_id: self.market.id.clone(),
+            user: caller,
+            order_id,
+            price,
+            remaining: placed.resting_size,
+            kept_priority,
+            timestamp,
+        });
+        Ok(placed)
+    }
+
+    /// Atomically cancels one of the caller's resting orders and submits `order` in its
+    /// place under a new id. The funds the old order locked count towards the new one. If
+    /// the new order is rejected, the old one is left untouched.
+    pub fn cancel_replace(&mut self, order_id: u64, order: NewOrder) -> Result<PlacedOrder, MarketError> {
+        if order.stop_price.is_some() {
+            return Err(MarketError::InvalidOrder);
+        }
+        let caller = self.auth_manager.get_current_address()?;
+        self.own_book_order(caller, order_id)?;
+        let new_order_id = self.next_order_id;
+        let placed = self.replace_order(caller, order_id, new_order_id, order)?;
+        self.next_order_id += 1;
+
+        self.event_emitter.emit_market_event(MarketEvent::OrderCancelled {
+            market_id: self.market.id.clone(),
+            user: caller,
+            order_id: order_id.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash: self.action_hash(&format!("cancel:{}", order_id)),
+        });
+        Ok(placed)
+    }
+
+    /// Collateral locked in this market: open buy orders' escrow and the outstanding
+    /// complete sets. `ExposureLimits::max_total_collateral` caps it, wherever the
+    /// collateral was deposited.
+    pub fn locked_collateral(&self) -> U256 {
+        let escrow = self.order_escrow.values().fold(U256::zero(), |total, escrow| total + escrow);
+        (escrow + CENTS - 1) / CENTS + self.yes_token_supply.max(self.no_token_supply)
+    }
+
+    /// Stop orders waiting for their trigger.
+    pub fn stop_orders(&self) -> &[StopOrder] {
+        &self.stop_orders
+    }
+
+    /// Last traded price of one outcome, which stop orders trigger on. Trades of the other
+    /// outcome count at the complementary price.
+    pub fn last_trade_price(&self, is_yes: bool) -> Option<u64> {
+        self.trades
+            .last()
+            .map(|trade| if trade.is_yes == is_yes { trade.price } else { 100 - trade.price })
+    }
+
+    /// Moves the creator's accrued fees into their collateral balance.
+    pub fn claim_creator_fees(&mut self) -> Result<U256, MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        if caller != self.market.creator {
+            return Err(MarketError::Unauthorized);
+        }
+
+        let amount = std::mem::take(&mut self.creator_fees);
+        self.accounts.credit(caller, amount);
+        Ok(amount)
+    }
+
+    /// Moves the accrued protocol fees into the treasury's collateral balance, from where the
+    /// treasury withdraws them like any other collateral. Anyone may call it.
+    pub fn sweep_treasury_fees(&mut self) -> Result<U256, MarketError> {
+        let treasury = self.fee_controller.as_ref().ok_or(MarketError::NoTreasury)?.treasury();
+        let amount = std::mem::take(&mut self.treasury_fees);
+        self.accounts.credit(treasury, amount);
+        Ok(amount)
+    }
+
+    /// Moves the caller's referral fees earned in this market into their collateral balance.
+    pub fn claim_referral_fees(&mut self) -> Result<U256, MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        let amount = self.referral_fees.remove(&caller).unwrap_or_default();
+        self.accounts.credit(caller, amount);
+        Ok(amount)
+    }
+
+    /// Applies a resolved group's outcome: YES if this market won, NO otherwise.
+    pub fn resolve_from_group(&mut self, group: &MarketGroup) -> Result<(), MarketError> {
+        if self.market.group_id.as_deref() != Some(group.id.as_str()) || !group.contains(&self.market.id) {
+            return Err(MarketError::GroupedMarket);
+        }
+        let winner = group.winning_market_id.as_deref().ok_or(MarketError::MarketNotResolved)?;
+        match self.market.status {
+            MarketStatus::Active | MarketStatus::Expired => {}
+            MarketStatus::Resolved => return Err(MarketError::MarketAlreadyResolved),
+            MarketStatus::Locked | MarketStatus::Voided => return Err(MarketError::MarketNotActive),
+        }
+
+        self.market.status = MarketStatus::Resolved;
+        self.market.resolved_outcome = Some(winner == self.market.id);
+        self.credit_winnings(winner == self.market.id);
+        Ok(())
+    }
+
+    /// Applies the parent market's resolution to a locked conditional market: it activates
+    /// if the parent's outcome meets the condition and is voided otherwise. A voided parent
+    /// voids its children too.
+    ///
+    /// Voiding redeems every complete set for collateral. Returns the new status, or `None`
+    /// if the event is not about this market's parent or the market is no longer locked.
+    pub fn on_parent_resolved(&mut self, event: &MarketEvent) -> Option<MarketStatus> {
+        let condition = self.market.parent_condition.as_ref()?;
+        let status = match event {
+            MarketEvent::MarketResolved { market_id, outcome, .. } if *market_id == condition.market_id => {
+                condition.status_after(*outcome)
+            }
+            MarketEvent::MarketVoided { market_id, .. } if *market_id == condition.market_id => {
+                MarketStatus::Voided
+            }
+            _ => return None,
+        };
+        if self.market.status != MarketStatus::Locked {
+            return None;
+        }
+
+        let parent_market_id = condition.market_id.clone();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.market.status = status.clone();
+        if status == MarketStatus::Active {
+            self.event_emitter.emit_market_event(MarketEvent::MarketActivated {
+                market_id: self.market.id.clone(),
+                parent_market_id,
+                timestamp,
+            });
+            return Some(status);
+        }
+
+        // Locked markets can't trade, so every holder has equal YES and NO balances
+        let mut refunded = U256::zero();
+        for (user, (yes, no)) in self.user_balances.iter_mut() {
+            let sets = (*yes).min(*no);
+            *yes -= sets;
+            *no -= sets;
+            self.accounts.credit(*user, sets);
+            refunded += sets;
+        }
+        self.yes_token_supply -= refunded;
+        self.no_token_supply -= refunded;
+
+        self.event_emitter.emit_market_event(MarketEvent::MarketVoided {
+            market_id: self.market.id.clone(),
+            parent_market_id,
+            refunded,
+            timestamp,
+        });
+        Some(status)
+    }
+
+    /// Replaces the market metadata, keeping the old version in the history.
+    /// Only the creator can edit, and only until the market first trades.
+    pub fn update_metadata(&mut self, metadata: MarketMetadata) -> Result<(), MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        if caller != self.market.creator {
+            return Err(MarketError::Unauthorized);
+        }
+        if self.first_trade_at.is_some() {
+            return Err(MarketError::MetadataLocked);
+        }
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let previous = std::mem::replace(&mut self.market.metadata, metadata.clone());
+        self.market.metadata_history.push(MetadataRevision {
+            metadata: previous,
+            replaced_by: caller,
+            replaced_at: timestamp,
+        });
+
+        self.event_emitter.emit_market_event(MarketEvent::MetadataUpdated {
+            market_id: self.market.id.clone(),
+            editor: caller,
+            metadata,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Settles a fill of a taker order on the `is_yes` book.
+    ///
+    /// Buyers pay the exact value out of their escrow. Sellers are paid in whole units,
+    /// rounded down, and the remainder goes to the treasury.
+    fn settle_fill(&mut self, is_yes: bool, fill: &Fill, timestamp: u64, taken: &mut U256) {
+        self.first_trade_at.get_or_insert(timestamp);
+        let amount = U256::from(fill.size);
+        // For mints and burns the two sides of each complete set are priced on opposite
+        // outcomes and add up to exactly one unit of collateral per set
+        let maker_value = notional(fill.price, fill.size);
+        let taker_value = notional(fill.taker_price(), fill.size);
+        // The taker pays the rate it was placed at, which is what a buyer's escrow reserved.
+        // It is rounded up over everything the order takes in the match, so splitting an
+        // order into small fills doesn't avoid it
+        let fee_on = |value: U256| (apply_bps_up(value, fill.taker_fee_bps) + CENTS - 1) / CENTS;
+        let mut taker_fee = fee_on(*taken + taker_value) - fee_on(*taken);
+        *taken += taker_value;
+        if fill.taker_side == OrderSide::Sell {
+            // A seller pays out of the fill's proceeds
+            taker_fee = taker_fee.min(taker_value / CENTS);
+        }
+        let fees = self.fee_schedule().split_taker_fee(taker_fee, taker_value / CENTS);
+        // The taker pays the fee, which funds the maker rebate: a buyer out of the escrow
+        // that reserved it at placement, a seller out of the proceeds
+        let taker_proceeds_fee = match fill.taker_side {
+            OrderSide::Buy => {
+                self.spend_escrow(fill.taker_order_id, fees.taker_fee * CENTS);
+                U256::zero()
+            }
+            OrderSide::Sell => fees.taker_fee,
+        };
+
+        match fill.kind {
+            FillKind::Transfer => {
+                let (buyer, seller, buy_order_id) = match fill.taker_side {
+                    OrderSide::Buy => (fill.taker, fill.maker, fill.taker_order_id),
+                    OrderSide::Sell => (fill.maker, fill.taker, fill.maker_order_id),
+                };
+
+                // The buyer pays from their order's escrow; the seller's tokens were locked at placement
+                self.spend_escrow(buy_order_id, maker_value);
+                self.adjust_tokens(buyer, is_yes, |balance| *balance += amount);
+                self.pay_out(seller, maker_value, taker_proceeds_fee);
+            }
+            FillKind::Mint => {
+                // Both buyers pay from their escrow and the complete set is split between them
+                self.spend_escrow(fill.taker_order_id, taker_value);
+                self.spend_escrow(fill.maker_order_id, maker_value);
+                self.adjust_tokens(fill.taker, is_yes, |balance| *balance += amount);
+                self.adjust_tokens(fill.maker, !is_yes, |balance| *balance += amount);
+                self.yes_token_supply += amount;
+                self.no_token_supply += amount;
+            }
+            FillKind::Burn => {
+                // Both sellers' tokens were locked at placement; together they redeem a complete set
+                self.pay_out(fill.taker, taker_value, taker_proceeds_fee);
+                self.pay_out(fill.maker, maker_value, U256::zero());
+                self.yes_token_supply -= amount;
+                self.no_token_supply -= amount;
+            }
+        }
+
+        self.accounts.credit(fill.maker, fees.maker_rebate);
+        let referral_fee = self.credit_referrer(fill, fees.taker_fee, fees.treasury_fee, timestamp);
+        self.creator_fees += fees.creator_fee;
+        self.treasury_fees += fees.treasury_fee - referral_fee;
+
+        if self.book_order(fill.maker_order_id).is_none() {
+            self.release_escrow(fill.maker_order_id, fill.maker);
+        }
+
+        let trade_id = self.trades.len() as u64;
+        self.trades.push(TradeRecord {
+            trade_id,
+            maker: fill.maker,
+            taker: fill.taker,
+            is_yes,
+            price: fill.taker_price(),
+            amount: fill.size,
+            timestamp,
+            flagged: false,
+        });
+
+        self.event_emitter.emit_market_event(MarketEvent::TradeExecuted {
+            market_id: self.market.id.clone(),
+            trade_id,
+            maker: fill.maker,
+            taker: fill.taker,
+            is_yes,
+            side: format!("{:?}", fill.taker_side),
+            price: fill.taker_price(),
+            amount: fill.size,
+            kind: fill.kind,
+            taker_fee: fees.taker_fee,
+            maker_rebate: fees.maker_rebate,
+            creator_fee: fees.creator_fee,
+            timestamp,
+        });
+    }
+
+    /// Links first-time traders to their referrers and credits the taker's referrer with
+    /// their share of the taker fee, at most the treasury's part. Returns the amount credited.
+    fn credit_referrer(&mut self, fill: &Fill, taker_fee: U256, treasury_fee: U256, timestamp: u64) -> U256 {
+        let Some(referrals) = self.referrals.clone() else { return U256::zero() };
+        for user in [fill.taker, fill.maker] {
+            if let Some(referrer) = referrals.record_trade(user, timestamp) {
+                self.event_emitter.emit_market_event(MarketEvent::ReferralLinked {
+                    market_id: self.market.id.clone(),
+                    user,
+                    referrer,
+                    timestamp,
+                });
+            }
+        }
+
+        let Some((referrer, share)) = referrals.share_of(fill.taker, taker_fee) else { return U256::zero() };
+        let share = share.min(treasury_fee);
+        if share.is_zero() {
+            return share;
+        }
+        *self.referral_fees.entry(referrer).or_default() += share;
+        referrals.credit(referrer, share);
+        self.event_emitter.emit_market_event(MarketEvent::ReferralFeeCredited {
+            market_id: self.market.id.clone(),
+            referrer,
+            user: fill.taker,
+            trade_id: self.trades.len() as u64,
+            amount: share,
+            timestamp,
+        });
+        share
+    }
+
+    /// Locks the order's funds, matches it and rests or cancels the remainder according to
+    /// its time in force. `order` must be valid and not a stop order.
+    ///
+    /// `reserved` is collateral already held for the owner, such as the escrow of an order
+    /// being replaced. It counts towards a buy's escrow and whatever isn't needed goes back
+    /// to the owner's account once the order's funds are locked. Nothing is locked or
+    /// returned if the order is rejected.
+    fn execute_order(
+        &mut self,
+        owner: Address,
+        order_id: u64,
+        order: NewOrder,
+        timestamp: u64,
+        reserved: U256,
+    ) -> Result<PlacedOrder, MarketError> {
+        // Expired orders must not fill
+        self.expire_orders(timestamp);
+
+        let NewOrder { is_yes, side, .. } = order;
+        let rests = matches!(order.time_in_force, TimeInForce::Gtc | TimeInForce::Gtt { .. });
+        let auction = self.batch_closes_at.is_some();
+        if auction && !rests {
+            return Err(MarketError::BatchAuctionRestingOnly);
+        }
+        let book = self.unified_book(is_yes);
+        let price = match order.order_type {
+            OrderType::Limit { price } => price,
+            OrderType::Market { max_slippage } => {
+                let best = book.best_price(side).ok_or(MarketError::NoLiquidity)?;
+                match side {
+                    OrderSide::Buy => (best + max_slippage).min(99),
+                    OrderSide::Sell => best.saturating_sub(max_slippage).max(1),
+                }
+            }
+        };
+        if order.post_only && !auction && book.crosses(side, price) {
+            return Err(MarketError::PostOnlyWouldCross);
+        }
+        if order.time_in_force == TimeInForce::Fok && book.fillable(side, price) < order.amount {
+            return Err(MarketError::FillOrKillUnfilled);
+        }
+
+        // Lock the funds the order needs
+        let taker_fee_bps = self.fee_schedule().taker_fee_bps;
+        let mut amount = order.amount;
+        let mut reused = U256::zero();
+        match side {
+            OrderSide::Buy => {
+                self.market.limits.check_position(self.exposure(owner, is_yes) + amount)?;
+                // The cost is rounded up to whole units, and the fee reserved in case the
+                // order fills as the taker
+                let cost = (notional(price, amount) + CENTS - 1) / CENTS;
+                let escrow = cost + apply_bps_up(cost, taker_fee_bps);
+                self.market.limits.check_total_collateral(self.locked_collateral() + escrow)?;
+                reused = reserved.min(escrow);
+                if !self.accounts.try_debit(owner, escrow - reused) {
+                    return Err(MarketError::InsufficientCollateral);
+                }
+                self.order_escrow.insert(order_id, escrow * CENTS);
+            }
+            OrderSide::Sell => {
+                let held = self.token_balance(owner, is_yes);
+                if order.reduce_only {
+                    amount = held.min(U256::from(amount)).as_u64();
+                }
+                if amount == 0 || held < U256::from(amount) {
+                    return Err(MarketError::InsufficientBalance);
+                }
+                self.adjust_tokens(owner, is_yes, |balance| *balance -= U256::from(amount));
+            }
+        }
+        self.accounts.credit(owner, reserved - reused);
+
+        let mut incoming = BookOrder {
+            order_id,
+            owner,
+            side,
+            price,
+            remaining: amount,
+            timestamp,
+            expires_at: match order.time_in_force {
+                TimeInForce::Gtt { expires_at } => Some(expires_at),
+                _ => None,
+            },
+            post_only: order.post_only,
+            self_trade_prevention: order.self_trade_prevention,
+            taker_fee_bps,
+        };
+        // During a batch auction orders only rest and match when the batch clears
+        let MatchResult { fills, released } = if auction {
+            MatchResult::default()
+        } else {
+            self.unified_book(is_yes).match_order(&mut incoming, order.self_trade_prevention)
+        };
+        let filled: u64 = fills.iter().map(|fill| fill.size).sum();
+        let unfilled = incoming.remaining;
+        if rests && unfilled > 0 {
+            let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+            book.rest(incoming.clone());
+        }
+
+        self.event_emitter.emit_market_event(MarketEvent::OrderPlaced {
+            market_id: self.market.id.clone(),
+            user: owner,
+            side: format!("{:?}", side),
+            price,
+            amount,
+            timestamp,
+            tx_hash: self.action_hash(&format!("order:{}", order_id)),
+        });
+
+        // Size self-trade prevention took off either order is returned without trading
+        let prevented = amount - filled - unfilled;
+        if prevented > 0 || !released.is_empty() {
+            self.refund_portion(is_yes, &BookOrder { remaining: prevented, ..incoming.clone() });
+            for Released { order: resting, complement } in &released {
+                self.refund_portion(is_yes != *complement, resting);
+                if self.book_order(resting.order_id).is_none() {
+                    self.release_escrow(resting.order_id, owner);
+                }
+            }
+            self.event_emitter.emit_market_event(MarketEvent::SelfTradePrevented {
+                market_id: self.market.id.clone(),
+                user: owner,
+                order_id,
+                mode: order.self_trade_prevention,
+                cancelled_size: prevented,
+                resting_order_ids: released.iter().map(|released| released.order.order_id).collect(),
+                resting_size: released.iter().map(|released| released.order.remaining).sum(),
+                timestamp,
+            });
+        }
+
+        let mut taken = U256::zero();
+        for fill in fills {
+            self.settle_fill(is_yes, &fill, timestamp, &mut taken);
+            let price = fill.taker_price();
+            self.check_circuit_breaker(if is_yes { price } else { 100 - price }, timestamp);
+        }
+        if !rests && unfilled > 0 {
+            self.refund_order(is_yes, &incoming);
+        } else if unfilled == 0 {
+            self.release_escrow(order_id, owner);
+        }
+
+        Ok(PlacedOrder {
+            order_id,
+            filled_size: filled,
+            resting_size: if rests { unfilled } else { 0 },
+        })
+    }
+
+    /// Takes the resting order `order_id` off the book and executes `order` as `new_order_id`,
+    /// with the old order's funds counting towards the new one. If that fails the old order
+    /// goes back where it was with its funds still locked.
+    fn replace_order(
+        &mut self,
+        owner: Address,
+        order_id: u64,
+        new_order_id: u64,
+        order: NewOrder,
+    ) -> Result<PlacedOrder, MarketError> {
+        let timestamp = self.ensure_can_trade(&order)?;
+        // Expire first so nothing moves in the book before the old order could go back
+        self.expire_orders(timestamp);
+        let (is_yes, _) = self.own_book_order(owner, order_id)?;
+        let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+        let (index, old) = book.remove(order_id).ok_or(MarketError::OrderNotFound)?;
+        // A buy's escrow is handed to the replacement rather than the shared account, so it
+        // can't be spent elsewhere before the replacement is in; its fraction of a unit is
+        // only given up once it is
+        let escrow = self.order_escrow.remove(&order_id).unwrap_or_default();
+        let reserved = match old.side {
+            OrderSide::Buy => escrow / CENTS,
+            OrderSide::Sell => {
+                self.refund_order(is_yes, &old);
+                U256::zero()
+            }
+        };
+
+        match self.execute_order(owner, new_order_id, order, timestamp, reserved) {
+            Ok(placed) => {
+                self.add_dust(escrow % CENTS);
+                self.trigger_stops(timestamp);
+                Ok(placed)
+            }
+            Err(e) => {
+                // execute_order fails before locking or returning anything, so the old
+                // order's funds are all still here
+                match old.side {
+                    OrderSide::Buy => {
+                        self.order_escrow.insert(order_id, escrow);
+                    }
+                    OrderSide::Sell => {
+                        self.adjust_tokens(owner, is_yes, |balance| *balance -= U256::from(old.remaining))
+                    }
+                }
+                let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+                book.reinsert(index, old);
+                Err(e)
+            }
+        }
+    }
+
+    /// Executes stop orders whose trigger the last trade price has reached. Their fills move
+    /// the price in turn, so this runs until no stop triggers, trading halts or the market
+    /// stops being active.
+    fn trigger_stops(&mut self, timestamp: u64) {
+        while self.halted_until.is_none() && self.market.status == MarketStatus::Active {
+            let triggered = self.stop_orders.iter().position(|stop| {
+                self.last_trade_price(stop.order.is_yes)
+                    .is_some_and(|last_price| stop.order.triggers_at(last_price))
+            });
+            let Some(index) = triggered else { break };
+
+            let stop = self.stop_orders.remove(index);
+            let last_price = self.last_trade_price(stop.order.is_yes).unwrap_or_default();
+            let order = NewOrder {
+                stop_price: None,
+                ..stop.order
+            };
+            let result = if order.is_valid(timestamp) {
+                self.execute_order(stop.owner, stop.order_id, order, timestamp, U256::zero())
+            } else {
+                Err(MarketError::InvalidOrder)
+            };
+            self.event_emitter.emit_market_event(MarketEvent::StopOrderTriggered {
+                market_id: self.market.id.clone(),
+                user: stop.owner,
+                order_id: stop.order_id,
+                last_price,
+                error: result.err().map(|e| e.to_string()),
+                timestamp,
+            });
+        }
+    }
+
+    /// Clears the current batch at its uniform price, settles the fills and schedules the
+    /// next batch, or ends batch auction mode if it has run its course. Returns the YES
+    /// clearing price, or `None` if nothing crossed.
+    fn run_batch(&mut self, now: u64) -> Option<u64> {
+        self.expire_orders(now);
+        let clearing = batch_auction::clear(&self.yes_book, &self.no_book);
+        let clearing_price = clearing.as_ref().map(|clearing| clearing.price);
+
+        if let Some(Clearing { price, volume, fills, released }) = clearing {
+            // Size is taken off the books fill by fill, so escrow is only released once an
+            // order's last fill has been paid from it
+            for Released { order, complement } in &released {
+                let is_yes = !complement;
+                let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+                book.take(order.order_id, order.remaining);
+                self.refund_portion(is_yes, order);
+                if self.book_order(order.order_id).is_none() {
+                    self.release_escrow(order.order_id, order.owner);
+                }
+            }
+
+            let first_trade_id = self.trades.len() as u64;
+            let mut taken: HashMap<u64, U256> = HashMap::new();
+            for (is_yes, fill) in &fills {
+                let maker_is_yes = if fill.kind == FillKind::Transfer { *is_yes } else { !*is_yes };
+                for (on_yes, order_id) in [(*is_yes, fill.taker_order_id), (maker_is_yes, fill.maker_order_id)] {
+                    let book = if on_yes { &mut self.yes_book } else { &mut self.no_book };
+                    book.take(order_id, fill.size);
+                }
+                self.settle_fill(*is_yes, fill, now, taken.entry(fill.taker_order_id).or_default());
+                if self.book_order(fill.taker_order_id).is_none() {
+                    self.release_escrow(fill.taker_order_id, fill.taker);
+                }
+            }
+            self.check_circuit_breaker(price, now);
+
+            self.event_emitter.emit_market_event(MarketEvent::BatchAuctionCleared {
+                market_id: self.market.id.clone(),
+                clearing_price: price,
+                volume,
+                trade_ids: (first_trade_id..self.trades.len() as u64).collect(),
+                timestamp: now,
+            });
+        }
+
+        match self.market.batch_auction {
+            Some(auction) if auction.ends_at.is_none_or(|ends_at| now < ends_at) => {
+                self.batch_closes_at = Some(now + auction.interval);
+            }
+            _ => {
+                self.market.batch_auction = None;
+                self.batch_closes_at = None;
+                self.event_emitter.emit_market_event(MarketEvent::BatchAuctionEnded {
+                    market_id: self.market.id.clone(),
+                    timestamp: now,
+                });
+            }
+        }
+        self.trigger_stops(now);
+        clearing_price
+    }
+
+    /// Cancels good-till-time orders that have reached their expiry.
+    fn expire_orders(&mut self, now: u64) {
+        for is_yes in [true, false] {
+            let book = if is_yes { &mut self.yes_book } else { &mut self.no_book };
+            for order in book.remove_expired(now) {
+                self.refund_order(is_yes, &order);
+                self.event_emitter.emit_market_event(MarketEvent::OrderCancelled {
+                    market_id: self.market.id.clone(),
+                    user: order.owner,
+                    order_id: order.order_id.to_string(),
+                    timestamp: now,
+                    tx_hash: self.action_hash(&format!("expire:{}", order.order_id)),
+                });
+            }
+        }
+    }
+
+    /// Redeems a user's winning tokens one-for-one, less the redemption fee, into their
+    /// collateral balance. Returns the net payout, or `None` if they hold no winning tokens.
+    fn redeem(&mut self, user: Address, outcome: bool) -> Option<U256> {
+        let payout = self.token_balance(user, outcome);
+        if payout.is_zero() {
+            return None;
+        }
+
+        self.adjust_tokens(user, outcome, |balance| *balance = U256::zero());
+        if outcome {
+            self.yes_token_supply -= payout;
+        } else {
+            self.no_token_supply -= payout;
+        }
+
+        let fees = self.fee_schedule().redemption_fees(payout);
+        self.creator_fees += fees.creator_fee;
+        self.treasury_fees += fees.treasury_fee;
+        let net = payout - fees.fee;
+        self.accounts.credit(user, net);
+
+        self.event_emitter.emit_market_event(MarketEvent::WinningsClaimed {
+            market_id: self.market.id.clone(),
+            user,
+            amount: net,
+            fee: fees.fee,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash: self.action_hash(&format!("claim:{:?}", user)),
+        });
+        Some(net)
+    }
+
+    /// Under portfolio margin, redeems every holder's winning tokens as the market resolves.
+    /// Tokens still locked in sell orders are claimed once the orders are cancelled.
+    fn credit_winnings(&mut self, outcome: bool) {
+        if self.portfolio_margin {
+            self.redeem_holders(outcome);
+        }
+    }
+
+    fn redeem_holders(&mut self, outcome: bool) -> Vec<(Address, U256)> {
+        let holders: Vec<Address> = self.user_balances.keys().copied().collect();
+        holders
+            .into_iter()
+            .filter_map(|holder| Some((holder, self.redeem(holder, outcome)?)))
+            .collect()
+    }
+
+    /// Redeems every holder's winning tokens into their collateral balance, as each of them
+    /// claiming would. Anyone may call it once the market has resolved, such as a keeper
+    /// redeeming on users' behalf. Returns the net payout per holder.
+    pub fn redeem_all(&mut self) -> Result<Vec<(Address, U256)>, MarketError> {
+        let outcome = self.winning_outcome()?;
+        self.ensure_not_paused()?;
+        Ok(self.redeem_holders(outcome))
+    }
+
+    /// Winning tokens the user can claim; zero until the market resolves.
+    pub fn claimable(&self, user: Address) -> U256 {
+        self.winning_outcome()
+            .map(|outcome| self.token_balance(user, outcome))
+            .unwrap_or_default()
+    }
+
+    fn winning_outcome(&self) -> Result<bool, MarketError> {
+        match (&self.market.status, self.market.resolved_outcome) {
+            (MarketStatus::Resolved, Some(outcome)) => Ok(outcome),
+            _ => Err(MarketError::MarketNotResolved),
+        }
+    }
+
+    /// Returns what `order.remaining` of an order had locked: its share of a buy's escrow, or
+    /// a sell's tokens.
+    fn refund_portion(&mut self, is_yes: bool, order: &BookOrder) {
+        match order.side {
+            OrderSide::Buy => {
+                let Some(escrow) = self.order_escrow.get_mut(&order.order_id) else { return };
+                // Whole units only; the order's fee reserve and any fraction wait for its release
+                let refund = notional(order.price, order.remaining).min(*escrow) / CENTS;
+                *escrow -= refund * CENTS;
+                self.accounts.credit(order.owner, refund);
+            }
+            OrderSide::Sell => {
+                self.adjust_tokens(order.owner, is_yes, |balance| *balance += U256::from(order.remaining))
+            }
+        }
+    }
+
+    /// Returns whatever an order that left the book unfilled still had locked.
+    fn refund_order(&mut self, is_yes: bool, order: &BookOrder) {
+        match order.side {
+            OrderSide::Buy => self.release_escrow(order.order_id, order.owner),
+            OrderSide::Sell => {
+                self.adjust_tokens(order.owner, is_yes, |balance| *balance += U256::from(order.remaining))
+            }
+        }
+    }
+
+    /// Checks that the market accepts `order` right now, clearing a batch that is due
+    /// first. Returns the current timestamp.
+    fn ensure_can_trade(&mut self, order: &NewOrder) -> Result<u64, MarketError> {
+        if self.market.status != MarketStatus::Active {
+            return Err(MarketError::MarketNotActive);
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if !order.is_valid(timestamp) {
+            return Err(MarketError::InvalidOrder);
+        }
+        self.ensure_not_paused()?;
+        self.ensure_before_close(timestamp)?;
+        self.ensure_trading_open(timestamp)?;
+        if self.batch_closes_at.is_some_and(|closes_at| timestamp >= closes_at) {
+            self.run_batch(timestamp);
+            self.ensure_trading_open(timestamp)?;
+        }
+        Ok(timestamp)
+    }
+
+    /// One of `owner`'s resting orders, and whether it rests on the YES book.
+    fn own_book_order(&self, owner: Address, order_id: u64) -> Result<(bool, BookOrder), MarketError> {
+        let (is_yes, order) = match (self.yes_book.get(order_id), self.no_book.get(order_id)) {
+            (Some(order), _) => (true, order),
+            (_, Some(order)) => (false, order),
+            (None, None) => return Err(MarketError::OrderNotFound),
+        };
+        if order.owner != owner {
+            return Err(MarketError::Unauthorized);
+        }
+        Ok((is_yes, order.clone()))
+    }
+
+    fn ensure_pauser(&self) -> Result<Address, MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        if !self.pause_controller.is_pauser(caller) {
+            return Err(MarketError::Unauthorized);
+        }
+        Ok(caller)
+    }
+
+    fn ensure_oracle(&self) -> Result<Address, MarketError> {
+        let caller = self.auth_manager.get_current_address()?;
+        let oracle = Address::from_str(&self.market.oracle_id).map_err(|_| MarketError::InvalidOracle)?;
+        if caller != oracle {
+            return Err(MarketError::InvalidOracle);
+        }
+        Ok(caller)
+    }
+
+    fn ensure_not_paused(&self) -> Result<(), MarketError> {
+        if self.is_paused() {
+            return Err(MarketError::MarketPaused);
+        }
+        Ok(())
+    }
+
+    /// Rejects orders once trading has closed or while an early resolution is pending.
+    fn ensure_before_close(&self, now: u64) -> Result<(), MarketError> {
+        if self.market.early_resolution.is_some() {
+            return Err(MarketError::TradingClosed);
+        }
+        match self.market.trading_close_timestamp {
+            Some(close) if now >= close => Err(MarketError::TradingClosed),
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects orders during a circuit breaker halt and lifts the halt once it has expired.
+    fn ensure_trading_open(&mut self, now: u64) -> Result<(), MarketError> {
+        match self.halted_until {
+            Some(halted_until) if now < halted_until => Err(MarketError::TradingHalted(halted_until)),
+            Some(_) => {
+                self.halted_until = None;
+                self.event_emitter.emit_market_event(MarketEvent::TradingResumed {
+                    market_id: self.market.id.clone(),
+                    timestamp: now,
+                });
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Records a YES-equivalent trade price and halts trading if it trips the breaker.
+    fn check_circuit_breaker(&mut self, price: u64, timestamp: u64) {
+        let Some(breaker) = self.market.circuit_breaker else { return };
+        let Some(trip) = self.price_window.record(&breaker, timestamp, price) else { return };
+
+        let halted_until = timestamp + breaker.halt_seconds;
+        self.halted_until = Some(halted_until);
+        self.price_window.clear();
+        self.event_emitter.emit_market_event(MarketEvent::TradingHalted {
+            market_id: self.market.id.clone(),
+            reference_price: trip.reference_price,
+            price: trip.price,
+            move_bps: trip.move_bps,
+            halted_until,
+            timestamp,
+        });
+    }
+
+    /// The `is_yes` book together with the other outcome's book, for matching.
+    fn unified_book(&mut self, is_yes: bool) -> UnifiedBook<'_> {
+        let (book, complement) = if is_yes {
+            (&mut self.yes_book, &mut self.no_book)
+        } else {
+            (&mut self.no_book, &mut self.yes_book)
+        };
+        UnifiedBook { book, complement }
+    }
+
+    fn book_order(&self, order_id: u64) -> Option<&BookOrder> {
+        self.yes_book.get(order_id).or_else(|| self.no_book.get(order_id))
+    }
+
+    fn spend_escrow(&mut self, order_id: u64, value: U256) {
+        if let Some(escrow) = self.order_escrow.get_mut(&order_id) {
+            *escrow = escrow.saturating_sub(value);
+        }
+    }
+
+    /// Returns whatever collateral a finished buy order did not spend, e.g. from price
+    /// improvement or an unused fee reserve.
+    fn release_escrow(&mut self, order_id: u64, owner: Address) {
+        if let Some(escrow) = self.order_escrow.remove(&order_id) {
+            self.pay_out(owner, escrow, U256::zero());
+        }
+    }
+
+    /// Credits `value` hundredths of collateral less `fee` to `user` in whole units.
+    fn pay_out(&mut self, user: Address, value: U256, fee: U256) {
+        self.accounts.credit(user, value / CENTS - fee);
+        self.add_dust(value % CENTS);
+    }
+
+    /// Collects fractions of a unit until they add up to whole units for the treasury.
+    fn add_dust(&mut self, hundredths: U256) {
+        self.rounding_dust += hundredths;
+        self.treasury_fees += self.rounding_dust / CENTS;
+        self.rounding_dust %= CENTS;
+    }
+
+    /// Tokens of one outcome `user` holds or has bids out for, as counted by the position cap.
+    pub fn exposure(&self, user: Address, is_yes: bool) -> U256 {
+        let book = if is_yes { &self.yes_book } else { &self.no_book };
+        let bids: u64 = book
+            .bids()
+            .iter()
+            .filter(|order| order.owner == user)
+            .map(|order| order.remaining)
+            .sum();
+        self.token_balance(user, is_yes) + bids
+    }
+
+    pub fn token_balance(&self, user: Address, is_yes: bool) -> U256 {
+        let (yes, no) = self.user_balances.get(&user).copied().unwrap_or_default();
+        if is_yes { yes } else { no }
+    }
+
+    fn adjust_tokens(&mut self, user: Address, is_yes: bool, update: impl FnOnce(&mut U256)) {
+        let (yes, no) = self.user_balances.entry(user).or_default();
+        update(if is_yes { yes } else { no });
+    }
+
+    /// Internal actions have no venue transaction; identify the action instead.
+    fn action_hash(&self, action: &str) -> H256 {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        H256::from(keccak256(format!("{}:{}:{}", self.market.id, action, timestamp)))
+    }
+}
+
+/// Hundredths of a collateral unit per unit.
+const CENTS: U256 = U256([100, 0, 0, 0]);
+
+/// Collateral value of `size` tokens at `price` cents, in hundredths of a unit so it is exact.
+fn notional(price: u64, size: u64) -> U256 {
+    U256::from(price) * U256::from(size)
 }
 
 #[async_trait]
 impl MarketContract for MarketContractState {
-    async fn mint_tokens(&mut self, _amount: u64) -> Result<(), MarketError> {
-        // Implementation here
+    /// Locks `amount` collateral and mints `amount` YES and `amount` NO tokens.
+    async fn mint_tokens(&mut self, amount: u64) -> Result<(), MarketError> {
+        if !matches!(self.market.status, MarketStatus::Active | MarketStatus::Locked) {
+            return Err(MarketError::MarketNotActive);
+        }
+        if amount == 0 {
+            return Err(MarketError::InvalidAmount);
+        }
+        self.ensure_not_paused()?;
+
+        let caller = self.auth_manager.get_current_address()?;
+        let limits = self.market.limits;
+        limits.check_open_interest(self.yes_token_supply.max(self.no_token_supply) + amount)?;
+        limits.check_position(self.exposure(caller, true).max(self.exposure(caller, false)) + amount)?;
+        limits.check_total_collateral(self.locked_collateral() + amount)?;
+
+        if !self.accounts.try_debit(caller, U256::from(amount)) {
+            return Err(MarketError::InsufficientCollateral);
+        }
+        let (yes, no) = self.user_balances.entry(caller).or_default();
+        *yes += U256::from(amount);
+        *no += U256::from(amount);
+        self.yes_token_supply += U256::from(amount);
+        self.no_token_supply += U256::from(amount);
+
+        self.event_emitter.emit_market_event(MarketEvent::TokensMinted {
+            market_id: self.market.id.clone(),
+            user: caller,
+            amount,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+
         Ok(())
     }
 
-    async fn burn_tokens(&mut self, _yes_amount: u64, _no_amount: u64) -> Result<(), MarketError> {
-        // Implementation here
+    /// Burns complete sets: equal YES and NO amounts are redeemed for collateral.
+    async fn burn_tokens(&mut self, yes_amount: u64, no_amount: u64) -> Result<(), MarketError> {
+        if yes_amount == 0 || yes_amount != no_amount {
+            return Err(MarketError::InvalidAmount);
+        }
+
+        let caller = self.auth_manager.get_current_address()?;
+        let amount = U256::from(yes_amount);
+        let (yes, no) = self.user_balances.entry(caller).or_default();
+        if *yes < amount || *no < amount {
+            return Err(MarketError::InsufficientBalance);
+        }
+
+        *yes -= amount;
+        *no -= amount;
+        self.yes_token_supply -= amount;
+        self.no_token_supply -= amount;
+        self.accounts.credit(caller, amount);
+
+        self.event_emitter.emit_market_event(MarketEvent::TokensBurned {
+            market_id: self.market.id.clone(),
+            user: caller,
+            yes_amount,
+            no_amount,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash: self.action_hash("burn"),
+        });
+
         Ok(())
     }
 
-    async fn resolve(&mut self, _outcome: bool) -> Result<(), MarketError> {
-        // Implementation here
+    async fn resolve(&mut self, outcome: bool) -> Result<(), MarketError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if self.market.group_id.is_some() {
+            return Err(MarketError::GroupedMarket);
+        }
+        let caller = self.ensure_oracle()?;
+        self.ensure_not_paused()?;
+        self.market.check_resolvable(outcome, now)?;
+
+        self.market.status = MarketStatus::Resolved;
+        self.market.resolved_outcome = Some(outcome);
+
+        self.event_emitter.emit_market_event(MarketEvent::MarketResolved {
+            market_id: self.market.id.clone(),
+            oracle: caller,
+            outcome,
+            timestamp: now,
+            tx_hash: self.action_hash("resolve"),
+        });
+        self.credit_winnings(outcome);
+
         Ok(())
     }
 
-    async fn claim_winnings(&mut self) -> Result<u64, MarketError> {
-        // Implementation here
-        Ok(0)
+    /// Redeems the caller's winning tokens one-for-one, less the redemption fee.
+    async fn claim_winnings(&mut self) -> Result<U256, MarketError> {
+        let outcome = self.winning_outcome()?;
+        self.ensure_not_paused()?;
+
+        let caller = self.auth_manager.get_current_address()?;
+        self.redeem(caller, outcome).ok_or(MarketError::InsufficientBalance)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::order_types::SelfTradePrevention;
+    use crate::events::EventLogger;
+    use crate::liquidity_rewards::ScoringFormula;
+    use crate::venue::SimulatedVenue;
+    use ethers::core::k256::ecdsa::SigningKey;
+    use ethers::signers::{LocalWallet, Signer};
+
+    pub(crate) async fn connected_auth() -> (Arc<AuthManager>, Address) {
+        let wallet: LocalWallet = SigningKey::random(&mut rand::thread_rng()).into();
+        let auth_manager = Arc::new(AuthManager::new("http://localhost:8545").await.unwrap());
+        auth_manager
+            .connect_wallet(&hex::encode(wallet.signer().to_bytes()))
+            .await
+            .unwrap();
+        (auth_manager, wallet.address())
+    }
+
+    /// A contract on a fresh simulated venue that holds deposits in a random custody account.
+    /// Each of `funded`'s wallets starts with 1,000,000 USDC to deposit.
+    pub(crate) fn funded_contract(
+        market: Market,
+        auth_manager: Arc<AuthManager>,
+        funded: &[Address],
+    ) -> MarketContractState {
+        let venue = SimulatedVenue::new();
+        for &user in funded {
+            venue.mint(user, "USDC", U256::from(1_000_000));
+        }
+        MarketContractState::new(
+            market,
+            auth_manager,
+            Arc::new(EventLogger::new(false, false, None)),
+            Arc::new(venue),
+        )
+        .with_custody(Address::random())
+    }
+
+    /// An active, fee-free market without limits or schedule, whose creator is also its oracle.
+    /// Tests override the fields they exercise.
+    pub(crate) fn test_market(id: &str, creator: Address) -> Market {
+        Market {
+            id: id.to_string(),
+            question: format!("Will {} happen?", id),
+            expiry_timestamp: 0,
+            oracle_id: format!("{:?}", creator),
+            collateral_token: "USDC".to_string(),
+            status: MarketStatus::Active,
+            yes_token_address: "YES".to_string(),
+            no_token_address: "NO".to_string(),
+            resolved_outcome: None,
+            creator,
+            fee_schedule: FeeSchedule::zero(),
+            metadata: MarketMetadata::default(),
+            metadata_history: Vec::new(),
+            group_id: None,
+            parent_condition: None,
+            circuit_breaker: None,
+            limits: ExposureLimits::default(),
+            trading_close_timestamp: None,
+            resolution_timestamp: None,
+            early_resolution: None,
+            batch_auction: None,
+            liquidity_rewards: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fees_on_fills_and_redemption() {
+        let (maker_auth, maker) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let creator = Address::random();
+        let market = Market {
+            creator,
+            fee_schedule: FeeSchedule::default(),
+            ..test_market("0xmarket", maker)
+        };
+        let mut contract = funded_contract(market, maker_auth.clone(), &[maker, taker]);
+
+        contract.deposit_collateral(U256::from(100_000)).await.unwrap();
+        contract.mint_tokens(10_000).await.unwrap();
+        contract.place_order(true, OrderSide::Sell, 60, 10_000).unwrap();
+
+        contract.auth_manager = taker_auth.clone();
+        contract.deposit_collateral(U256::from(100_000)).await.unwrap();
+        let placed = contract.place_order(true, OrderSide::Buy, 65, 10_000).unwrap();
+        assert_eq!(placed.filled_size, 10_000);
+
+        // Notional 6000: taker fee 18, maker rebate 6, creator 2, treasury 10
+        assert_eq!(contract.accounts.balance(taker), U256::from(100_000 - 6_000 - 18));
+        assert_eq!(contract.accounts.balance(maker), U256::from(90_000 + 6_000 + 6));
+        assert_eq!(contract.creator_fees, U256::from(2));
+        assert_eq!(contract.treasury_fees, U256::from(10));
+
+        contract.auth_manager = maker_auth;
+        contract.resolve(true).await.unwrap();
+
+        // Redemption fee of 50 on 10000: creator 10, treasury 40
+        contract.auth_manager = taker_auth;
+        assert_eq!(contract.claim_winnings().await.unwrap(), U256::from(9_950));
+        assert_eq!(contract.creator_fees, U256::from(12));
+        assert_eq!(contract.treasury_fees, U256::from(50));
+    }
+
+    #[tokio::test]
+    async fn test_metadata_edits_lock_after_first_trade() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let market = Market {
+            metadata: MarketMetadata {
+                resolution_source: "https://weather.gov".to_string(),
+                ..MarketMetadata::default()
+            },
+            ..test_market("0xmarket", user)
+        };
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker]);
+
+        let edited = MarketMetadata {
+            description: "Rain at the Paris-Montsouris station".to_string(),
+            ..contract.market.metadata.clone()
+        };
+        contract.update_metadata(edited.clone()).unwrap();
+        assert_eq!(contract.market.metadata, edited);
+        assert_eq!(contract.market.metadata_history.len(), 1);
+        assert_eq!(contract.market.metadata_history[0].replaced_by, user);
+
+        // Only the creator can edit
+        contract.auth_manager = taker_auth.clone();
+        assert!(matches!(contract.update_metadata(edited.clone()), Err(MarketError::Unauthorized)));
+
+        contract.auth_manager = auth_manager.clone();
+        contract.deposit_collateral(U256::from(100)).await.unwrap();
+        contract.mint_tokens(10).await.unwrap();
+        contract.place_order(true, OrderSide::Sell, 60, 10).unwrap();
+        contract.auth_manager = taker_auth;
+        contract.deposit_collateral(U256::from(100)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 60, 10).unwrap();
+
+        contract.auth_manager = auth_manager;
+        assert!(matches!(contract.update_metadata(edited), Err(MarketError::MetadataLocked)));
+        assert_eq!(contract.market.metadata_history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rounding_fee_escrow_and_treasury() {
+        let (maker_auth, maker) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let treasury = Address::random();
+        let fee_controller = Arc::new(FeeController::new(treasury));
+        let market = Market {
+            fee_schedule: FeeSchedule::default(),
+            ..test_market("0xmarket", maker)
+        };
+        let mut contract = funded_contract(market, maker_auth.clone(), &[maker, taker])
+            .with_fee_controller(fee_controller.clone());
+
+        contract.deposit_collateral(U256::from(10_001)).await.unwrap();
+        contract.mint_tokens(10_001).await.unwrap();
+        contract.place_order(true, OrderSide::Sell, 99, 1).unwrap();
+
+        // One token at 99 costs a whole unit and the fee rounds up to another; the fee and
+        // both fractions go to the treasury
+        contract.auth_manager = taker_auth.clone();
+        contract.deposit_collateral(U256::from(2)).await.unwrap();
+        assert_eq!(contract.place_order(true, OrderSide::Buy, 99, 1).unwrap().filled_size, 1);
+        assert!(contract.accounts.balance(taker).is_zero());
+        assert!(contract.accounts.balance(maker).is_zero());
+        assert_eq!(contract.treasury_fees, U256::from(2));
+        assert!(contract.rounding_dust.is_zero());
+
+        // The taker fee is locked with the order, so a buyer can't trade without it
+        contract.auth_manager = maker_auth.clone();
+        contract.place_order(true, OrderSide::Sell, 60, 10_000).unwrap();
+        contract.auth_manager = taker_auth.clone();
+        contract.deposit_collateral(U256::from(6_017)).await.unwrap();
+        assert!(matches!(
+            contract.place_order(true, OrderSide::Buy, 60, 10_000),
+            Err(MarketError::InsufficientCollateral)
+        ));
+        contract.deposit_collateral(U256::from(1)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 60, 10_000).unwrap();
+        assert!(contract.accounts.balance(taker).is_zero());
+        assert_eq!(contract.accounts.balance(maker), U256::from(6_006));
+        assert_eq!(contract.treasury_fees, U256::from(12));
+
+        // The admin's override applies to the market already trading
+        fee_controller.set_schedule("0xmarket", FeeSchedule::zero());
+        contract.place_order(true, OrderSide::Sell, 50, 10_000).unwrap();
+        contract.auth_manager = maker_auth;
+        contract.place_order(true, OrderSide::Buy, 50, 10_000).unwrap();
+        assert_eq!(contract.accounts.balance(maker), U256::from(1_006));
+        assert_eq!(contract.accounts.balance(taker), U256::from(5_000));
+        assert_eq!(contract.treasury_fees, U256::from(12));
+
+        assert_eq!(contract.sweep_treasury_fees().unwrap(), U256::from(12));
+        assert_eq!(contract.accounts.balance(treasury), U256::from(12));
+        assert!(contract.treasury_fees.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_taker_fee_fixed_at_placement() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let fee_controller = Arc::new(FeeController::new(Address::random()));
+        let market = Market {
+            fee_schedule: FeeSchedule::default(),
+            batch_auction: Some(BatchAuction { interval: 60, ends_at: None }),
+            ..test_market("0xmarket", user)
+        };
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker])
+            .with_fee_controller(fee_controller.clone());
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.place_order(false, OrderSide::Buy, 45, 100).unwrap();
+        contract.auth_manager = taker_auth;
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 60, 100).unwrap();
+
+        // Raising the fee while the taker's order rests doesn't charge more than it reserved
+        fee_controller.set_schedule("0xmarket", FeeSchedule { taker_fee_bps: 1_000, ..FeeSchedule::default() });
+        contract.batch_closes_at = Some(0);
+        assert_eq!(contract.clear_batch().unwrap(), Some(57));
+        assert_eq!(contract.accounts.balance(taker), U256::from(1_000 - 57 - 1));
+        assert_eq!(contract.accounts.balance(user), U256::from(957));
+        assert_eq!(contract.treasury_fees, U256::from(1));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_market_voids_and_refunds() {
+        let (auth_manager, user) = connected_auth().await;
+        let market = Market {
+            status: MarketStatus::Locked,
+            fee_schedule: FeeSchedule::default(),
+            parent_condition: Some(ParentCondition {
+                market_id: "0xparent".to_string(),
+                required_outcome: true,
+            }),
+            ..test_market("0xchild", user)
+        };
+        let mut contract = funded_contract(market, auth_manager, &[user]);
+
+        contract.deposit_collateral(U256::from(100)).await.unwrap();
+        contract.mint_tokens(60).await.unwrap();
+        assert!(matches!(contract.place_order(true, OrderSide::Sell, 50, 10), Err(MarketError::MarketNotActive)));
+        assert!(matches!(contract.resolve(true).await, Err(MarketError::MarketNotActive)));
+
+        let resolved = |market_id: &str, outcome: bool| MarketEvent::MarketResolved {
+            market_id: market_id.to_string(),
+            oracle: user,
+            outcome,
+            timestamp: 0,
+            tx_hash: H256::zero(),
+        };
+        assert_eq!(contract.on_parent_resolved(&resolved("0xother", false)), None);
+        assert_eq!(contract.on_parent_resolved(&resolved("0xparent", false)), Some(MarketStatus::Voided));
+
+        assert_eq!(contract.accounts.balance(user), U256::from(100));
+        assert_eq!(contract.token_balance(user, true), U256::zero());
+        assert!(contract.yes_token_supply.is_zero() && contract.no_token_supply.is_zero());
+        assert_eq!(contract.on_parent_resolved(&resolved("0xparent", true)), None);
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_circuit_breaker() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let market = Market {
+            circuit_breaker: Some(CircuitBreaker::default()),
+            ..test_market("0xmarket", user)
+        };
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker]);
+        contract.deposit_collateral(U256::from(10_000)).await.unwrap();
+
+        contract.pause("oracle compromised").unwrap();
+        assert!(matches!(contract.mint_tokens(100).await, Err(MarketError::MarketPaused)));
+        assert!(matches!(contract.place_order(true, OrderSide::Buy, 50, 10), Err(MarketError::MarketPaused)));
+        contract.unpause().unwrap();
+        contract.mint_tokens(100).await.unwrap();
+
+        // 50 -> 70 is a 40% move, beyond the default 20% limit
+        contract.place_order(true, OrderSide::Sell, 50, 10).unwrap();
+        contract.place_order(true, OrderSide::Sell, 70, 10).unwrap();
+        contract.auth_manager = taker_auth;
+        contract.deposit_collateral(U256::from(10_000)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 50, 10).unwrap();
+        contract.place_order(true, OrderSide::Buy, 70, 10).unwrap();
+        assert!(matches!(
+            contract.place_order(true, OrderSide::Buy, 60, 10),
+            Err(MarketError::TradingHalted(_))
+        ));
+
+        contract.auth_manager = auth_manager;
+        contract.resume_trading().unwrap();
+        contract.place_order(true, OrderSide::Buy, 60, 10).unwrap();
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_exposure_limits() {
+        let (auth_manager, user) = connected_auth().await;
+        let market = Market {
+            limits: ExposureLimits {
+                max_open_interest: Some(U256::from(500)),
+                max_position: Some(U256::from(300)),
+                max_total_collateral: Some(U256::from(1_000)),
+            },
+            ..test_market("0xmarket", user)
+        };
+        let mut contract = funded_contract(market, auth_manager, &[user]);
+
+        // The collateral cap is on what the market locks, not on deposits, which may fund
+        // other markets under portfolio margin
+        contract.deposit_collateral(U256::from(2_000)).await.unwrap();
+
+        contract.mint_tokens(200).await.unwrap();
+        assert!(matches!(
+            contract.mint_tokens(101).await,
+            Err(MarketError::LimitExceeded(LimitExceeded::Position { .. }))
+        ));
+
+        // Resting bids count towards the position
+        contract.place_order(true, OrderSide::Buy, 40, 100).unwrap();
+        assert!(matches!(
+            contract.place_order(true, OrderSide::Buy, 40, 1),
+            Err(MarketError::LimitExceeded(LimitExceeded::Position { .. }))
+        ));
+
+        contract.market.limits.max_position = None;
+        contract.mint_tokens(300).await.unwrap();
+        assert!(matches!(
+            contract.mint_tokens(1).await,
+            Err(MarketError::LimitExceeded(LimitExceeded::OpenInterest { .. }))
+        ));
+
+        // 500 complete sets and the bid's 40 of escrow are locked
+        contract.market.limits.max_open_interest = None;
+        assert_eq!(contract.locked_collateral(), U256::from(540));
+        assert!(matches!(
+            contract.mint_tokens(461).await,
+            Err(MarketError::LimitExceeded(LimitExceeded::TotalCollateral { .. }))
+        ));
+        contract.mint_tokens(460).await.unwrap();
+        assert!(matches!(
+            contract.place_order(false, OrderSide::Buy, 40, 1),
+            Err(MarketError::LimitExceeded(LimitExceeded::TotalCollateral { .. }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_early_resolution_flags_late_trades() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let market = Market {
+            expiry_timestamp: now + 86_400,
+            trading_close_timestamp: Some(now + 3_600),
+            resolution_timestamp: Some(now + 7_200),
+            ..test_market("0xmarket", user)
+        };
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker]);
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.mint_tokens(100).await.unwrap();
+        contract.place_order(true, OrderSide::Sell, 50, 10).unwrap();
+        contract.auth_manager = taker_auth;
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 50, 10).unwrap();
+        contract.auth_manager = auth_manager;
+        assert!(matches!(contract.resolve(true).await, Err(MarketError::MarketNotExpired)));
+
+        // The event happened before the trade, so the trade is flagged
+        let event_timestamp = contract.trades[0].timestamp;
+        assert_eq!(contract.propose_early_resolution(true, event_timestamp).unwrap(), vec![0]);
+        assert_eq!(contract.flagged_trades().len(), 1);
+        assert!(matches!(
+            contract.place_order(true, OrderSide::Buy, 50, 10),
+            Err(MarketError::TradingClosed)
+        ));
+
+        assert!(matches!(contract.resolve(false).await, Err(MarketError::ProposalMismatch)));
+        contract.resolve(true).await.unwrap();
+        assert_eq!(contract.market.resolved_outcome, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_advanced_order_types() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let market = test_market("0xmarket", user);
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker]);
+        contract.deposit_collateral(U256::from(10_000)).await.unwrap();
+        contract.mint_tokens(100).await.unwrap();
+        assert!(matches!(
+            contract.submit_order(NewOrder::market(true, OrderSide::Buy, 10, 5)),
+            Err(MarketError::NoLiquidity)
+        ));
+
+        contract.place_order(true, OrderSide::Sell, 50, 20).unwrap();
+        contract.auth_manager = taker_auth.clone();
+        contract.deposit_collateral(U256::from(10_000)).await.unwrap();
+        let post_only = NewOrder {
+            post_only: true,
+            ..NewOrder::limit(true, OrderSide::Buy, 50, 10)
+        };
+        assert!(matches!(contract.submit_order(post_only), Err(MarketError::PostOnlyWouldCross)));
+        let fok = NewOrder {
+            time_in_force: TimeInForce::Fok,
+            ..NewOrder::limit(true, OrderSide::Buy, 50, 30)
+        };
+        assert!(matches!(contract.submit_order(fok), Err(MarketError::FillOrKillUnfilled)));
+        let ioc = NewOrder {
+            time_in_force: TimeInForce::Ioc,
+            ..NewOrder::limit(true, OrderSide::Buy, 50, 30)
+        };
+        let placed = contract.submit_order(ioc).unwrap();
+        assert_eq!((placed.filled_size, placed.resting_size), (20, 0));
+        assert!(contract.order_escrow.is_empty());
+
+        // A sell stop at 45 triggers on the trade at 45 and sells into the 40 bid
+        let stop = NewOrder {
+            stop_price: Some(45),
+            ..NewOrder::market(true, OrderSide::Sell, 10, 10)
+        };
+        contract.auth_manager = auth_manager.clone();
+        contract.submit_order(stop).unwrap();
+        contract.auth_manager = taker_auth;
+        contract.place_order(true, OrderSide::Buy, 40, 10).unwrap();
+        contract.place_order(true, OrderSide::Buy, 45, 5).unwrap();
+        assert_eq!(contract.stop_orders().len(), 1);
+        contract.auth_manager = auth_manager;
+        contract.place_order(true, OrderSide::Sell, 45, 5).unwrap();
+        assert!(contract.stop_orders().is_empty());
+        assert_eq!(contract.last_trade_price(true), Some(40));
+        assert_eq!(contract.yes_book.best_bid(), None);
+
+        // Crossing your own ask cancels the incoming order by default
+        contract.place_order(true, OrderSide::Sell, 55, 5).unwrap();
+        let placed = contract.place_order(true, OrderSide::Buy, 55, 5).unwrap();
+        assert_eq!((placed.filled_size, placed.resting_size), (0, 0));
+        assert_eq!(contract.yes_book.best_ask(), Some(55));
+        let decrement = NewOrder {
+            self_trade_prevention: SelfTradePrevention::Decrement,
+            ..NewOrder::limit(true, OrderSide::Buy, 55, 5)
+        };
+        contract.submit_order(decrement).unwrap();
+        assert_eq!(contract.yes_book.best_ask(), None);
+        assert!(contract.order_escrow.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unified_book_mints_and_burns() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let market = test_market("0xmarket", user);
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker]);
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.auth_manager = taker_auth.clone();
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+
+        // A NO bid at 40 and a YES bid at 60 mint 100 complete sets between them
+        contract.auth_manager = auth_manager.clone();
+        contract.place_order(false, OrderSide::Buy, 40, 100).unwrap();
+        contract.auth_manager = taker_auth.clone();
+        let placed = contract.place_order(true, OrderSide::Buy, 60, 100).unwrap();
+        assert_eq!(placed.filled_size, 100);
+        assert_eq!((contract.yes_token_supply, contract.no_token_supply), (U256::from(100), U256::from(100)));
+        assert_eq!(contract.token_balance(user, false), U256::from(100));
+        assert_eq!(contract.accounts.balance(taker), U256::from(940));
+        assert_eq!(contract.last_trade_price(false), Some(40));
+
+        // A YES ask at 55 and a NO ask at 45 burn them again
+        contract.place_order(true, OrderSide::Sell, 55, 100).unwrap();
+        contract.auth_manager = auth_manager;
+        contract.place_order(false, OrderSide::Sell, 45, 100).unwrap();
+        assert!(contract.yes_token_supply.is_zero() && contract.no_token_supply.is_zero());
+        assert_eq!(contract.accounts.balance(user), U256::from(1_005));
+        assert_eq!(contract.accounts.balance(taker), U256::from(995));
+        assert!(contract.order_escrow.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_auction_clears_at_uniform_price() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let market = Market {
+            batch_auction: Some(BatchAuction { interval: 60, ends_at: None }),
+            ..test_market("0xmarket", user)
+        };
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker]);
+        assert!(contract.batch_closes_at.is_some());
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.auth_manager = taker_auth.clone();
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+
+        // Crossing orders rest until the batch clears
+        contract.auth_manager = auth_manager.clone();
+        contract.place_order(false, OrderSide::Buy, 45, 100).unwrap();
+        contract.auth_manager = taker_auth.clone();
+        let placed = contract.place_order(true, OrderSide::Buy, 60, 100).unwrap();
+        assert_eq!((placed.filled_size, placed.resting_size), (0, 100));
+        assert!(matches!(
+            contract.submit_order(NewOrder::market(true, OrderSide::Buy, 10, 5)),
+            Err(MarketError::BatchAuctionRestingOnly)
+        ));
+        assert!(matches!(contract.clear_batch(), Err(MarketError::BatchAuctionNotDue)));
+
+        // Both sides trade at 57 for YES, 43 for NO, and get back what they locked above it
+        contract.batch_closes_at = Some(0);
+        assert_eq!(contract.clear_batch().unwrap(), Some(57));
+        assert_eq!((contract.yes_token_supply, contract.no_token_supply), (U256::from(100), U256::from(100)));
+        assert_eq!(contract.accounts.balance(user), U256::from(957));
+        assert_eq!(contract.accounts.balance(taker), U256::from(943));
+        assert!(contract.order_escrow.is_empty());
+        assert_eq!(contract.last_trade_price(true), Some(57));
+        assert!(contract.batch_closes_at.is_some_and(|closes_at| closes_at > 0));
+
+        // Back to continuous matching
+        contract.auth_manager = auth_manager.clone();
+        assert_eq!(contract.end_batch_auction().unwrap(), None);
+        contract.place_order(false, OrderSide::Sell, 45, 100).unwrap();
+        contract.auth_manager = taker_auth;
+        let placed = contract.place_order(true, OrderSide::Sell, 55, 100).unwrap();
+        assert_eq!(placed.filled_size, 100);
+        assert!(contract.yes_token_supply.is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_amend_and_cancel_replace() {
+        let (auth_manager, user) = connected_auth().await;
+        let (other_auth, other) = connected_auth().await;
+        let market = test_market("0xmarket", user);
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, other]);
+        contract.deposit_collateral(U256::from(100)).await.unwrap();
+        contract.auth_manager = other_auth.clone();
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+
+        contract.auth_manager = auth_manager.clone();
+        let mine = contract.place_order(true, OrderSide::Buy, 40, 100).unwrap().order_id;
+        contract.auth_manager = other_auth.clone();
+        let theirs = contract.place_order(true, OrderSide::Buy, 40, 100).unwrap().order_id;
+        assert!(matches!(contract.amend_order(mine, None, 50), Err(MarketError::Unauthorized)));
+
+        // Reducing size keeps the front of the queue and unlocks the difference
+        contract.auth_manager = auth_manager.clone();
+        let placed = contract.amend_order(mine, None, 50).unwrap();
+        assert_eq!((placed.order_id, placed.resting_size), (mine, 50));
+        assert_eq!(contract.yes_book.bids()[0].order_id, mine);
+        assert_eq!(contract.accounts.balance(user), U256::from(80));
+        assert_eq!(contract.order_escrow[&mine], U256::from(2_000));
+
+        // A price change goes to the back of the queue
+        contract.amend_order(mine, Some(41), 50).unwrap();
+        contract.amend_order(mine, Some(40), 50).unwrap();
+        let queue: Vec<u64> = contract.yes_book.bids().iter().map(|order| order.order_id).collect();
+        assert_eq!(queue, vec![theirs, mine]);
+
+        // A rejected replacement leaves the original order where it was
+        assert!(matches!(
+            contract.cancel_replace(mine, NewOrder::limit(true, OrderSide::Buy, 90, 500)),
+            Err(MarketError::InsufficientCollateral)
+        ));
+        assert_eq!(contract.yes_book.bids()[1].order_id, mine);
+        assert_eq!(contract.accounts.balance(user), U256::from(80));
+        assert_eq!(contract.order_escrow[&mine], U256::from(2_000));
+
+        // Funds the old order locked count towards the new one
+        let placed = contract.cancel_replace(mine, NewOrder::limit(true, OrderSide::Buy, 50, 200)).unwrap();
+        assert!(placed.order_id > theirs);
+        assert!(contract.yes_book.get(mine).is_none());
+        assert_eq!(contract.yes_book.bids()[0].order_id, placed.order_id);
+        assert!(contract.accounts.balance(user).is_zero());
+    }
+
+    #[tokio::test]
+    async fn test_amend_keeps_post_only() {
+        let (auth_manager, user) = connected_auth().await;
+        let (other_auth, other) = connected_auth().await;
+        let market = test_market("0xmarket", user);
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, other]);
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.mint_tokens(100).await.unwrap();
+        contract.place_order(true, OrderSide::Sell, 50, 100).unwrap();
+
+        contract.auth_manager = other_auth;
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        let post_only = NewOrder {
+            post_only: true,
+            ..NewOrder::limit(true, OrderSide::Buy, 40, 100)
+        };
+        let quote = contract.submit_order(post_only).unwrap().order_id;
+        assert!(contract.yes_book.get(quote).unwrap().post_only);
+
+        // Amending across the spread would take the ask, so the quote stays where it was
+        assert!(matches!(contract.amend_order(quote, Some(55), 100), Err(MarketError::PostOnlyWouldCross)));
+        assert_eq!(contract.yes_book.best_bid(), Some(40));
+        assert_eq!(contract.yes_book.best_ask(), Some(50));
+        contract.amend_order(quote, Some(45), 100).unwrap();
+        assert!(contract.yes_book.get(quote).unwrap().post_only);
+    }
+
+    #[tokio::test]
+    async fn test_liquidity_rewards() {
+        let (auth_manager, user) = connected_auth().await;
+        let (maker_auth, maker) = connected_auth().await;
+        let market = test_market("0xmarket", user);
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, maker]);
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        assert!(matches!(contract.fund_liquidity_rewards(U256::from(300)), Err(MarketError::NoRewardProgram)));
+        let program = RewardProgram {
+            per_sample: 100,
+            sample_interval: 3_600,
+            max_spread: 5,
+            min_size: 1,
+            formula: ScoringFormula::Linear,
+        };
+        contract.set_liquidity_rewards(Some(program)).unwrap();
+        contract.fund_liquidity_rewards(U256::from(300)).unwrap();
+
+        // Only the maker quotes both sides; the creator's bid is too far out to score
+        contract.place_order(true, OrderSide::Buy, 30, 100).unwrap();
+        contract.auth_manager = maker_auth.clone();
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 49, 100).unwrap();
+        contract.place_order(false, OrderSide::Buy, 49, 100).unwrap();
+
+        assert_eq!(contract.sample_liquidity_rewards().unwrap(), U256::from(100));
+        assert!(matches!(contract.sample_liquidity_rewards(), Err(MarketError::RewardSampleNotDue)));
+        assert_eq!(contract.claim_liquidity_rewards().unwrap(), U256::from(100));
+        assert_eq!(contract.accounts.balance(maker), U256::from(1_000 - 49 - 49 + 100));
+
+        // Ending the program refunds what is left
+        contract.auth_manager = maker_auth;
+        assert!(matches!(contract.set_liquidity_rewards(None), Err(MarketError::Unauthorized)));
+        contract.auth_manager = auth_manager;
+        contract.set_liquidity_rewards(None).unwrap();
+        assert_eq!(contract.accounts.balance(user), U256::from(1_000 - 30 - 100));
+    }
+
+    #[tokio::test]
+    async fn test_referral_fee_sharing() {
+        let (auth_manager, user) = connected_auth().await;
+        let (taker_auth, taker) = connected_auth().await;
+        let referrer = Address::random();
+        let market = Market {
+            fee_schedule: FeeSchedule {
+                taker_fee_bps: 1_000,
+                maker_rebate_bps: 0,
+                redemption_fee_bps: 0,
+                creator_share_bps: 0,
+            },
+            ..test_market("0xmarket", user)
+        };
+        let referrals = Arc::new(ReferralRegistry::new(5_000));
+        referrals.register(taker, referrer).unwrap();
+        let mut contract = funded_contract(market, auth_manager.clone(), &[user, taker])
+            .with_referrals(referrals.clone());
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.place_order(true, OrderSide::Buy, 50, 100).unwrap();
+
+        // The first trade links the referral, and the referrer gets half the taker fee
+        contract.auth_manager = taker_auth;
+        contract.deposit_collateral(U256::from(1_000)).await.unwrap();
+        contract.place_order(false, OrderSide::Buy, 50, 100).unwrap();
+        assert_eq!(referrals.referral(taker).unwrap().referrer, referrer);
+        assert_eq!(contract.referral_fees[&referrer], U256::from(2));
+        assert_eq!(contract.treasury_fees, U256::from(3));
+        assert_eq!(referrals.earnings(referrer), U256::from(2));
+        assert!(referrals.register(taker, Address::random()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_portfolio_margin() {
+        let (auth_manager, user) = connected_auth().await;
+        let (other_auth, other) = connected_auth().await;
+        let accounts = Arc::new(CollateralAccounts::new());
+        let venue = Arc::new(SimulatedVenue::new());
+        let custody = Address::random();
+        venue.mint(user, "USDC", U256::from(1_000));
+        venue.mint(other, "USDC", U256::from(1_000));
+        let contract = |id: &str, auth_manager: Arc<AuthManager>| {
+            MarketContractState::new(
+                test_market(id, user),
+                auth_manager,
+                Arc::new(EventLogger::new(false, false, None)),
+                venue.clone(),
+            )
+            .with_custody(custody)
+            .with_accounts(accounts.clone())
+        };
+        let mut first = contract("0xfirst", auth_manager.clone());
+        let mut second = contract("0xsecond", other_auth.clone());
+
+        // Collateral deposited through one market funds orders in the other
+        first.deposit_collateral(U256::from(1_000)).await.unwrap();
+        second.deposit_collateral(U256::from(1_000)).await.unwrap();
+        second.place_order(false, OrderSide::Buy, 40, 100).unwrap();
+        second.auth_manager = auth_manager;
+        second.place_order(true, OrderSide::Buy, 60, 100).unwrap();
+        assert_eq!(accounts.balance(user), U256::from(940));
+        assert_eq!(accounts.balance(other), U256::from(960));
+
+        // Winnings are credited at resolution, with nothing left to claim
+        second.resolve(true).await.unwrap();
+        assert_eq!(accounts.balance(user), U256::from(1_040));
+        assert!(matches!(second.claim_winnings().await, Err(MarketError::InsufficientBalance)));
+        first.withdraw_collateral(U256::from(1_000)).await.unwrap();
+        assert_eq!(accounts.balance(user), U256::from(40));
+
+        // Every balance is backed by collateral held in custody
+        assert_eq!(venue.balance(user, "USDC").await.unwrap(), U256::from(1_000));
+        assert_eq!(venue.balance(custody, "USDC").await.unwrap(), U256::from(1_000));
+
+        // A withdrawal the venue fails leaves the balance where it was
+        venue.transfer_collateral(custody, Address::random(), "USDC", U256::from(1_000)).await.unwrap();
+        assert!(matches!(first.withdraw_collateral(U256::from(40)).await, Err(MarketError::ApiError(_))));
+        assert_eq!(accounts.balance(user), U256::from(40));
+    }
+}
@@ -0,0 +1,736 @@
+use crate::venue::OrderSide;
+use ethers::types::Address;
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+/// A Hyperliquid websocket channel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Subscription {
+    L2Book { coin: String },
+    Trades { coin: String },
+    UserFills { user: Address },
+    OrderUpdates { user: Address },
+}
+
+impl Subscription {
+    fn to_wire(&self) -> Value {
+        match self {
+            Subscription::L2Book { coin } => json!({ "type": "l2Book", "coin": coin }),
+            Subscription::Trades { coin } => json!({ "type": "trades", "coin": coin }),
+            Subscription::UserFills { user } => json!({ "type": "userFills", "user": user }),
+            Subscription::OrderUpdates { user } => json!({ "type": "orderUpdates", "user": user }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BookLevel {
+    #[serde(deserialize_with = "de_f64")]
+    pub px: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub sz: f64,
+    pub n: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct L2Book {
+    pub coin: String,
+    pub time: u64,
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Trade {
+    pub coin: String,
+    #[serde(deserialize_with = "de_side")]
+    pub side: OrderSide,
+    #[serde(deserialize_with = "de_f64")]
+    pub px: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub sz: f64,
+    pub time: u64,
+    pub tid: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserFill {
+    pub coin: String,
+    #[serde(deserialize_with = "de_side")]
+    pub side: OrderSide,
+    #[serde(deserialize_with = "de_f64")]
+    pub px: f64,
+    #[serde(deserialize_with = "de_f64")]
+    pub sz: f64,
+    pub time: u64,
+    pub oid: u64,
+    pub tid: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderUpdate {
+    pub coin: String,
+    pub side: OrderSide,
+    pub limit_px: f64,
+    pub sz: f64,
+    pub orig_sz: f64,
+    pub oid: u64,
+    pub status: String,
+    pub status_timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarketDataEvent {
+    L2Book(L2Book),
+    Trades(Vec<Trade>),
+    UserFills {
+        user: Address,
+        is_snapshot: bool,
+        fills: Vec<UserFill>,
+    },
+    OrderUpdates(Vec<OrderUpdate>),
+    /// The connection dropped and was re-established. Updates published while
+    /// disconnected may be missing, so consumers should refresh any snapshot state.
+    Gap {
+        subscription: Subscription,
+        last_update: Option<u64>,
+        reconnected_at: u64,
+    },
+}
+
+pub type MarketDataStream = mpsc::UnboundedReceiver<MarketDataEvent>;
+
+#[derive(Debug, Clone)]
+pub struct MarketDataConfig {
+    pub initial_reconnect_delay: Duration,
+    pub max_reconnect_delay: Duration,
+    pub ping_interval: Duration,
+    /// Reconnect if nothing arrives for this long, even if the socket looks open.
+    pub stale_timeout: Duration,
+}
+
+impl Default for MarketDataConfig {
+    fn default() -> Self {
+        Self {
+            initial_reconnect_delay: Duration::from_millis(500),
+            max_reconnect_delay: Duration::from_secs(30),
+            ping_interval: Duration::from_secs(30),
+            stale_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+enum Command {
+    Subscribe(Subscription, mpsc::UnboundedSender<MarketDataEvent>),
+}
+
+/// A websocket connection to Hyperliquid's market data feed.
+///
+/// The connection runs on a background task that reconnects with exponential backoff
+/// and replays every active subscription. After a reconnect each stream receives a
+/// `MarketDataEvent::Gap`, and replayed trades and stale books are dropped. A subscription
+/// is dropped upstream once all of its streams are.
+#[derive(Debug, Clone)]
+pub struct MarketDataClient {
+    commands: mpsc::UnboundedSender<Command>,
+    /// Order updates don't name their user, so a connection only streams them for one.
+    order_updates_user: Arc<Mutex<Option<Address>>>,
+}
+
+impl MarketDataClient {
+    /// Starts the background task for `url`; the socket opens on the first subscription.
+    /// Must be called within a tokio runtime.
+    pub fn connect(url: impl Into<String>, config: MarketDataConfig) -> Self {
+        let (commands, receiver) = mpsc::unbounded();
+        let order_updates_user = Arc::default();
+        tokio::spawn(run_connection(url.into(), config, receiver, Arc::clone(&order_updates_user)));
+        Self {
+            commands,
+            order_updates_user,
+        }
+    }
+
+    /// Order updates can only be streamed for one user at a time; other users need a
+    /// connection of their own until all of that user's order update streams are dropped.
+    pub fn subscribe(&self, subscription: Subscription) -> Result<MarketDataStream, String> {
+        if let Subscription::OrderUpdates { user } = subscription {
+            let mut order_updates_user = self.order_updates_user.lock().unwrap();
+            match *order_updates_user {
+                Some(current) if current != user => {
+                    return Err(format!("This connection already streams order updates for {:?}", current));
+                }
+                _ => *order_updates_user = Some(user),
+            }
+        }
+        let (sender, stream) = mpsc::unbounded();
+        self.commands
+            .unbounded_send(Command::Subscribe(subscription, sender))
+            .map_err(|_| "Market data connection has shut down".to_string())?;
+        Ok(stream)
+    }
+}
+
+#[derive(Default)]
+struct SubscriptionState {
+    subscribers: Vec<mpsc::UnboundedSender<MarketDataEvent>>,
+    last_update: Option<u64>,
+    /// Highest trade id seen, used to drop trades replayed after a reconnect.
+    last_tid: Option<u64>,
+}
+
+impl SubscriptionState {
+    fn publish(&mut self, event: MarketDataEvent) {
+        self.subscribers.retain(|subscriber| subscriber.unbounded_send(event.clone()).is_ok());
+    }
+}
+
+enum SessionEnd {
+    Disconnected,
+    Shutdown,
+}
+
+async fn run_connection(
+    url: String,
+    config: MarketDataConfig,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    order_updates_user: Arc<Mutex<Option<Address>>>,
+) {
+    let mut subscriptions: HashMap<Subscription, SubscriptionState> = HashMap::new();
+    let mut reconnect_delay = config.initial_reconnect_delay;
+    let mut connected_before = false;
+
+    // Don't open a socket until someone subscribes.
+    match commands.next().await {
+        Some(Command::Subscribe(subscription, sender)) => {
+            subscriptions.entry(subscription).or_default().subscribers.push(sender);
+        }
+        None => return,
+    }
+
+    loop {
+        if let Ok((socket, _)) = connect_async(url.as_str()).await {
+            reconnect_delay = config.initial_reconnect_delay;
+            if connected_before {
+                let reconnected_at = now_millis();
+                for (subscription, state) in subscriptions.iter_mut() {
+                    let gap = MarketDataEvent::Gap {
+                        subscription: subscription.clone(),
+                        last_update: state.last_update,
+                        reconnected_at,
+                    };
+                    state.publish(gap);
+                }
+            }
+            connected_before = true;
+            drop_closed(&mut subscriptions, &order_updates_user);
+
+            match run_session(socket, &config, &mut subscriptions, &mut commands, &order_updates_user).await {
+                SessionEnd::Shutdown => return,
+                SessionEnd::Disconnected => log::warn!("Market data connection to {} lost", url),
+            }
+        }
+
+        // Keep accepting subscriptions while waiting to reconnect.
+        let sleep = tokio::time::sleep(reconnect_delay);
+        tokio::pin!(sleep);
+        loop {
+            tokio::select! {
+                _ = &mut sleep => break,
+                command = commands.next() => match command {
+                    Some(Command::Subscribe(subscription, sender)) => {
+                        subscriptions.entry(subscription).or_default().subscribers.push(sender);
+                    }
+                    None => return,
+                },
+            }
+        }
+        reconnect_delay = (reconnect_delay * 2).min(config.max_reconnect_delay);
+    }
+}
+
+async fn run_session<S>(
+    socket: S,
+    config: &MarketDataConfig,
+    subscriptions: &mut HashMap<Subscription, SubscriptionState>,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    order_updates_user: &Mutex<Option<Address>>,
+) -> SessionEnd
+where
+    S: futures::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>>
+        + futures::Sink<Message>
+        + Unpin,
+{
+    let (mut write, mut read) = socket.split();
+
+    for subscription in subscriptions.keys() {
+        if write.send(subscribe_message(subscription)).await.is_err() {
+            return SessionEnd::Disconnected;
+        }
+    }
+
+    let mut ping = tokio::time::interval(config.ping_interval);
+    ping.tick().await;
+    let mut last_message = tokio::time::Instant::now();
+
+    loop {
+        tokio::select! {
+            message = read.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    last_message = tokio::time::Instant::now();
+                    dispatch(&text, subscriptions);
+                    if unsubscribe_closed(&mut write, subscriptions, order_updates_user).await.is_err() {
+                        return SessionEnd::Disconnected;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return SessionEnd::Disconnected,
+                Some(Ok(_)) => last_message = tokio::time::Instant::now(),
+            },
+            command = commands.next() => match command {
+                Some(Command::Subscribe(subscription, sender)) => {
+                    let state = subscriptions.entry(subscription.clone()).or_default();
+                    let is_new = state.subscribers.is_empty();
+                    state.subscribers.push(sender);
+                    if is_new && write.send(subscribe_message(&subscription)).await.is_err() {
+                        return SessionEnd::Disconnected;
+                    }
+                }
+                None => return SessionEnd::Shutdown,
+            },
+            _ = ping.tick() => {
+                if last_message.elapsed() > config.stale_timeout {
+                    return SessionEnd::Disconnected;
+                }
+                let ping = Message::Text(json!({ "method": "ping" }).to_string());
+                if write.send(ping).await.is_err() || unsubscribe_closed(&mut write, subscriptions, order_updates_user).await.is_err() {
+                    return SessionEnd::Disconnected;
+                }
+            }
+        }
+    }
+}
+
+fn subscribe_message(subscription: &Subscription) -> Message {
+    Message::Text(json!({ "method": "subscribe", "subscription": subscription.to_wire() }).to_string())
+}
+
+/// Forgets the subscriptions whose streams have all been dropped and returns them. Once
+/// the order updates subscription goes, another user may subscribe to order updates.
+fn drop_closed(
+    subscriptions: &mut HashMap<Subscription, SubscriptionState>,
+    order_updates_user: &Mutex<Option<Address>>,
+) -> Vec<Subscription> {
+    let mut closed = Vec::new();
+    subscriptions.retain(|subscription, state| {
+        state.subscribers.retain(|subscriber| !subscriber.is_closed());
+        if state.subscribers.is_empty() {
+            closed.push(subscription.clone());
+        }
+        !state.subscribers.is_empty()
+    });
+    if closed.iter().any(|subscription| matches!(subscription, Subscription::OrderUpdates { .. })) {
+        *order_updates_user.lock().unwrap() = None;
+    }
+    closed
+}
+
+async fn unsubscribe_closed<W: futures::Sink<Message> + Unpin>(
+    write: &mut W,
+    subscriptions: &mut HashMap<Subscription, SubscriptionState>,
+    order_updates_user: &Mutex<Option<Address>>,
+) -> Result<(), W::Error> {
+    for subscription in drop_closed(subscriptions, order_updates_user) {
+        let message = json!({ "method": "unsubscribe", "subscription": subscription.to_wire() });
+        write.send(Message::Text(message.to_string())).await?;
+    }
+    Ok(())
+}
+
+fn dispatch(text: &str, subscriptions: &mut HashMap<Subscription, SubscriptionState>) {
+    let Ok(message) = serde_json::from_str::<Value>(text) else {
+        log::warn!("Ignoring malformed market data message: {}", text);
+        return;
+    };
+    let data = message.get("data").cloned().unwrap_or(Value::Null);
+
+    let parsed = match message.get("channel").and_then(Value::as_str) {
+        Some("l2Book") => parse_l2_book(data),
+        Some("trades") => parse_trades(data),
+        Some("userFills") => parse_user_fills(data),
+        Some("orderUpdates") => parse_order_updates(data),
+        _ => return,
+    };
+
+    match parsed {
+        Ok(event) => route(event, subscriptions),
+        Err(e) => log::warn!("Failed to parse market data message: {}", e),
+    }
+}
+
+fn route(event: MarketDataEvent, subscriptions: &mut HashMap<Subscription, SubscriptionState>) {
+    match event {
+        MarketDataEvent::L2Book(book) => {
+            let key = Subscription::L2Book { coin: book.coin.clone() };
+            if let Some(state) = subscriptions.get_mut(&key) {
+                // A book older than the last one delivered is stale.
+                if state.last_update.is_some_and(|last| book.time < last) {
+                    return;
+                }
+                state.last_update = Some(book.time);
+                state.publish(MarketDataEvent::L2Book(book));
+            }
+        }
+        MarketDataEvent::Trades(trades) => {
+            let Some(coin) = trades.first().map(|trade| trade.coin.clone()) else {
+                return;
+            };
+            if let Some(state) = subscriptions.get_mut(&Subscription::Trades { coin }) {
+                let fresh: Vec<Trade> = trades
+                    .into_iter()
+                    .filter(|trade| state.last_tid.is_none_or(|last| trade.tid > last))
+                    .collect();
+                if let Some(last) = fresh.iter().map(|trade| trade.tid).max() {
+                    state.last_tid = Some(last);
+                    state.last_update = fresh.iter().map(|trade| trade.time).max();
+                    state.publish(MarketDataEvent::Trades(fresh));
+                }
+            }
+        }
+        MarketDataEvent::UserFills { user, is_snapshot, fills } => {
+            if let Some(state) = subscriptions.get_mut(&Subscription::UserFills { user }) {
+                if let Some(last) = fills.iter().map(|fill| fill.time).max() {
+                    state.last_update = Some(last);
+                }
+                state.publish(MarketDataEvent::UserFills { user, is_snapshot, fills });
+            }
+        }
+        MarketDataEvent::OrderUpdates(updates) => {
+            // Order updates do not name the user; `subscribe` allows one user per connection.
+            for (subscription, state) in subscriptions.iter_mut() {
+                if matches!(subscription, Subscription::OrderUpdates { .. }) {
+                    state.last_update = updates.iter().map(|update| update.status_timestamp).max();
+                    state.publish(MarketDataEvent::OrderUpdates(updates.clone()));
+                }
+            }
+        }
+        MarketDataEvent::Gap { .. } => {}
+    }
+}
+
+fn parse_l2_book(data: Value) -> Result<MarketDataEvent, serde_json::Error> {
+    #[derive(Deserialize)]
+    struct RawBook {
+        coin: String,
+        time: u64,
+        levels: (Vec<BookLevel>, Vec<BookLevel>),
+    }
+
+    let raw: RawBook = serde_json::from_value(data)?;
+    Ok(MarketDataEvent::L2Book(L2Book {
+        coin: raw.coin,
+        time: raw.time,
+        bids: raw.levels.0,
+        asks: raw.levels.1,
+    }))
+}
+
+fn parse_trades(data: Value) -> Result<MarketDataEvent, serde_json::Error> {
+    Ok(MarketDataEvent::Trades(serde_json::from_value(data)?))
+}
+
+fn parse_user_fills(data: Value) -> Result<MarketDataEvent, serde_json::Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RawUserFills {
+        user: Address,
+        #[serde(default)]
+        is_snapshot: bool,
+        fills: Vec<UserFill>,
+    }
+
+    let raw: RawUserFills = serde_json::from_value(data)?;
+    Ok(MarketDataEvent::UserFills {
+        user: raw.user,
+        is_snapshot: raw.is_snapshot,
+        fills: raw.fills,
+    })
+}
+
+fn parse_order_updates(data: Value) -> Result<MarketDataEvent, serde_json::Error> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RawOrder {
+        coin: String,
+        #[serde(deserialize_with = "de_side")]
+        side: OrderSide,
+        #[serde(deserialize_with = "de_f64")]
+        limit_px: f64,
+        #[serde(deserialize_with = "de_f64")]
+        sz: f64,
+        #[serde(deserialize_with = "de_f64")]
+        orig_sz: f64,
+        oid: u64,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct RawOrderUpdate {
+        order: RawOrder,
+        status: String,
+        status_timestamp: u64,
+    }
+
+    let raw: Vec<RawOrderUpdate> = serde_json::from_value(data)?;
+    Ok(MarketDataEvent::OrderUpdates(
+        raw.into_iter()
+            .map(|update| OrderUpdate {
+                coin: update.order.coin,
+                side: update.order.side,
+                limit_px: update.order.limit_px,
+                sz: update.order.sz,
+                orig_sz: update.order.orig_sz,
+                oid: update.order.oid,
+                status: update.status,
+                status_timestamp: update.status_timestamp,
+            })
+            .collect(),
+    ))
+}
+
+fn de_f64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+fn de_side<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OrderSide, D::Error> {
+    match String::deserialize(deserializer)?.as_str() {
+        "B" => Ok(OrderSide::Buy),
+        "A" => Ok(OrderSide::Sell),
+        other => Err(serde::de::Error::custom(format!("unknown side {}", other))),
+    }
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc as tokio_mpsc;
+    use tokio_tungstenite::accept_async;
+
+    /// A stand-in for the Hyperliquid websocket server. Each accepted connection reports
+    /// the subscriptions it receives and replays one scripted batch of messages per
+    /// connection; it closes the socket after the batch when `drop_after` is set. While
+    /// held open it reports unsubscribes as `{"unsubscribe": subscription}`.
+    async fn spawn_stand_in(
+        scripts: Vec<(Vec<String>, bool)>,
+    ) -> (String, tokio_mpsc::UnboundedReceiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (subscribed, subscriptions) = tokio_mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            for (messages, drop_after) in scripts {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut socket = accept_async(stream).await.unwrap();
+
+                while let Some(Ok(Message::Text(text))) = socket.next().await {
+                    let request: Value = serde_json::from_str(&text).unwrap();
+                    if request["method"] == "subscribe" {
+                        subscribed.send(request["subscription"].clone()).unwrap();
+                        break;
+                    }
+                }
+                for message in messages {
+                    socket.send(Message::Text(message)).await.unwrap();
+                }
+                if drop_after {
+                    socket.close(None).await.ok();
+                } else {
+                    // Hold the connection open until the client goes away.
+                    while let Some(Ok(message)) = socket.next().await {
+                        let Message::Text(text) = message else { continue };
+                        let request: Value = serde_json::from_str(&text).unwrap();
+                        if request["method"] == "unsubscribe" {
+                            subscribed.send(json!({ "unsubscribe": request["subscription"] })).unwrap();
+                        }
+                    }
+                }
+            }
+        });
+
+        (url, subscriptions)
+    }
+
+    fn trade_message(tid: u64) -> String {
+        json!({
+            "channel": "trades",
+            "data": [{ "coin": "ETH", "side": "B", "px": "2000.5", "sz": "1.5", "time": 1_000 + tid, "hash": "0x0", "tid": tid }]
+        })
+        .to_string()
+    }
+
+    fn test_config() -> MarketDataConfig {
+        MarketDataConfig {
+            initial_reconnect_delay: Duration::from_millis(10),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_l2_book_subscription() {
+        let book = json!({
+            "channel": "l2Book",
+            "data": {
+                "coin": "ETH",
+                "time": 42,
+                "levels": [
+                    [{ "px": "1999.5", "sz": "3", "n": 2 }],
+                    [{ "px": "2000.5", "sz": "1.25", "n": 1 }]
+                ]
+            }
+        });
+        let (url, mut subscriptions) = spawn_stand_in(vec![(vec![book.to_string()], false)]).await;
+
+        let client = MarketDataClient::connect(url, test_config());
+        let mut stream = client.subscribe(Subscription::L2Book { coin: "ETH".to_string() }).unwrap();
+
+        assert_eq!(
+            subscriptions.recv().await.unwrap(),
+            json!({ "type": "l2Book", "coin": "ETH" })
+        );
+        match stream.next().await.unwrap() {
+            MarketDataEvent::L2Book(book) => {
+                assert_eq!(book.time, 42);
+                assert_eq!(book.bids[0].px, 1999.5);
+                assert_eq!(book.asks[0].sz, 1.25);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_resubscribes_and_reports_gap() {
+        let (url, mut subscriptions) = spawn_stand_in(vec![
+            (vec![trade_message(1), trade_message(2)], true),
+            // The server replays trade 2 after the reconnect; it must not be delivered twice.
+            (vec![trade_message(2), trade_message(3)], false),
+        ])
+        .await;
+
+        let client = MarketDataClient::connect(url, test_config());
+        let mut stream = client.subscribe(Subscription::Trades { coin: "ETH".to_string() }).unwrap();
+
+        let mut tids = Vec::new();
+        let mut gaps = 0;
+        while tids.len() < 3 {
+            match stream.next().await.unwrap() {
+                MarketDataEvent::Trades(trades) => tids.extend(trades.iter().map(|trade| trade.tid)),
+                MarketDataEvent::Gap { last_update, .. } => {
+                    assert_eq!(last_update, Some(1_002));
+                    gaps += 1;
+                }
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+
+        assert_eq!(tids, vec![1, 2, 3]);
+        assert_eq!(gaps, 1);
+        // Subscribed once per connection.
+        assert!(subscriptions.recv().await.is_some());
+        assert!(subscriptions.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_streams_unsubscribe() {
+        let (url, mut subscriptions) = spawn_stand_in(vec![(vec![trade_message(1)], false)]).await;
+        let config = MarketDataConfig {
+            ping_interval: Duration::from_millis(10),
+            ..test_config()
+        };
+
+        let client = MarketDataClient::connect(url, config);
+        let mut stream = client.subscribe(Subscription::Trades { coin: "ETH".to_string() }).unwrap();
+        assert!(subscriptions.recv().await.is_some());
+        assert!(matches!(stream.next().await, Some(MarketDataEvent::Trades(_))));
+
+        drop(stream);
+        assert_eq!(
+            subscriptions.recv().await.unwrap(),
+            json!({ "unsubscribe": { "type": "trades", "coin": "ETH" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_order_updates_for_one_user_per_connection() {
+        let client = MarketDataClient::connect("ws://127.0.0.1:9", test_config());
+        let (user, other) = (Address::random(), Address::random());
+        let _first = client.subscribe(Subscription::OrderUpdates { user }).unwrap();
+        let _second = client.subscribe(Subscription::OrderUpdates { user }).unwrap();
+        assert!(client.subscribe(Subscription::OrderUpdates { user: other }).is_err());
+        assert!(client.subscribe(Subscription::UserFills { user: other }).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_order_updates_free_for_another_user_after_unsubscribe() {
+        let (url, mut subscriptions) = spawn_stand_in(vec![(Vec::new(), false)]).await;
+        let config = MarketDataConfig {
+            ping_interval: Duration::from_millis(10),
+            ..test_config()
+        };
+
+        let client = MarketDataClient::connect(url, config);
+        let (user, other) = (Address::random(), Address::random());
+        let stream = client.subscribe(Subscription::OrderUpdates { user }).unwrap();
+        assert!(subscriptions.recv().await.is_some());
+        assert!(client.subscribe(Subscription::OrderUpdates { user: other }).is_err());
+
+        drop(stream);
+        assert_eq!(
+            subscriptions.recv().await.unwrap(),
+            json!({ "unsubscribe": { "type": "orderUpdates", "user": user } })
+        );
+        assert!(client.subscribe(Subscription::OrderUpdates { user: other }).is_ok());
+    }
+
+    #[test]
+    fn test_parse_user_fills_and_order_updates() {
+        let user = Address::random();
+        let fills = parse_user_fills(json!({
+            "user": user,
+            "isSnapshot": true,
+            "fills": [{ "coin": "ETH", "side": "A", "px": "0.61", "sz": "10", "time": 5, "oid": 7, "tid": 9 }]
+        }))
+        .unwrap();
+        assert!(matches!(
+            fills,
+            MarketDataEvent::UserFills { is_snapshot: true, ref fills, .. } if fills[0].side == OrderSide::Sell
+        ));
+
+        let updates = parse_order_updates(json!([{
+            "order": { "coin": "ETH", "side": "B", "limitPx": "0.4", "sz": "5", "origSz": "10", "oid": 7, "timestamp": 1 },
+            "status": "open",
+            "statusTimestamp": 3
+        }]))
+        .unwrap();
+        match updates {
+            MarketDataEvent::OrderUpdates(updates) => {
+                assert_eq!(updates[0].oid, 7);
+                assert_eq!(updates[0].orig_sz, 10.0);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
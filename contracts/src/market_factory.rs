@@ -1,11 +1,20 @@
 use crate::{
+    accounts::CollateralAccounts,
     auth::{AuthManager, AuthError},
+    circuit_breaker::CircuitBreaker,
     events::{EventEmitter, MarketEvent},
-    hyperliquid_client::HyperliquidClient,
-    market::{Market, MarketStatus},
+    fees::{FeeController, FeeSchedule},
+    limits::ExposureLimits,
+    market::{Market, MarketError, MarketMetadata, MarketStatus, ParentCondition},
+    market_group::{GroupError, MarketGroup},
+    pause::PauseController,
+    referrals::{ReferralError, ReferralRegistry},
+    templates::{MarketSpec, MarketTemplate, TemplateError, TemplateParams},
+    validation::{question_similarity, MarketProposal, ValidationRules, Violation},
+    venue::{outcome_token_id, VenueClient, VenueError},
 };
 use async_trait::async_trait;
-use ethers::types::{Address, U256};
+use ethers::types::{Address, H256, U256};
 use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 use std::{collections::HashMap, sync::Arc};
@@ -20,9 +29,51 @@ pub enum MarketFactoryError {
     #[error("Authentication error: {0}")]
     AuthError(#[from] AuthError),
     #[error("API error: {0}")]
-    ApiError(String),
+    ApiError(#[from] VenueError),
     #[error("Unauthorized")]
     Unauthorized,
+    #[error("Market already exists: {0}")]
+    MarketAlreadyExists(String),
+    #[error("Insufficient collateral for listing fee: required {required}, available {available}")]
+    InsufficientListingFee { required: U256, available: U256 },
+    #[error("Withdrawal exceeds collected fees")]
+    InsufficientFeeBalance,
+    #[error("Invalid fee schedule")]
+    InvalidFeeSchedule,
+    #[error("Market not found: {0}")]
+    MarketNotFound(String),
+    #[error("Market validation failed: {}", describe_violations(.0))]
+    ValidationFailed(Vec<Violation>),
+    #[error("Template error: {0}")]
+    TemplateError(#[from] TemplateError),
+    #[error("Group error: {0}")]
+    GroupError(#[from] GroupError),
+    #[error("Invalid parent condition: {0}")]
+    InvalidParentCondition(String),
+    #[error("Factory is paused")]
+    FactoryPaused,
+    #[error("Invalid trading schedule: {0}")]
+    InvalidSchedule(String),
+    #[error("Referral error: {0}")]
+    ReferralError(#[from] ReferralError),
+}
+
+fn describe_violations(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Outcome for one template instance. In a dry run `outcome` reports whether the market
+/// would be created; otherwise whether it was.
+#[derive(Debug)]
+pub struct BulkCreateResult {
+    pub params: TemplateParams,
+    pub market_id: Option<String>,
+    pub question: Option<String>,
+    pub outcome: Result<(), MarketFactoryError>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,12 +94,18 @@ pub enum MarketFactoryEvent {
 
 #[async_trait]
 pub trait MarketFactory {
+    /// Creates a market. With a `parent_condition` the market starts locked and only
+    /// activates if the parent resolves to the required outcome.
+    #[allow(clippy::too_many_arguments)]
     async fn create_market(
         &mut self,
         question: String,
         expiry_timestamp: u64,
         oracle_id: Address,
         collateral_token: String,
+        metadata: MarketMetadata,
+        salt: u64,
+        parent_condition: Option<ParentCondition>,
     ) -> Result<String, MarketFactoryError>;
 
     fn get_market(&self, market_id: String) -> Option<Market>;
@@ -57,66 +114,457 @@ pub trait MarketFactory {
 }
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct MarketFactoryState {
     markets: HashMap<String, Market>,
     oracle_whitelist: Vec<Address>,
-    next_market_id: u64,
     auth_manager: Arc<AuthManager>,
     event_emitter: Arc<dyn EventEmitter>,
     listing_fee: U256,
-    client: HyperliquidClient,
+    client: Arc<dyn VenueClient>,
+    admin: Address, // Deployer; also the treasury account that holds collected fees
+    fee_balances: HashMap<String, U256>, // Collected, unwithdrawn fees per collateral token
+    default_fee_schedule: FeeSchedule, // Applied to newly created markets
+    default_limits: ExposureLimits, // Applied to newly created markets
+    validation_rules: ValidationRules,
+    groups: HashMap<String, MarketGroup>,
+    pause_controller: Arc<PauseController>, // Shared with the factory's markets
+    referrals: Arc<ReferralRegistry>, // Shared with the factory's markets
+    fee_controller: Arc<FeeController>, // Shared with the factory's markets
+    accounts: HashMap<String, Arc<CollateralAccounts>>, // Portfolio margin accounts per collateral token
 }
 
 impl MarketFactoryState {
     pub async fn new(
-        _api_url: &str,
+        client: Arc<dyn VenueClient>,
         auth_manager: Arc<AuthManager>,
         event_emitter: Arc<dyn EventEmitter>,
         listing_fee: U256,
     ) -> Result<Self, MarketFactoryError> {
-        let client = HyperliquidClient::new(auth_manager.clone());
-
+        let admin = auth_manager.get_current_address()?;
         Ok(Self {
             markets: HashMap::new(),
             oracle_whitelist: Vec::new(),
-            next_market_id: 0,
             auth_manager,
             event_emitter,
             listing_fee,
             client,
+            admin,
+            fee_balances: HashMap::new(),
+            default_fee_schedule: FeeSchedule::default(),
+            default_limits: ExposureLimits::unlimited(),
+            validation_rules: ValidationRules::default(),
+            groups: HashMap::new(),
+            pause_controller: Arc::new(PauseController::new(admin)),
+            referrals: Arc::new(ReferralRegistry::default()),
+            fee_controller: Arc::new(FeeController::new(admin)),
+            accounts: HashMap::new(),
         })
     }
 
-    fn generate_market_id(&mut self) -> String {
-        let id = self.next_market_id;
-        self.next_market_id += 1;
-        format!("MARKET_{}", id)
+    /// Pass to `MarketContractState::with_pause_controller` so markets honour the
+    /// factory's pause switches and pauser role.
+    pub fn pause_controller(&self) -> Arc<PauseController> {
+        self.pause_controller.clone()
     }
 
-    #[allow(dead_code)]
-    fn generate_token_address(market_id: &str, is_yes: bool) -> String {
-        let mut hasher = Keccak256::new();
-        hasher.update(market_id.as_bytes());
-        hasher.update(if is_yes { b"YES\0\0" } else { b"NO\0\0\0" });
-        format!("0x{}", hex::encode(hasher.finalize()))
+    /// Pass to `MarketContractState::with_referrals` so markets link referrals and pay
+    /// referral fees.
+    pub fn referrals(&self) -> Arc<ReferralRegistry> {
+        self.referrals.clone()
     }
 
-    async fn get_caller_address(&self) -> Result<Address, MarketFactoryError> {
-        self.auth_manager.get_current_address().map_err(MarketFactoryError::AuthError)
+    /// Pass to `MarketContractState::with_fee_controller` so markets follow fee schedule
+    /// changes and can sweep their protocol fees to the treasury.
+    pub fn fee_controller(&self) -> Arc<FeeController> {
+        self.fee_controller.clone()
     }
-}
 
-#[async_trait]
-impl MarketFactory for MarketFactoryState {
-    async fn create_market(
+    /// The collateral accounts shared by the factory's markets in `collateral_token`. Pass to
+    /// `MarketContractState::with_accounts` to trade those markets on portfolio margin.
+    pub fn accounts(&mut self, collateral_token: &str) -> Arc<CollateralAccounts> {
+        self.accounts.entry(collateral_token.to_string()).or_default().clone()
+    }
+
+    /// Registers `referrer` for the caller. It takes effect at the caller's first trade,
+    /// so it must come before it.
+    pub async fn register_referrer(&mut self, referrer: Address) -> Result<(), MarketFactoryError> {
+        let user = self.get_caller_address().await?;
+        self.referrals.register(user, referrer)?;
+        self.event_emitter.emit_market_event(MarketEvent::ReferralRegistered {
+            user,
+            referrer,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+        Ok(())
+    }
+
+    /// Sets the share of referred users' taker fees paid to their referrers. Admin only.
+    pub async fn set_referral_share(&mut self, share_bps: u64) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        self.referrals.set_share_bps(share_bps)?;
+        Ok(())
+    }
+
+    pub async fn add_pauser(&mut self, pauser: Address) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        self.pause_controller.grant_pauser(pauser);
+        Ok(())
+    }
+
+    pub async fn remove_pauser(&mut self, pauser: Address) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        self.pause_controller.revoke_pauser(pauser);
+        Ok(())
+    }
+
+    /// Pauses market creation and minting, trading and claiming on every market.
+    pub async fn pause(&mut self, reason: &str) -> Result<(), MarketFactoryError> {
+        let pauser = self.ensure_pauser().await?;
+        self.pause_controller.set_factory_paused(true);
+        self.emit_paused(None, pauser, reason);
+        Ok(())
+    }
+
+    pub async fn unpause(&mut self) -> Result<(), MarketFactoryError> {
+        let pauser = self.ensure_pauser().await?;
+        self.pause_controller.set_factory_paused(false);
+        self.emit_unpaused(None, pauser);
+        Ok(())
+    }
+
+    pub async fn pause_market(&mut self, market_id: &str, reason: &str) -> Result<(), MarketFactoryError> {
+        let pauser = self.ensure_pauser().await?;
+        if !self.markets.contains_key(market_id) {
+            return Err(MarketFactoryError::MarketNotFound(market_id.to_string()));
+        }
+        self.pause_controller.set_market_paused(market_id, true);
+        self.emit_paused(Some(market_id.to_string()), pauser, reason);
+        Ok(())
+    }
+
+    pub async fn unpause_market(&mut self, market_id: &str) -> Result<(), MarketFactoryError> {
+        let pauser = self.ensure_pauser().await?;
+        if !self.markets.contains_key(market_id) {
+            return Err(MarketFactoryError::MarketNotFound(market_id.to_string()));
+        }
+        self.pause_controller.set_market_paused(market_id, false);
+        self.emit_unpaused(Some(market_id.to_string()), pauser);
+        Ok(())
+    }
+
+    fn emit_paused(&self, market_id: Option<String>, pauser: Address, reason: &str) {
+        self.event_emitter.emit_market_event(MarketEvent::Paused {
+            market_id,
+            pauser,
+            reason: reason.to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+    }
+
+    fn emit_unpaused(&self, market_id: Option<String>, pauser: Address) {
+        self.event_emitter.emit_market_event(MarketEvent::Unpaused {
+            market_id,
+            pauser,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+    }
+
+    /// Sets or removes (`None`) a market's circuit breaker.
+    pub async fn set_market_circuit_breaker(
         &mut self,
-        question: String,
-        expiry_timestamp: u64,
-        oracle_id: Address,
-        collateral_token: String,
+        market_id: &str,
+        circuit_breaker: Option<CircuitBreaker>,
+    ) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        let market = self.markets
+            .get_mut(market_id)
+            .ok_or_else(|| MarketFactoryError::MarketNotFound(market_id.to_string()))?;
+        market.circuit_breaker = circuit_breaker;
+        Ok(())
+    }
+
+    pub fn validation_rules(&self) -> &ValidationRules {
+        &self.validation_rules
+    }
+
+    pub async fn set_validation_rules(&mut self, rules: ValidationRules) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        self.validation_rules = rules;
+        Ok(())
+    }
+
+    /// Checks a proposed market against the validation rules and existing markets
+    /// without creating it.
+    pub fn validate_market(&self, proposal: &MarketProposal) -> Vec<Violation> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.validation_rules.validate(proposal, self.markets.values(), now)
+    }
+
+    pub fn default_fee_schedule(&self) -> FeeSchedule {
+        self.default_fee_schedule
+    }
+
+    /// Sets the fee schedule for markets created from now on. Existing markets keep theirs.
+    pub async fn set_default_fee_schedule(&mut self, schedule: FeeSchedule) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        if !schedule.is_valid() {
+            return Err(MarketFactoryError::InvalidFeeSchedule);
+        }
+        self.default_fee_schedule = schedule;
+        Ok(())
+    }
+
+    /// Overrides one market's fee schedule. Markets sharing the factory's fee controller
+    /// apply it from their next fill.
+    pub async fn set_market_fee_schedule(
+        &mut self,
+        market_id: &str,
+        schedule: FeeSchedule,
+    ) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        if !schedule.is_valid() {
+            return Err(MarketFactoryError::InvalidFeeSchedule);
+        }
+        let market = self.markets
+            .get_mut(market_id)
+            .ok_or_else(|| MarketFactoryError::MarketNotFound(market_id.to_string()))?;
+        market.fee_schedule = schedule;
+        self.fee_controller.set_schedule(market_id, schedule);
+        Ok(())
+    }
+
+    pub fn default_limits(&self) -> ExposureLimits {
+        self.default_limits
+    }
+
+    /// Sets the exposure caps for markets created from now on. Existing markets keep theirs.
+    pub async fn set_default_limits(&mut self, limits: ExposureLimits) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        self.default_limits = limits;
+        Ok(())
+    }
+
+    /// Overrides one market's exposure caps. Caps only block new exposure; positions
+    /// already above a lowered cap are left alone.
+    pub async fn set_market_limits(
+        &mut self,
+        market_id: &str,
+        limits: ExposureLimits,
+    ) -> Result<(), MarketFactoryError> {
+        self.ensure_admin().await?;
+        let market = self.markets
+            .get_mut(market_id)
+            .ok_or_else(|| MarketFactoryError::MarketNotFound(market_id.to_string()))?;
+        market.limits = limits;
+        Ok(())
+    }
+
+    pub fn listing_fee(&self) -> U256 {
+        self.listing_fee
+    }
+
+    /// The treasury account collected fees are transferred to. Pass to
+    /// `MarketContractState::with_custody` so it also holds the markets' deposited collateral.
+    pub fn treasury(&self) -> Address {
+        self.admin
+    }
+
+    pub fn fee_balance(&self, token: &str) -> U256 {
+        self.fee_balances.get(token).copied().unwrap_or_default()
+    }
+
+    /// Sends collected fees from the treasury to `recipient`. Only the admin can withdraw.
+    pub async fn withdraw_fees(
+        &mut self,
+        token: &str,
+        amount: U256,
+        recipient: Address,
+    ) -> Result<H256, MarketFactoryError> {
+        self.ensure_admin().await?;
+
+        let balance = self.fee_balance(token);
+        if amount > balance {
+            return Err(MarketFactoryError::InsufficientFeeBalance);
+        }
+
+        let tx_hash = self.client
+            .transfer_collateral(self.admin, recipient, token, amount)
+            .await?;
+        self.fee_balances.insert(token.to_string(), balance - amount);
+
+        self.event_emitter.emit_market_event(MarketEvent::FeesWithdrawn {
+            token: token.to_string(),
+            recipient,
+            amount,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash,
+        });
+
+        Ok(tx_hash)
+    }
+
+    /// Links existing, ungrouped, active markets into a mutually exclusive group.
+    pub async fn create_group(
+        &mut self,
+        title: String,
+        market_ids: Vec<String>,
+        salt: u64,
     ) -> Result<String, MarketFactoryError> {
-        if expiry_timestamp <= std::time::SystemTime::now()
+        let creator = self.get_caller_address().await?;
+        let unique: std::collections::HashSet<&String> = market_ids.iter().collect();
+        if unique.len() < 2 || unique.len() != market_ids.len() {
+            return Err(GroupError::TooFewMarkets.into());
+        }
+        for market_id in &market_ids {
+            let market = self.markets
+                .get(market_id)
+                .ok_or_else(|| GroupError::MarketNotFound(market_id.clone()))?;
+            if market.group_id.is_some() {
+                return Err(GroupError::MarketAlreadyGrouped(market_id.clone()).into());
+            }
+            if market.status != MarketStatus::Active {
+                return Err(GroupError::MarketNotActive(market_id.clone()).into());
+            }
+        }
+
+        let group_id = MarketGroup::generate_group_id(creator, &title, salt);
+        if self.groups.contains_key(&group_id) {
+            return Err(GroupError::GroupAlreadyExists(group_id).into());
+        }
+        for market_id in &market_ids {
+            if let Some(market) = self.markets.get_mut(market_id) {
+                market.group_id = Some(group_id.clone());
+            }
+        }
+        self.groups.insert(group_id.clone(), MarketGroup {
+            id: group_id.clone(),
+            title: title.clone(),
+            market_ids: market_ids.clone(),
+            creator,
+            winning_market_id: None,
+            converted_amount: U256::zero(),
+        });
+
+        self.event_emitter.emit_market_event(MarketEvent::GroupCreated {
+            group_id: group_id.clone(),
+            title,
+            market_ids,
+            creator,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+
+        Ok(group_id)
+    }
+
+    pub fn get_group(&self, group_id: &str) -> Option<MarketGroup> {
+        self.groups.get(group_id).cloned()
+    }
+
+    pub fn list_groups(&self) -> Vec<MarketGroup> {
+        self.groups.values().cloned().collect()
+    }
+
+    /// The group's markets, in the order they were linked.
+    pub fn group_markets(&self, group_id: &str) -> Result<Vec<Market>, MarketFactoryError> {
+        let group = self.groups
+            .get(group_id)
+            .ok_or_else(|| GroupError::GroupNotFound(group_id.to_string()))?;
+        Ok(group
+            .market_ids
+            .iter()
+            .filter_map(|market_id| self.markets.get(market_id).cloned())
+            .collect())
+    }
+
+    /// Resolves every market in the group at once: the winner YES, all others NO.
+    /// The caller must be the oracle of every member market, and each member must pass the
+    /// checks resolving it alone would: not paused, and resolvable to its outcome now.
+    ///
+    /// This only updates the registry. Callers holding the members' live
+    /// `MarketContractState`s must apply the result with `resolve_from_group`, passing the
+    /// group from `get_group`, so holders can claim.
+    pub async fn resolve_group(
+        &mut self,
+        group_id: &str,
+        winning_market_id: &str,
+    ) -> Result<Vec<(String, bool)>, MarketFactoryError> {
+        let caller = self.get_caller_address().await?;
+        let group = self.groups
+            .get(group_id)
+            .ok_or_else(|| GroupError::GroupNotFound(group_id.to_string()))?;
+        if group.winning_market_id.is_some() {
+            return Err(GroupError::GroupAlreadyResolved.into());
+        }
+        let outcomes = group.outcomes(winning_market_id)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        for (market_id, outcome) in &outcomes {
+            let market = self.markets
+                .get(market_id)
+                .ok_or_else(|| GroupError::MarketNotFound(market_id.clone()))?;
+            if market.oracle_id != format!("{:?}", caller) {
+                return Err(MarketFactoryError::InvalidOracle);
+            }
+            if self.pause_controller.is_paused(market_id) {
+                return Err(GroupError::MarketError(MarketError::MarketPaused).into());
+            }
+            market.check_resolvable(*outcome, timestamp).map_err(|e| match e {
+                MarketError::MarketNotActive => GroupError::MarketNotActive(market_id.clone()),
+                e => GroupError::MarketError(e),
+            })?;
+        }
+        for (market_id, outcome) in &outcomes {
+            if let Some(market) = self.markets.get_mut(market_id) {
+                market.status = MarketStatus::Resolved;
+                market.resolved_outcome = Some(*outcome);
+            }
+            let event = MarketEvent::MarketResolved {
+                market_id: market_id.clone(),
+                oracle: caller,
+                outcome: *outcome,
+                timestamp,
+                tx_hash: H256::from_slice(&Keccak256::digest(format!("{}:resolve:{}", market_id, timestamp))),
+            };
+            self.apply_parent_resolution(&event);
+            self.event_emitter.emit_market_event(event);
+        }
+        if let Some(group) = self.groups.get_mut(group_id) {
+            group.winning_market_id = Some(winning_market_id.to_string());
+        }
+
+        self.event_emitter.emit_market_event(MarketEvent::GroupResolved {
+            group_id: group_id.to_string(),
+            winning_market_id: winning_market_id.to_string(),
+            timestamp,
+        });
+
+        Ok(outcomes)
+    }
+
+    /// Runs every check `create_market` makes before touching the venue and returns the
+    /// id the market would get.
+    fn preflight(&self, creator: Address, spec: &MarketSpec) -> Result<String, MarketFactoryError> {
+        if spec.expiry_timestamp <= std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs()
@@ -124,29 +572,187 @@ impl MarketFactory for MarketFactoryState {
             return Err(MarketFactoryError::InvalidExpiryTime);
         }
 
-        if !self.oracle_whitelist.contains(&oracle_id) {
+        if !self.oracle_whitelist.contains(&spec.oracle_id) {
             return Err(MarketFactoryError::InvalidOracle);
         }
 
+        let market_id = Self::generate_market_id(creator, &spec.question, spec.expiry_timestamp, spec.salt);
+        if self.markets.contains_key(&market_id) {
+            return Err(MarketFactoryError::MarketAlreadyExists(market_id));
+        }
+        if let Some(condition) = &spec.parent_condition {
+            self.check_parent_condition(condition, spec.expiry_timestamp)?;
+        }
+        Self::check_schedule(spec)?;
+        let violations = self.validate_market(&MarketProposal {
+            question: &spec.question,
+            expiry_timestamp: spec.expiry_timestamp,
+            collateral_token: &spec.collateral_token,
+            resolution_source: &spec.metadata.resolution_source,
+        });
+        if !violations.is_empty() {
+            return Err(MarketFactoryError::ValidationFailed(violations));
+        }
+
+        Ok(market_id)
+    }
+
+    /// Trading must close no later than the scheduled resolution, which in turn can't be
+    /// after expiry.
+    fn check_schedule(spec: &MarketSpec) -> Result<(), MarketFactoryError> {
+        let resolution = spec.resolution_timestamp.unwrap_or(spec.expiry_timestamp);
+        if resolution > spec.expiry_timestamp {
+            return Err(MarketFactoryError::InvalidSchedule(
+                "resolution must not be after expiry".to_string(),
+            ));
+        }
+        if let Some(close) = spec.trading_close_timestamp {
+            if close > resolution {
+                return Err(MarketFactoryError::InvalidSchedule(
+                    "trading must close no later than resolution".to_string(),
+                ));
+            }
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            if close <= now {
+                return Err(MarketFactoryError::InvalidSchedule(
+                    "trading close must be in the future".to_string(),
+                ));
+            }
+        }
+        if spec.batch_auction.is_some_and(|auction| auction.interval == 0) {
+            return Err(MarketFactoryError::InvalidSchedule(
+                "batch auction interval must be positive".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The parent must still be unresolved and must expire no later than the child, so the
+    /// child can't reach its own expiry while still locked.
+    fn check_parent_condition(
+        &self,
+        condition: &ParentCondition,
+        expiry_timestamp: u64,
+    ) -> Result<(), MarketFactoryError> {
+        let parent = self.markets.get(&condition.market_id).ok_or_else(|| {
+            MarketFactoryError::InvalidParentCondition(format!("parent market {} not found", condition.market_id))
+        })?;
+        if !matches!(parent.status, MarketStatus::Active | MarketStatus::Locked) {
+            return Err(MarketFactoryError::InvalidParentCondition(format!(
+                "parent market {} is {:?}",
+                condition.market_id, parent.status
+            )));
+        }
+        if parent.expiry_timestamp > expiry_timestamp {
+            return Err(MarketFactoryError::InvalidParentCondition(
+                "market must not expire before its parent".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Markets conditional on `parent_market_id`.
+    pub fn conditional_markets(&self, parent_market_id: &str) -> Vec<Market> {
+        self.markets
+            .values()
+            .filter(|market| {
+                market
+                    .parent_condition
+                    .as_ref()
+                    .is_some_and(|condition| condition.market_id == parent_market_id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Updates the registry after `MarketResolved` or `MarketVoided`: locked children of the
+    /// market activate or void, and voiding cascades to their own children.
+    /// Returns the markets whose status changed.
+    pub fn apply_parent_resolution(&mut self, event: &MarketEvent) -> Vec<(String, MarketStatus)> {
+        let mut pending = match event {
+            MarketEvent::MarketResolved { market_id, outcome, .. } => vec![(market_id.clone(), Some(*outcome))],
+            MarketEvent::MarketVoided { market_id, .. } => vec![(market_id.clone(), None)],
+            _ => return Vec::new(),
+        };
+
+        let mut changed = Vec::new();
+        while let Some((parent_market_id, outcome)) = pending.pop() {
+            for market in self.markets.values_mut() {
+                let Some(condition) = &market.parent_condition else { continue };
+                if condition.market_id != parent_market_id || market.status != MarketStatus::Locked {
+                    continue;
+                }
+                let status = match outcome {
+                    Some(outcome) => condition.status_after(outcome),
+                    None => MarketStatus::Voided,
+                };
+                if status == MarketStatus::Voided {
+                    pending.push((market.id.clone(), None));
+                }
+                market.status = status.clone();
+                changed.push((market.id.clone(), status));
+            }
+        }
+        changed
+    }
+
+    /// Creates a market from a full spec, including an optional trading schedule that the
+    /// `create_market` shorthand leaves unset.
+    pub async fn create_market_from_spec(&mut self, spec: MarketSpec) -> Result<String, MarketFactoryError> {
+        if self.pause_controller.is_factory_paused() {
+            return Err(MarketFactoryError::FactoryPaused);
+        }
         let caller_address = self.get_caller_address().await?;
 
         // Generate market ID and create token markets
-        let market_id = self.generate_market_id();
+        let market_id = self.preflight(caller_address, &spec)?;
+        let MarketSpec {
+            question,
+            expiry_timestamp,
+            oracle_id,
+            collateral_token,
+            metadata,
+            parent_condition,
+            trading_close_timestamp,
+            resolution_timestamp,
+            batch_auction,
+            ..
+        } = spec;
+        // The fee is only taken once the pair exists, so a failed listing costs nothing. Pair
+        // creation is idempotent, so a listing whose fee transfer failed can be retried.
+        self.check_listing_fee(caller_address, &collateral_token).await?;
         let (yes_token_address, no_token_address) = self.client
             .create_market_pair(&market_id, &collateral_token)
-            .await
-            .map_err(|e| MarketFactoryError::ApiError(e))?;
+            .await?;
+        self.collect_listing_fee(&market_id, caller_address, &collateral_token).await?;
 
         // Create market
         let market = Market {
+            id: market_id.clone(),
             question: question.clone(),
             expiry_timestamp,
             oracle_id: format!("{:?}", oracle_id),
-            collateral_token: format!("{:?}", collateral_token),
-            status: MarketStatus::Active,
+            collateral_token,
+            status: if parent_condition.is_some() { MarketStatus::Locked } else { MarketStatus::Active },
             yes_token_address: yes_token_address.clone(),
             no_token_address: no_token_address.clone(),
             resolved_outcome: None,
+            creator: caller_address,
+            fee_schedule: self.default_fee_schedule,
+            metadata: metadata.clone(),
+            metadata_history: Vec::new(),
+            group_id: None,
+            parent_condition: parent_condition.clone(),
+            circuit_breaker: Some(CircuitBreaker::default()),
+            limits: self.default_limits,
+            trading_close_timestamp,
+            resolution_timestamp,
+            early_resolution: None,
+            batch_auction,
+            liquidity_rewards: None,
         };
 
         self.markets.insert(market_id.clone(), market.clone());
@@ -160,6 +766,8 @@ impl MarketFactory for MarketFactoryState {
             oracle_id,
             yes_token: yes_token_address,
             no_token: no_token_address,
+            metadata,
+            parent_condition,
             timestamp: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -169,6 +777,199 @@ impl MarketFactory for MarketFactoryState {
         Ok(market_id)
     }
 
+    /// Instantiates `template` once per parameter set and creates the markets.
+    ///
+    /// Instances are independent: one failing doesn't stop the rest. With `dry_run` nothing
+    /// is created and every instance is only validated, including against near-duplicates
+    /// elsewhere in the same batch.
+    pub async fn create_markets_from_template(
+        &mut self,
+        template: &MarketTemplate,
+        params: &[TemplateParams],
+        dry_run: bool,
+    ) -> Result<Vec<BulkCreateResult>, MarketFactoryError> {
+        let creator = self.get_caller_address().await?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut results = Vec::with_capacity(params.len());
+        let mut batch: Vec<(String, String)> = Vec::new(); // (market id, question) of valid instances
+        for instance_params in params {
+            let spec = match template.instantiate(instance_params, now) {
+                Ok(spec) => spec,
+                Err(e) => {
+                    results.push(BulkCreateResult {
+                        params: instance_params.clone(),
+                        market_id: None,
+                        question: None,
+                        outcome: Err(e.into()),
+                    });
+                    continue;
+                }
+            };
+            let market_id = Self::generate_market_id(creator, &spec.question, spec.expiry_timestamp, spec.salt);
+            let question = spec.question.clone();
+
+            let outcome = if dry_run {
+                self.preflight(creator, &spec).and_then(|_| {
+                    let duplicates = self.batch_duplicates(&question, &batch);
+                    if duplicates.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(MarketFactoryError::ValidationFailed(duplicates))
+                    }
+                })
+            } else {
+                self.create_market_from_spec(spec).await.map(|_| ())
+            };
+
+            if outcome.is_ok() {
+                batch.push((market_id.clone(), question.clone()));
+            }
+            results.push(BulkCreateResult {
+                params: instance_params.clone(),
+                market_id: Some(market_id),
+                question: Some(question),
+                outcome,
+            });
+        }
+
+        Ok(results)
+    }
+
+    fn batch_duplicates(&self, question: &str, batch: &[(String, String)]) -> Vec<Violation> {
+        let Some(threshold) = self.validation_rules.duplicate_similarity else {
+            return Vec::new();
+        };
+        batch
+            .iter()
+            .filter_map(|(market_id, other)| {
+                let similarity = question_similarity(question, other);
+                (similarity >= threshold).then(|| Violation::NearDuplicate {
+                    market_id: market_id.clone(),
+                    similarity,
+                })
+            })
+            .collect()
+    }
+
+    /// Rejects creators who can't pay the listing fee before anything is created.
+    async fn check_listing_fee(&self, creator: Address, token: &str) -> Result<(), MarketFactoryError> {
+        if self.listing_fee.is_zero() {
+            return Ok(());
+        }
+
+        let available = self.client.balance(creator, token).await?;
+        if available < self.listing_fee {
+            return Err(MarketFactoryError::InsufficientListingFee {
+                required: self.listing_fee,
+                available,
+            });
+        }
+        Ok(())
+    }
+
+    /// Debits the listing fee from the creator's collateral on the venue into the treasury.
+    async fn collect_listing_fee(
+        &mut self,
+        market_id: &str,
+        creator: Address,
+        token: &str,
+    ) -> Result<(), MarketFactoryError> {
+        if self.listing_fee.is_zero() {
+            return Ok(());
+        }
+
+        let tx_hash = self.client
+            .transfer_collateral(creator, self.admin, token, self.listing_fee)
+            .await?;
+        *self.fee_balances.entry(token.to_string()).or_default() += self.listing_fee;
+
+        self.event_emitter.emit_market_event(MarketEvent::ListingFeeCollected {
+            market_id: market_id.to_string(),
+            creator,
+            token: token.to_string(),
+            amount: self.listing_fee,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            tx_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Derives a market id that any deployment can recompute from the creation
+    /// parameters. The question is hashed first so every field has a fixed width.
+    pub fn generate_market_id(
+        creator: Address,
+        question: &str,
+        expiry_timestamp: u64,
+        salt: u64,
+    ) -> String {
+        let mut hasher = Keccak256::new();
+        hasher.update(creator.as_bytes());
+        hasher.update(Keccak256::digest(question.as_bytes()));
+        hasher.update(expiry_timestamp.to_be_bytes());
+        hasher.update(salt.to_be_bytes());
+        format!("0x{}", hex::encode(hasher.finalize()))
+    }
+
+    pub fn generate_token_address(market_id: &str, is_yes: bool) -> String {
+        outcome_token_id(market_id, is_yes)
+    }
+
+    async fn get_caller_address(&self) -> Result<Address, MarketFactoryError> {
+        self.auth_manager.get_current_address().map_err(MarketFactoryError::AuthError)
+    }
+
+    async fn ensure_admin(&self) -> Result<(), MarketFactoryError> {
+        if self.get_caller_address().await? != self.admin {
+            return Err(MarketFactoryError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    async fn ensure_pauser(&self) -> Result<Address, MarketFactoryError> {
+        let caller = self.get_caller_address().await?;
+        if !self.pause_controller.is_pauser(caller) {
+            return Err(MarketFactoryError::Unauthorized);
+        }
+        Ok(caller)
+    }
+}
+
+#[async_trait]
+impl MarketFactory for MarketFactoryState {
+    #[allow(clippy::too_many_arguments)]
+    async fn create_market(
+        &mut self,
+        question: String,
+        expiry_timestamp: u64,
+        oracle_id: Address,
+        collateral_token: String,
+        metadata: MarketMetadata,
+        salt: u64,
+        parent_condition: Option<ParentCondition>,
+    ) -> Result<String, MarketFactoryError> {
+        self.create_market_from_spec(MarketSpec {
+            question,
+            expiry_timestamp,
+            oracle_id,
+            collateral_token,
+            metadata,
+            salt,
+            parent_condition,
+            trading_close_timestamp: None,
+            resolution_timestamp: None,
+            batch_auction: None,
+        })
+        .await
+    }
+
     fn get_market(&self, market_id: String) -> Option<Market> {
         self.markets.get(&market_id).cloned()
     }
@@ -205,10 +1006,23 @@ impl MarketFactory for MarketFactoryState {
 mod tests {
     use super::*;
     use crate::events::EventLogger;
+    use crate::venue::{OrderRequest, OrderSide, SimulatedVenue};
     use ethers::core::k256::ecdsa::SigningKey;
-    use ethers::signers::LocalWallet;
+    use ethers::signers::{LocalWallet, Signer};
+
+    fn source(url: &str) -> MarketMetadata {
+        MarketMetadata {
+            resolution_source: url.to_string(),
+            ..MarketMetadata::default()
+        }
+    }
 
     async fn setup_test_factory() -> (MarketFactoryState, LocalWallet) {
+        let (factory, wallet, _) = setup_test_factory_with_venue().await;
+        (factory, wallet)
+    }
+
+    async fn setup_test_factory_with_venue() -> (MarketFactoryState, LocalWallet, SimulatedVenue) {
         // Create test wallet
         let wallet: LocalWallet = SigningKey::random(&mut rand::thread_rng()).into();
         let private_key = wallet.signer().to_bytes().to_vec();
@@ -228,8 +1042,12 @@ mod tests {
         // Create event logger
         let event_logger = Arc::new(EventLogger::new(true, false, None));
 
+        // Fund the creator so listing fees can be paid
+        let venue = SimulatedVenue::new();
+        venue.mint(wallet.address(), "USDC", U256::from(1000));
+
         let factory = MarketFactoryState::new(
-            "http://localhost:8080",
+            Arc::new(venue.clone()),
             auth_manager,
             event_logger,
             U256::from(100),
@@ -237,7 +1055,7 @@ mod tests {
         .await
         .unwrap();
 
-        (factory, wallet)
+        (factory, wallet, venue)
     }
 
     #[tokio::test]
@@ -259,6 +1077,9 @@ mod tests {
                 future_timestamp,
                 wallet.address(),
                 "USDC".to_string(),
+                source("https://www.coingecko.com"),
+                0,
+                None,
             )
             .await;
 
@@ -286,9 +1107,326 @@ mod tests {
                 future_timestamp,
                 Address::zero(),
                 "USDC".to_string(),
+                source("https://www.coingecko.com"),
+                0,
+                None,
             )
             .await;
 
         assert!(matches!(result, Err(MarketFactoryError::InvalidOracle)));
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_market_ids_are_deterministic() {
+        let (mut factory, wallet) = setup_test_factory().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        let creator = factory.auth_manager.get_current_address().unwrap();
+        let question = "Will ETH price be above $2000 tomorrow?".to_string();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+
+        let market_id = factory
+            .create_market(question.clone(), expiry, wallet.address(), "USDC".to_string(), source("https://www.coingecko.com"), 7, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            market_id,
+            MarketFactoryState::generate_market_id(creator, &question, expiry, 7)
+        );
+
+        let market = factory.get_market(market_id.clone()).unwrap();
+        assert_eq!(market.id, market_id);
+        assert_eq!(
+            market.yes_token_address,
+            MarketFactoryState::generate_token_address(&market_id, true)
+        );
+        assert_eq!(
+            market.no_token_address,
+            MarketFactoryState::generate_token_address(&market_id, false)
+        );
+
+        // Same parameters collide; a new salt gives a new market once duplicate
+        // question detection is switched off.
+        let duplicate = factory
+            .create_market(question.clone(), expiry, wallet.address(), "USDC".to_string(), source("https://www.coingecko.com"), 7, None)
+            .await;
+        assert!(matches!(duplicate, Err(MarketFactoryError::MarketAlreadyExists(_))));
+        factory
+            .set_validation_rules(ValidationRules {
+                duplicate_similarity: None,
+                ..ValidationRules::default()
+            })
+            .await
+            .unwrap();
+        let other_id = factory
+            .create_market(question, expiry, wallet.address(), "USDC".to_string(), source("https://www.coingecko.com"), 8, None)
+            .await
+            .unwrap();
+        assert_ne!(other_id, market_id);
+    }
+
+    #[tokio::test]
+    async fn test_listing_fee_collected_and_withdrawn() {
+        let (mut factory, wallet, venue) = setup_test_factory_with_venue().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+
+        factory
+            .create_market("Will it rain?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://weather.gov"), 0, None)
+            .await
+            .unwrap();
+        assert_eq!(factory.fee_balance("USDC"), U256::from(100));
+
+        let recipient = Address::random();
+        factory.withdraw_fees("USDC", U256::from(60), recipient).await.unwrap();
+        assert_eq!(factory.fee_balance("USDC"), U256::from(40));
+        assert_eq!(venue.balance(recipient, "USDC").await.unwrap(), U256::from(60));
+
+        let result = factory.withdraw_fees("USDC", U256::from(41), recipient).await;
+        assert!(matches!(result, Err(MarketFactoryError::InsufficientFeeBalance)));
+    }
+
+    #[tokio::test]
+    async fn test_create_market_insufficient_listing_fee() {
+        let (mut factory, wallet, venue) = setup_test_factory_with_venue().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        venue.transfer_collateral(wallet.address(), Address::random(), "USDC", U256::from(950)).await.unwrap();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+
+        let result = factory
+            .create_market("Will it rain?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://weather.gov"), 0, None)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(MarketFactoryError::InsufficientListingFee { available, .. }) if available == U256::from(50)
+        ));
+        assert!(factory.list_markets().is_empty());
+
+        // Nothing was registered with the venue, so the same market can be listed once funded.
+        venue.mint(wallet.address(), "USDC", U256::from(50));
+        factory
+            .create_market("Will it rain?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://weather.gov"), 0, None)
+            .await
+            .unwrap();
+        assert_eq!(factory.fee_balance("USDC"), U256::from(100));
+    }
+
+    #[tokio::test]
+    async fn test_create_market_retries_after_failed_fee_transfer() {
+        let (mut factory, wallet, venue) = setup_test_factory_with_venue().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+
+        // A resting buy holds back 950 of the 1000 balance, so the fee check passes but the
+        // transfer fails after the token pair was created
+        let (other_yes, _) = venue.create_market_pair("OTHER", "USDC").await.unwrap();
+        let resting = venue.place_order(OrderRequest {
+            owner: wallet.address(),
+            token: other_yes.clone(),
+            side: OrderSide::Buy,
+            price: 50,
+            size: 1_900,
+        }).await.unwrap();
+        let result = factory
+            .create_market("Will it rain?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://weather.gov"), 0, None)
+            .await;
+        assert!(matches!(result, Err(MarketFactoryError::ApiError(VenueError::Rejected(_)))));
+        assert!(factory.list_markets().is_empty());
+
+        venue.cancel_order(wallet.address(), &other_yes, resting.order_id).await.unwrap();
+        factory
+            .create_market("Will it rain?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://weather.gov"), 0, None)
+            .await
+            .unwrap();
+        assert_eq!(factory.fee_balance("USDC"), U256::from(100));
+    }
+
+    #[tokio::test]
+    async fn test_create_market_reports_violations() {
+        let (mut factory, wallet) = setup_test_factory().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+
+        factory
+            .create_market("Will it rain in Paris tomorrow?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://weather.gov"), 0, None)
+            .await
+            .unwrap();
+
+        let result = factory
+            .create_market("Will it rain in Paris, tomorrow".to_string(), expiry, wallet.address(), "DOGE".to_string(), MarketMetadata::default(), 0, None)
+            .await;
+
+        match result {
+            Err(MarketFactoryError::ValidationFailed(violations)) => {
+                assert_eq!(violations.len(), 3);
+                assert!(violations.contains(&Violation::InvalidCollateralToken { token: "DOGE".to_string() }));
+                assert!(violations.contains(&Violation::MissingResolutionSource));
+                assert!(matches!(violations[2], Violation::NearDuplicate { .. }));
+            }
+            other => panic!("expected validation failure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_dry_run_and_bulk_create() {
+        let (mut factory, wallet) = setup_test_factory().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+
+        // `copy` is not used in the question, so each asset appears twice
+        let template = MarketTemplate {
+            question: "Will {asset} close above ${threshold} this week?".to_string(),
+            expiry: crate::templates::ExpiryRule::AfterCreation { seconds: 86400 },
+            oracle_id: wallet.address(),
+            collateral_token: "USDC".to_string(),
+            metadata: source("https://www.coingecko.com"),
+            salt: 0,
+            parent_condition: None,
+            grid: [
+                ("asset".to_string(), vec!["BTC".to_string(), "ETH".to_string()]),
+                ("copy".to_string(), vec!["a".to_string(), "b".to_string()]),
+                ("threshold".to_string(), vec!["100".to_string()]),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let params = template.grid_params();
+
+        let results = factory.create_markets_from_template(&template, &params, true).await.unwrap();
+        let valid: Vec<bool> = results.iter().map(|result| result.outcome.is_ok()).collect();
+        assert_eq!(valid, vec![true, false, true, false]);
+        assert!(matches!(results[1].outcome, Err(MarketFactoryError::ValidationFailed(_))));
+        assert!(factory.list_markets().is_empty());
+
+        let results = factory.create_markets_from_template(&template, &params, false).await.unwrap();
+        assert!(matches!(results[1].outcome, Err(MarketFactoryError::MarketAlreadyExists(_))));
+        assert_eq!(factory.list_markets().len(), 2);
+        let market_id = results[2].market_id.clone().unwrap();
+        assert_eq!(
+            factory.get_market(market_id).unwrap().question,
+            "Will ETH close above $100 this week?"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_group_resolves_exactly_one_yes() {
+        let (mut factory, wallet) = setup_test_factory().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+
+        let mut market_ids = Vec::new();
+        for candidate in ["Alice", "Bob", "Carol"] {
+            let question = format!("Will {} win the election?", candidate);
+            market_ids.push(
+                factory
+                    .create_market(question, expiry, wallet.address(), "USDC".to_string(), source("https://vote.gov"), 0, None)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let group_id = factory
+            .create_group("Who wins the election?".to_string(), market_ids.clone(), 0)
+            .await
+            .unwrap();
+        assert_eq!(factory.group_markets(&group_id).unwrap().len(), 3);
+        let regroup = factory.create_group("Again".to_string(), market_ids[..2].to_vec(), 0).await;
+        assert!(matches!(regroup, Err(MarketFactoryError::GroupError(GroupError::MarketAlreadyGrouped(_)))));
+
+        // Members can't be resolved before their resolution time or while paused
+        let early = factory.resolve_group(&group_id, &market_ids[1]).await;
+        assert!(matches!(early, Err(MarketFactoryError::GroupError(GroupError::MarketError(MarketError::MarketNotExpired)))));
+        for market_id in &market_ids {
+            factory.markets.get_mut(market_id).unwrap().resolution_timestamp = Some(0);
+        }
+        factory.pause_controller.set_market_paused(&market_ids[0], true);
+        let paused = factory.resolve_group(&group_id, &market_ids[1]).await;
+        assert!(matches!(paused, Err(MarketFactoryError::GroupError(GroupError::MarketError(MarketError::MarketPaused)))));
+        factory.pause_controller.set_market_paused(&market_ids[0], false);
+
+        // A voided member can't be resolved, and nothing else is either
+        factory.markets.get_mut(&market_ids[2]).unwrap().status = MarketStatus::Voided;
+        let voided = factory.resolve_group(&group_id, &market_ids[1]).await;
+        assert!(matches!(voided, Err(MarketFactoryError::GroupError(GroupError::MarketNotActive(_)))));
+        assert_eq!(factory.get_market(market_ids[1].clone()).unwrap().status, MarketStatus::Active);
+        factory.markets.get_mut(&market_ids[2]).unwrap().status = MarketStatus::Active;
+
+        let outcomes = factory.resolve_group(&group_id, &market_ids[1]).await.unwrap();
+        assert_eq!(outcomes.iter().filter(|(_, outcome)| *outcome).count(), 1);
+        assert_eq!(factory.get_market(market_ids[1].clone()).unwrap().resolved_outcome, Some(true));
+        assert_eq!(factory.get_market(market_ids[0].clone()).unwrap().resolved_outcome, Some(false));
+
+        let again = factory.resolve_group(&group_id, &market_ids[0]).await;
+        assert!(matches!(again, Err(MarketFactoryError::GroupError(GroupError::GroupAlreadyResolved))));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_markets_follow_parent() {
+        let (mut factory, wallet) = setup_test_factory().await;
+        factory.add_oracle(wallet.address()).await.unwrap();
+        let expiry = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() + 86400;
+        let condition = |market_id: &str, required_outcome: bool| {
+            Some(ParentCondition { market_id: market_id.to_string(), required_outcome })
+        };
+
+        let parent = factory
+            .create_market("Will the Fed cut rates in March?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://fed.gov"), 0, None)
+            .await
+            .unwrap();
+        let missing = factory
+            .create_market("If the ECB cuts, will EUR fall?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://ecb.eu"), 0, condition("0xmissing", true))
+            .await;
+        assert!(matches!(missing, Err(MarketFactoryError::InvalidParentCondition(_))));
+        let too_early = factory
+            .create_market("If the Fed cuts, will gold rally?".to_string(), expiry - 60, wallet.address(), "USDC".to_string(), source("https://lbma.org"), 0, condition(&parent, true))
+            .await;
+        assert!(matches!(too_early, Err(MarketFactoryError::InvalidParentCondition(_))));
+
+        let on_cut = factory
+            .create_market("If the Fed cuts, will BTC hit a new high?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://coingecko.com"), 0, condition(&parent, true))
+            .await
+            .unwrap();
+        let on_hold = factory
+            .create_market("If the Fed holds, will the dollar strengthen?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://ice.com"), 0, condition(&parent, false))
+            .await
+            .unwrap();
+        let nested = factory
+            .create_market("If the dollar strengthens, will gold fall?".to_string(), expiry, wallet.address(), "USDC".to_string(), source("https://lbma.org"), 0, condition(&on_hold, true))
+            .await
+            .unwrap();
+        assert_eq!(factory.get_market(on_cut.clone()).unwrap().status, MarketStatus::Locked);
+        assert_eq!(factory.conditional_markets(&parent).len(), 2);
+
+        let changed = factory.apply_parent_resolution(&MarketEvent::MarketResolved {
+            market_id: parent,
+            oracle: wallet.address(),
+            outcome: true,
+            timestamp: 0,
+            tx_hash: H256::zero(),
+        });
+        assert_eq!(changed.len(), 3);
+        assert_eq!(factory.get_market(on_cut).unwrap().status, MarketStatus::Active);
+        assert_eq!(factory.get_market(on_hold).unwrap().status, MarketStatus::Voided);
+        assert_eq!(factory.get_market(nested).unwrap().status, MarketStatus::Voided);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{
+    events::MarketEvent,
+    market::{MarketContractState, MarketError, MarketStatus},
+};
+use ethers::types::{Address, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GroupError {
+    #[error("Group not found: {0}")]
+    GroupNotFound(String),
+    #[error("Group already exists: {0}")]
+    GroupAlreadyExists(String),
+    #[error("A group needs at least two markets")]
+    TooFewMarkets,
+    #[error("Market not found: {0}")]
+    MarketNotFound(String),
+    #[error("Market {0} already belongs to a group")]
+    MarketAlreadyGrouped(String),
+    #[error("Market {0} is not active")]
+    MarketNotActive(String),
+    #[error("Market {0} is not in this group")]
+    NotAMember(String),
+    #[error("Group already resolved")]
+    GroupAlreadyResolved,
+    #[error("Conversion must include every other market in the group exactly once")]
+    IncompleteConversion,
+    #[error("Market error: {0}")]
+    MarketError(#[from] MarketError),
+}
+
+/// Binary markets on one event, such as one market per candidate in an election.
+///
+/// Outcomes are mutually exclusive: the group resolves by naming a single winning market,
+/// which resolves YES while every other member resolves NO.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketGroup {
+    pub id: String,
+    pub title: String,
+    pub market_ids: Vec<String>,
+    pub creator: Address,
+    pub winning_market_id: Option<String>,
+    pub converted_amount: U256, // NO sets converted to YES; the members' collateral backs them jointly
+}
+
+impl MarketGroup {
+    pub fn generate_group_id(creator: Address, title: &str, salt: u64) -> String {
+        let mut hasher = Keccak256::new();
+        hasher.update(b"GROUP");
+        hasher.update(creator.as_bytes());
+        hasher.update(Keccak256::digest(title.as_bytes()));
+        hasher.update(salt.to_be_bytes());
+        format!("0x{}", hex::encode(hasher.finalize()))
+    }
+
+    pub fn contains(&self, market_id: &str) -> bool {
+        self.market_ids.iter().any(|id| id == market_id)
+    }
+
+    /// Each member's outcome once `winning_market_id` has won.
+    pub fn outcomes(&self, winning_market_id: &str) -> Result<Vec<(String, bool)>, GroupError> {
+        if !self.contains(winning_market_id) {
+            return Err(GroupError::NotAMember(winning_market_id.to_string()));
+        }
+        Ok(self
+            .market_ids
+            .iter()
+            .map(|id| (id.clone(), id == winning_market_id))
+            .collect())
+    }
+
+    /// Negative risk conversion: `amount` NO in every member except `target` becomes
+    /// `amount` YES in `target` plus `(n - 2) * amount` collateral.
+    ///
+    /// Since exactly one member resolves YES, the NO tokens pay `n - 1` if `target` wins
+    /// and `n - 2` otherwise, which is what YES on `target` plus `n - 2` collateral pays.
+    /// The collateral is credited in `target`'s market.
+    pub fn convert_negative_risk(
+        &mut self,
+        target: &mut MarketContractState,
+        others: &mut [&mut MarketContractState],
+        amount: u64,
+    ) -> Result<U256, GroupError> {
+        if self.winning_market_id.is_some() {
+            return Err(GroupError::GroupAlreadyResolved);
+        }
+        if amount == 0 {
+            return Err(MarketError::InvalidAmount.into());
+        }
+        if !self.contains(&target.market.id) {
+            return Err(GroupError::NotAMember(target.market.id.clone()));
+        }
+        let other_ids: HashSet<&str> = others.iter().map(|market| market.market.id.as_str()).collect();
+        let expected: HashSet<&str> = self
+            .market_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| *id != target.market.id)
+            .collect();
+        if other_ids != expected || others.len() != expected.len() {
+            return Err(GroupError::IncompleteConversion);
+        }
+
+        let user = target.auth_manager.get_current_address().map_err(MarketError::from)?;
+        let amount_u256 = U256::from(amount);
+        for market in std::iter::once(&*target).chain(others.iter().map(|market| &**market)) {
+            if market.market.status != MarketStatus::Active {
+                return Err(GroupError::MarketNotActive(market.market.id.clone()));
+            }
+        }
+        for market in others.iter() {
+            if market.token_balance(user, false) < amount_u256 {
+                return Err(MarketError::InsufficientBalance.into());
+            }
+        }
+
+        for market in others.iter_mut() {
+            let (_, no) = market.user_balances.entry(user).or_default();
+            *no -= amount_u256;
+            market.no_token_supply -= amount_u256;
+        }
+        let (yes, _) = target.user_balances.entry(user).or_default();
+        *yes += amount_u256;
+        target.yes_token_supply += amount_u256;
+        let collateral = amount_u256 * U256::from(others.len().saturating_sub(1));
+        target.accounts.credit(user, collateral);
+        self.converted_amount += amount_u256;
+
+        target.event_emitter.emit_market_event(MarketEvent::NegativeRiskConverted {
+            group_id: self.id.clone(),
+            user,
+            target_market_id: target.market.id.clone(),
+            amount,
+            collateral_released: collateral,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        });
+
+        Ok(collateral)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::AuthManager, fees::FeeSchedule,
+        market::{
+            tests::{connected_auth, funded_contract, test_market},
+            Market,
+        },
+        MarketContract,
+    };
+    use std::sync::Arc;
+
+    async fn grouped_market(id: &str, auth_manager: Arc<AuthManager>) -> MarketContractState {
+        let market = Market {
+            question: format!("Will {} win?", id),
+            expiry_timestamp: u64::MAX,
+            fee_schedule: FeeSchedule::default(),
+            group_id: Some("0xgroup".to_string()),
+            ..test_market(id, Address::zero())
+        };
+        let user = auth_manager.get_current_address().unwrap();
+        let mut contract = funded_contract(market, auth_manager, &[user]);
+        contract.deposit_collateral(U256::from(100)).await.unwrap();
+        contract.mint_tokens(100).await.unwrap();
+        contract
+    }
+
+    #[tokio::test]
+    async fn test_negative_risk_conversion() {
+        let (auth_manager, user) = connected_auth().await;
+
+        let mut alice = grouped_market("alice", auth_manager.clone()).await;
+        let mut bob = grouped_market("bob", auth_manager.clone()).await;
+        let mut carol = grouped_market("carol", auth_manager.clone()).await;
+        let mut group = MarketGroup {
+            id: "0xgroup".to_string(),
+            title: "Who wins?".to_string(),
+            market_ids: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+            creator: user,
+            winning_market_id: None,
+            converted_amount: U256::zero(),
+        };
+
+        let incomplete = group.convert_negative_risk(&mut alice, &mut [&mut bob], 40);
+        assert!(matches!(incomplete, Err(GroupError::IncompleteConversion)));
+
+        let released = group
+            .convert_negative_risk(&mut alice, &mut [&mut bob, &mut carol], 40)
+            .unwrap();
+        assert_eq!(released, U256::from(40));
+        assert_eq!(alice.token_balance(user, true), U256::from(140));
+        assert_eq!(bob.token_balance(user, false), U256::from(60));
+        assert_eq!(carol.token_balance(user, false), U256::from(60));
+        assert_eq!(alice.accounts.balance(user), U256::from(40));
+
+        // Grouped markets can't be resolved one by one
+        assert!(matches!(alice.resolve(true).await, Err(MarketError::GroupedMarket)));
+        group.winning_market_id = Some("bob".to_string());
+        alice.resolve_from_group(&group).unwrap();
+        bob.resolve_from_group(&group).unwrap();
+        assert_eq!(alice.market.resolved_outcome, Some(false));
+        assert_eq!(bob.market.resolved_outcome, Some(true));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::venue::{OrderRequest, OrderSide, VenueClient, VenueError};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+
+/// Where a market's fair value comes from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum FairValue {
+    /// A fixed YES price in cents.
+    Static { price: u64 },
+    /// For "Will `coin` be above `strike`?" markets: the last trade of `coin` on the price
+    /// feed, mapped through a logistic curve that moves from 27% to 73% over `scale`
+    /// either side of the strike.
+    PriceFeed { coin: String, strike: f64, scale: f64 },
+    /// A probability between 0 and 1 at a JSON `pointer` in the document at `url`, e.g.
+    /// another venue's odds.
+    External { url: String, pointer: String },
+}
+
+impl FairValue {
+    /// Converts a probability to a quotable YES price.
+    pub fn from_probability(probability: f64) -> Option<u64> {
+        if !(0.0..=1.0).contains(&probability) {
+            return None;
+        }
+        Some(((probability * 100.0).round() as u64).clamp(1, 99))
+    }
+
+    /// The YES price a `PriceFeed` source implies for an underlying price.
+    pub fn from_underlying(price: f64, strike: f64, scale: f64) -> Option<u64> {
+        if scale <= 0.0 {
+            return None;
+        }
+        Self::from_probability(1.0 / (1.0 + (-(price - strike) / scale).exp()))
+    }
+}
+
+/// How the bot quotes around fair value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuoteConfig {
+    pub spread: u64, // Cents between bid and ask
+    pub size: u64,
+    pub max_inventory: u64, // The side that would grow the position past this stops quoting
+    pub skew: u64, // Cents both quotes move against a position of `max_inventory`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    pub side: OrderSide,
+    pub price: u64,
+    pub size: u64,
+}
+
+impl QuoteConfig {
+    /// Bid and ask around `fair` for a position of `inventory` YES tokens. A long position
+    /// lowers both quotes so the ask is more likely to fill, and a short one raises them.
+    pub fn quotes(&self, fair: u64, inventory: i64) -> Vec<Quote> {
+        let max_inventory = self.max_inventory.max(1) as i64;
+        let shift = self.skew as i64 * inventory.clamp(-max_inventory, max_inventory) / max_inventory;
+        let center = fair as i64 - shift;
+        let bid = (center - (self.spread / 2) as i64).clamp(1, 98) as u64;
+        let ask = (center + self.spread.div_ceil(2) as i64).clamp(bid as i64 + 1, 99) as u64;
+
+        let mut quotes = Vec::new();
+        if inventory < max_inventory {
+            let size = self.size.min((max_inventory - inventory) as u64);
+            quotes.push(Quote { side: OrderSide::Buy, price: bid, size });
+        }
+        if inventory > -max_inventory {
+            let size = self.size.min((max_inventory + inventory) as u64);
+            quotes.push(Quote { side: OrderSide::Sell, price: ask, size });
+        }
+        quotes
+    }
+}
+
+/// Quotes two-sided liquidity on one market's YES token.
+///
+/// Each `step` picks up fills of the bot's orders to track its position, then cancels and
+/// replaces the quotes whenever one filled or fair value moved them.
+///
+/// The quotes rest on the venue's book for the market's outcome tokens rather than on a
+/// `MarketContractState` book. A contract state only lives as long as the process that built
+/// it and nothing shares its book between processes, while the venue's book is the one every
+/// trader of the spot-deployed outcome tokens sees, so that is where liquidity reaches users.
+#[derive(Debug)]
+pub struct MarketMaker {
+    pub market_id: String,
+    owner: Address,
+    token: String,
+    config: QuoteConfig,
+    venue: Arc<dyn VenueClient>,
+    inventory: i64,
+    open: Vec<(u64, Quote)>,
+    filled: HashMap<u64, u64>, // Fill size already counted per order
+}
+
+impl MarketMaker {
+    /// `token` is the market's YES token as the venue names it, as returned by
+    /// `VenueClient::create_market_pair`.
+    pub fn new(market_id: &str, token: &str, owner: Address, config: QuoteConfig, venue: Arc<dyn VenueClient>) -> Self {
+        Self {
+            market_id: market_id.to_string(),
+            owner,
+            token: token.to_string(),
+            config,
+            venue,
+            inventory: 0,
+            open: Vec::new(),
+            filled: HashMap::new(),
+        }
+    }
+
+    /// Net YES tokens bought by the bot's quotes.
+    pub fn inventory(&self) -> i64 {
+        self.inventory
+    }
+
+    pub fn open_quotes(&self) -> Vec<Quote> {
+        self.open.iter().map(|(_, quote)| *quote).collect()
+    }
+
+    /// Updates the position from new fills and requotes around `fair` if needed. Returns
+    /// whether the quotes were replaced.
+    pub async fn step(&mut self, fair: u64) -> Result<bool, VenueError> {
+        let filled = self.sync_fills().await?;
+        let target = self.config.quotes(fair, self.inventory);
+        if !filled && self.open_quotes() == target {
+            return Ok(false);
+        }
+
+        self.cancel_all().await?;
+        for quote in target {
+            let placed = self.venue
+                .place_order(OrderRequest {
+                    owner: self.owner,
+                    token: self.token.clone(),
+                    side: quote.side,
+                    price: quote.price,
+                    size: quote.size,
+                })
+                .await?;
+            self.filled.entry(placed.order_id).or_default();
+            if placed.resting_size > 0 {
+                self.open.push((placed.order_id, quote));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Cancels every open quote, e.g. on shutdown. On error the quotes that weren't cancelled
+    /// stay tracked, so a later call retries them.
+    pub async fn cancel_all(&mut self) -> Result<(), VenueError> {
+        while let Some(&(order_id, _)) = self.open.first() {
+            match self.venue.cancel_order(self.owner, &self.token, order_id).await {
+                // Filled in the meantime
+                Ok(()) | Err(VenueError::Rejected(_)) => {
+                    self.open.remove(0);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts fills of the bot's orders it hasn't seen yet. Returns whether there were any.
+    async fn sync_fills(&mut self) -> Result<bool, VenueError> {
+        let mut totals: HashMap<u64, (OrderSide, u64)> = HashMap::new();
+        for fill in self.venue.fills(self.owner).await? {
+            if fill.token == self.token && self.filled.contains_key(&fill.order_id) {
+                totals.entry(fill.order_id).or_insert((fill.side, 0)).1 += fill.size;
+            }
+        }
+
+        let mut any = false;
+        for (order_id, (side, total)) in totals {
+            let counted = self.filled.insert(order_id, total).unwrap_or_default();
+            let new = total.saturating_sub(counted) as i64;
+            if new > 0 {
+                any = true;
+                self.inventory += if side == OrderSide::Buy { new } else { -new };
+            }
+        }
+        Ok(any)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::venue::SimulatedVenue;
+    use ethers::types::U256;
+
+    #[test]
+    fn test_quotes_skew_with_inventory() {
+        let config = QuoteConfig { spread: 4, size: 10, max_inventory: 20, skew: 4 };
+        let flat = config.quotes(50, 0);
+        assert_eq!((flat[0].price, flat[1].price), (48, 52));
+
+        // Long 10 of 20 moves both quotes down 2 cents
+        let long = config.quotes(50, 10);
+        assert_eq!((long[0].price, long[0].size, long[1].price), (46, 10, 50));
+
+        // At the limit only the side that reduces the position is quoted
+        let full = config.quotes(50, 20);
+        assert_eq!(full, vec![Quote { side: OrderSide::Sell, price: 48, size: 10 }]);
+
+        assert_eq!(config.quotes(1, 0)[0].price, 1);
+        assert_eq!(FairValue::from_underlying(100.0, 100.0, 5.0), Some(50));
+        assert_eq!(FairValue::from_probability(1.2), None);
+    }
+
+    #[tokio::test]
+    async fn test_requotes_on_fill() {
+        let venue = SimulatedVenue::new();
+        let (yes_token, _) = venue.create_market_pair("MARKET", "USDC").await.unwrap();
+        let bot = Address::random();
+        let taker = Address::random();
+        venue.mint(bot, &yes_token, U256::from(100));
+        venue.mint(bot, "USDC", U256::from(1_000));
+        venue.mint(taker, "USDC", U256::from(1_000));
+
+        let config = QuoteConfig { spread: 4, size: 10, max_inventory: 20, skew: 4 };
+        let mut maker = MarketMaker::new("MARKET", &yes_token, bot, config, Arc::new(venue.clone()));
+        assert!(maker.step(50).await.unwrap());
+        assert!(!maker.step(50).await.unwrap());
+
+        // Lifting the ask leaves the bot short and both quotes move up
+        venue.place_order(OrderRequest {
+            owner: taker,
+            token: yes_token.clone(),
+            side: OrderSide::Buy,
+            price: 52,
+            size: 10,
+        }).await.unwrap();
+        assert!(maker.step(50).await.unwrap());
+        assert_eq!(maker.inventory(), -10);
+        let quotes = maker.open_quotes();
+        assert_eq!((quotes[0].price, quotes[1].price), (50, 54));
+
+        // Fair value moving requotes without a fill
+        assert!(maker.step(60).await.unwrap());
+        maker.cancel_all().await.unwrap();
+        assert!(maker.open_quotes().is_empty());
+    }
+}
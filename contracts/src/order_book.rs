@@ -0,0 +1,512 @@
+use crate::order_types::SelfTradePrevention;
+use crate::venue::OrderSide;
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+
+/// A resting limit order. Prices are in cents of collateral per outcome token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookOrder {
+    pub order_id: u64,
+    pub owner: Address,
+    pub side: OrderSide,
+    pub price: u64,
+    pub remaining: u64,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub expires_at: Option<u64>, // Good-till-time orders leave the book at this time
+    #[serde(default)]
+    pub post_only: bool, // Kept so an amended order is still rejected if it would take liquidity
+    #[serde(default)]
+    pub self_trade_prevention: SelfTradePrevention, // Applies again if an amendment makes it match
+    #[serde(default)]
+    pub taker_fee_bps: u64, // Fee rate at placement, which a buy's escrow was sized with
+}
+
+/// How a fill settles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FillKind {
+    /// Both orders are on the same outcome; tokens move from seller to buyer.
+    #[default]
+    Transfer,
+    /// A buy crossed a buy of the other outcome; a complete set is minted from both
+    /// buyers' collateral.
+    Mint,
+    /// A sell crossed a sell of the other outcome; a complete set is burned and the
+    /// collateral paid out to both sellers.
+    Burn,
+}
+
+/// A match between an incoming (taker) order and a resting (maker) order.
+/// Fills always execute at the maker's price, which for `Mint` and `Burn` fills is a price
+/// of the other outcome: the taker's price is then `100 - price`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fill {
+    pub maker_order_id: u64,
+    pub taker_order_id: u64,
+    pub maker: Address,
+    pub taker: Address,
+    pub taker_side: OrderSide,
+    pub price: u64,
+    pub size: u64,
+    #[serde(default)]
+    pub kind: FillKind,
+    #[serde(default)]
+    pub taker_fee_bps: u64, // The taker order's fee rate at placement
+}
+
+impl Fill {
+    /// The price in terms of the taker's outcome.
+    pub fn taker_price(&self) -> u64 {
+        match self.kind {
+            FillKind::Transfer => self.price,
+            FillKind::Mint | FillKind::Burn => 100 - self.price,
+        }
+    }
+}
+
+/// Price-time priority book for a single outcome token.
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    bids: Vec<BookOrder>, // Best (highest) price first, then oldest first
+    asks: Vec<BookOrder>, // Best (lowest) price first, then oldest first
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches `order` against the opposite side and rests whatever is left.
+    /// Returns the fills in execution order. Test only: orders go through
+    /// `UnifiedBook::match_order`, which applies self-trade prevention.
+    #[cfg(test)]
+    fn place(&mut self, mut order: BookOrder) -> Vec<Fill> {
+        let fills = self.match_order(&mut order);
+        if order.remaining > 0 {
+            self.rest(order);
+        }
+        fills
+    }
+
+    /// Matches `order` against the opposite side without resting the remainder, which is
+    /// left in `order.remaining`. Test only, like `place`.
+    #[cfg(test)]
+    fn match_order(&mut self, order: &mut BookOrder) -> Vec<Fill> {
+        let mut fills = Vec::new();
+        let resting = match order.side {
+            OrderSide::Buy => &mut self.asks,
+            OrderSide::Sell => &mut self.bids,
+        };
+
+        while order.remaining > 0 {
+            let Some(maker) = resting.first_mut() else { break };
+            if !Self::crosses_price(order.side, order.price, maker.price) {
+                break;
+            }
+
+            let size = order.remaining.min(maker.remaining);
+            fills.push(Fill {
+                maker_order_id: maker.order_id,
+                taker_order_id: order.order_id,
+                maker: maker.owner,
+                taker: order.owner,
+                taker_side: order.side,
+                price: maker.price,
+                size,
+                kind: FillKind::Transfer,
+                taker_fee_bps: order.taker_fee_bps,
+            });
+            order.remaining -= size;
+            maker.remaining -= size;
+            if maker.remaining == 0 {
+                resting.remove(0);
+            }
+        }
+        fills
+    }
+
+    /// Whether an order on `side` at `price` would take liquidity.
+    pub fn crosses(&self, side: OrderSide, price: u64) -> bool {
+        let best = match side {
+            OrderSide::Buy => self.best_ask(),
+            OrderSide::Sell => self.best_bid(),
+        };
+        best.is_some_and(|best| Self::crosses_price(side, price, best))
+    }
+
+    /// Size an order on `side` at `price` could fill right now.
+    pub fn fillable(&self, side: OrderSide, price: u64) -> u64 {
+        let resting = match side {
+            OrderSide::Buy => &self.asks,
+            OrderSide::Sell => &self.bids,
+        };
+        resting
+            .iter()
+            .take_while(|maker| Self::crosses_price(side, price, maker.price))
+            .map(|maker| maker.remaining)
+            .sum()
+    }
+
+    /// Removes good-till-time orders whose expiry has passed and returns them.
+    pub fn remove_expired(&mut self, now: u64) -> Vec<BookOrder> {
+        let mut expired = Vec::new();
+        for side in [&mut self.bids, &mut self.asks] {
+            side.retain(|order| match order.expires_at {
+                Some(expires_at) if expires_at <= now => {
+                    expired.push(order.clone());
+                    false
+                }
+                _ => true,
+            });
+        }
+        expired
+    }
+
+    pub fn cancel(&mut self, order_id: u64) -> Option<BookOrder> {
+        for side in [&mut self.bids, &mut self.asks] {
+            if let Some(index) = side.iter().position(|order| order.order_id == order_id) {
+                return Some(side.remove(index));
+            }
+        }
+        None
+    }
+
+    /// Removes an order and returns it with its place in the queue, so `reinsert` can put it
+    /// back without losing priority.
+    pub fn remove(&mut self, order_id: u64) -> Option<(usize, BookOrder)> {
+        for side in [&mut self.bids, &mut self.asks] {
+            if let Some(index) = side.iter().position(|order| order.order_id == order_id) {
+                return Some((index, side.remove(index)));
+            }
+        }
+        None
+    }
+
+    /// Puts an order taken out with `remove` back where it was.
+    pub fn reinsert(&mut self, index: usize, order: BookOrder) {
+        let side = match order.side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        side.insert(index.min(side.len()), order);
+    }
+
+    /// Takes `size` off a resting order, removing it once nothing remains.
+    pub fn take(&mut self, order_id: u64, size: u64) {
+        for side in [&mut self.bids, &mut self.asks] {
+            if let Some(index) = side.iter().position(|order| order.order_id == order_id) {
+                side[index].remaining = side[index].remaining.saturating_sub(size);
+                if side[index].remaining == 0 {
+                    side.remove(index);
+                }
+                return;
+            }
+        }
+    }
+
+    pub fn get(&self, order_id: u64) -> Option<&BookOrder> {
+        self.bids
+            .iter()
+            .chain(self.asks.iter())
+            .find(|order| order.order_id == order_id)
+    }
+
+    pub fn best_bid(&self) -> Option<u64> {
+        self.bids.first().map(|order| order.price)
+    }
+
+    pub fn best_ask(&self) -> Option<u64> {
+        self.asks.first().map(|order| order.price)
+    }
+
+    pub fn bids(&self) -> &[BookOrder] {
+        &self.bids
+    }
+
+    pub fn asks(&self) -> &[BookOrder] {
+        &self.asks
+    }
+
+    /// Inserts behind every order at the same or a better price.
+    pub fn rest(&mut self, order: BookOrder) {
+        let side = match order.side {
+            OrderSide::Buy => &mut self.bids,
+            OrderSide::Sell => &mut self.asks,
+        };
+        let index = side
+            .iter()
+            .position(|resting| match order.side {
+                OrderSide::Buy => resting.price < order.price,
+                OrderSide::Sell => resting.price > order.price,
+            })
+            .unwrap_or(side.len());
+        side.insert(index, order);
+    }
+
+    fn crosses_price(side: OrderSide, price: u64, resting_price: u64) -> bool {
+        match side {
+            OrderSide::Buy => resting_price <= price,
+            OrderSide::Sell => resting_price >= price,
+        }
+    }
+}
+
+/// Size taken off a resting order by self-trade prevention, with `order.remaining` set to
+/// the size removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Released {
+    pub order: BookOrder,
+    pub complement: bool, // The order rests on the other outcome's book
+}
+
+/// What matching an incoming order did to the books.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchResult {
+    pub fills: Vec<Fill>,
+    pub released: Vec<Released>,
+}
+
+/// The YES and NO books of a binary market seen as one book, so liquidity isn't split
+/// between the two outcomes.
+///
+/// A bid for NO at `q` is an ask for YES at `100 - q`, and an ask for NO at `q` is a bid for
+/// YES at `100 - q`. An incoming order therefore matches the opposite side of its own book
+/// and the same side of the complementary book, best effective price first and oldest
+/// first at equal prices.
+pub struct UnifiedBook<'a> {
+    pub book: &'a mut OrderBook,
+    pub complement: &'a mut OrderBook,
+}
+
+impl UnifiedBook<'_> {
+    /// Matches `order` against both books without resting the remainder, which is left in
+    /// `order.remaining`. Size that self-trade prevention cancels from the incoming order is
+    /// neither filled nor left remaining.
+    pub fn match_order(&mut self, order: &mut BookOrder, self_trade: SelfTradePrevention) -> MatchResult {
+        let mut result = MatchResult::default();
+        while order.remaining > 0 {
+            let Some((effective_price, kind)) = self.best(order.side) else { break };
+            if !OrderBook::crosses_price(order.side, order.price, effective_price) {
+                break;
+            }
+
+            let complement = kind != FillKind::Transfer;
+            let resting = match (complement, order.side) {
+                (false, OrderSide::Buy) => &mut self.book.asks,
+                (false, OrderSide::Sell) => &mut self.book.bids,
+                (true, OrderSide::Buy) => &mut self.complement.bids,
+                (true, OrderSide::Sell) => &mut self.complement.asks,
+            };
+            let maker = &mut resting[0];
+            let size = order.remaining.min(maker.remaining);
+
+            if maker.owner == order.owner {
+                let (cancel_resting, cancel_incoming) = match self_trade {
+                    SelfTradePrevention::CancelNewest => (false, true),
+                    SelfTradePrevention::CancelOldest => (true, false),
+                    SelfTradePrevention::CancelBoth => (true, true),
+                    SelfTradePrevention::Decrement => (false, false),
+                };
+                if cancel_resting {
+                    result.released.push(Released { order: resting.remove(0), complement });
+                } else if !cancel_incoming {
+                    maker.remaining -= size;
+                    order.remaining -= size;
+                    result.released.push(Released {
+                        order: BookOrder { remaining: size, ..maker.clone() },
+                        complement,
+                    });
+                    if maker.remaining == 0 {
+                        resting.remove(0);
+                    }
+                }
+                if cancel_incoming {
+                    order.remaining = 0;
+                }
+                continue;
+            }
+
+            result.fills.push(Fill {
+                maker_order_id: maker.order_id,
+                taker_order_id: order.order_id,
+                maker: maker.owner,
+                taker: order.owner,
+                taker_side: order.side,
+                price: maker.price,
+                size,
+                kind,
+                taker_fee_bps: order.taker_fee_bps,
+            });
+            order.remaining -= size;
+            maker.remaining -= size;
+            if maker.remaining == 0 {
+                resting.remove(0);
+            }
+        }
+        result
+    }
+
+    /// Best price, in the book's outcome, an order on `side` could trade at right now.
+    pub fn best_price(&self, side: OrderSide) -> Option<u64> {
+        self.best(side).map(|(price, _)| price)
+    }
+
+    /// Whether an order on `side` at `price` would take liquidity from either book.
+    pub fn crosses(&self, side: OrderSide, price: u64) -> bool {
+        self.best_price(side)
+            .is_some_and(|best| OrderBook::crosses_price(side, price, best))
+    }
+
+    /// Size an order on `side` at `price` could fill right now across both books.
+    pub fn fillable(&self, side: OrderSide, price: u64) -> u64 {
+        // A buy at `price` takes complementary bids at `100 - price` or better, just as a sell
+        // at that price would, and vice versa
+        let complement_side = match side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+        self.book.fillable(side, price) + self.complement.fillable(complement_side, 100 - price)
+    }
+
+    /// The better of the two books' best offers for an order on `side`, as
+    /// (price in the book's outcome, how it would settle).
+    fn best(&self, side: OrderSide) -> Option<(u64, FillKind)> {
+        let (direct, complement, kind) = match side {
+            OrderSide::Buy => (self.book.asks.first(), self.complement.bids.first(), FillKind::Mint),
+            OrderSide::Sell => (self.book.bids.first(), self.complement.asks.first(), FillKind::Burn),
+        };
+        let direct = direct.map(|maker| (maker.price, maker.timestamp, FillKind::Transfer));
+        let complement = complement.map(|maker| (100 - maker.price, maker.timestamp, kind));
+        let (price, _, kind) = match (direct, complement) {
+            (Some(direct), Some(complement)) => {
+                let complement_better = match side {
+                    OrderSide::Buy => (complement.0, complement.1) < (direct.0, direct.1),
+                    OrderSide::Sell => complement.0 > direct.0 || (complement.0 == direct.0 && complement.1 < direct.1),
+                };
+                if complement_better { complement } else { direct }
+            }
+            (Some(only), None) | (None, Some(only)) => only,
+            (None, None) => return None,
+        };
+        Some((price, kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(order_id: u64, owner: Address, side: OrderSide, price: u64, size: u64) -> BookOrder {
+        BookOrder {
+            order_id,
+            owner,
+            side,
+            price,
+            remaining: size,
+            timestamp: order_id,
+            expires_at: None,
+            post_only: false,
+            self_trade_prevention: SelfTradePrevention::default(),
+            taker_fee_bps: 0,
+        }
+    }
+
+    #[test]
+    fn test_price_time_priority() {
+        let mut book = OrderBook::new();
+        let (a, b, c) = (Address::random(), Address::random(), Address::random());
+
+        assert!(book.place(order(1, a, OrderSide::Sell, 60, 10)).is_empty());
+        assert!(book.place(order(2, b, OrderSide::Sell, 55, 10)).is_empty());
+        assert!(book.place(order(3, a, OrderSide::Sell, 55, 10)).is_empty());
+        assert_eq!(book.best_ask(), Some(55));
+
+        let fills = book.place(order(4, c, OrderSide::Buy, 60, 25));
+        let matched: Vec<_> = fills.iter().map(|fill| (fill.maker_order_id, fill.price, fill.size)).collect();
+        assert_eq!(matched, vec![(2, 55, 10), (3, 55, 10), (1, 60, 5)]);
+        assert_eq!(book.get(1).unwrap().remaining, 5);
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_unmatched_remainder_rests_and_cancels() {
+        let mut book = OrderBook::new();
+        let owner = Address::random();
+
+        book.place(order(1, owner, OrderSide::Sell, 70, 5));
+        let fills = book.place(order(2, owner, OrderSide::Buy, 65, 5));
+        assert!(fills.is_empty());
+        assert_eq!(book.best_bid(), Some(65));
+
+        let cancelled = book.cancel(2).unwrap();
+        assert_eq!(cancelled.remaining, 5);
+        assert_eq!(book.best_bid(), None);
+        assert!(book.cancel(2).is_none());
+    }
+
+    #[test]
+    fn test_depth_and_expiry() {
+        let mut book = OrderBook::new();
+        let owner = Address::random();
+
+        book.place(order(1, owner, OrderSide::Sell, 55, 10));
+        book.place(BookOrder {
+            expires_at: Some(100),
+            ..order(2, owner, OrderSide::Sell, 60, 10)
+        });
+        assert!(book.crosses(OrderSide::Buy, 55));
+        assert!(!book.crosses(OrderSide::Buy, 54));
+        assert_eq!(book.fillable(OrderSide::Buy, 60), 20);
+
+        let expired = book.remove_expired(100);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(book.fillable(OrderSide::Buy, 60), 10);
+
+        let mut taker = order(3, owner, OrderSide::Buy, 60, 15);
+        let fills = book.match_order(&mut taker);
+        assert_eq!((fills.len(), taker.remaining), (1, 5));
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_unified_book_matches_complementary_orders() {
+        let (mut yes_book, mut no_book) = (OrderBook::new(), OrderBook::new());
+        let (a, b, c) = (Address::random(), Address::random(), Address::random());
+
+        yes_book.place(order(1, a, OrderSide::Sell, 62, 10));
+        no_book.place(order(2, b, OrderSide::Buy, 40, 10)); // A YES ask at 60
+        let mut unified = UnifiedBook { book: &mut yes_book, complement: &mut no_book };
+        assert_eq!(unified.best_price(OrderSide::Buy), Some(60));
+        assert_eq!(unified.fillable(OrderSide::Buy, 62), 20);
+
+        let mut taker = order(3, c, OrderSide::Buy, 62, 15);
+        let fills = unified.match_order(&mut taker, SelfTradePrevention::CancelNewest).fills;
+        let matched: Vec<_> = fills.iter().map(|fill| (fill.maker_order_id, fill.kind, fill.taker_price())).collect();
+        assert_eq!(matched, vec![(2, FillKind::Mint, 60), (1, FillKind::Transfer, 62)]);
+        assert_eq!(taker.remaining, 0);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_modes() {
+        let (owner, other) = (Address::random(), Address::random());
+        let setup = || {
+            let mut book = OrderBook::new();
+            book.place(order(1, owner, OrderSide::Sell, 50, 10));
+            book.place(order(2, other, OrderSide::Sell, 55, 10));
+            book
+        };
+        let run = |mode| {
+            let (mut book, mut complement) = (setup(), OrderBook::new());
+            let mut taker = order(3, owner, OrderSide::Buy, 60, 15);
+            let result = UnifiedBook { book: &mut book, complement: &mut complement }.match_order(&mut taker, mode);
+            let filled: u64 = result.fills.iter().map(|fill| fill.size).sum();
+            let released: Vec<_> = result.released.iter().map(|released| released.order.remaining).collect();
+            (filled, taker.remaining, released, book.get(1).map(|order| order.remaining))
+        };
+
+        assert_eq!(run(SelfTradePrevention::CancelNewest), (0, 0, vec![], Some(10)));
+        assert_eq!(run(SelfTradePrevention::CancelOldest), (10, 5, vec![10], None));
+        assert_eq!(run(SelfTradePrevention::CancelBoth), (0, 0, vec![10], None));
+        assert_eq!(run(SelfTradePrevention::Decrement), (5, 0, vec![10], None));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::venue::OrderSide;
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+
+/// How an order is priced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderType {
+    /// Trades at `price` cents or better.
+    Limit { price: u64 },
+    /// Trades immediately against the book, at most `max_slippage` cents worse than the best
+    /// opposite price when the order executes. Never rests.
+    Market { max_slippage: u64 },
+}
+
+/// How long an order stays on the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TimeInForce {
+    /// Good till cancelled.
+    #[default]
+    Gtc,
+    /// Immediate or cancel: fills what it can and cancels the rest.
+    Ioc,
+    /// Fill or kill: fills completely on arrival or not at all.
+    Fok,
+    /// Good till time: rests until `expires_at`, then is cancelled.
+    Gtt { expires_at: u64 },
+}
+
+/// What happens when an order would match a resting order of the same owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SelfTradePrevention {
+    /// Cancel the rest of the incoming order.
+    #[default]
+    CancelNewest,
+    /// Cancel the resting order and keep matching.
+    CancelOldest,
+    /// Cancel both the resting order and the rest of the incoming order.
+    CancelBoth,
+    /// Shrink both orders by the size they overlap without trading, and keep matching.
+    Decrement,
+}
+
+/// An order as submitted to a market.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewOrder {
+    pub is_yes: bool,
+    pub side: OrderSide,
+    pub amount: u64,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    pub post_only: bool, // Rejected if it would take liquidity
+    pub reduce_only: bool, // May only sell tokens already held; the size is clipped to the holding
+    pub stop_price: Option<u64>, // Held back until the last trade price reaches it
+    #[serde(default)]
+    pub self_trade_prevention: SelfTradePrevention,
+}
+
+impl NewOrder {
+    /// A good-till-cancelled limit order.
+    pub fn limit(is_yes: bool, side: OrderSide, price: u64, amount: u64) -> Self {
+        Self {
+            is_yes,
+            side,
+            amount,
+            order_type: OrderType::Limit { price },
+            time_in_force: TimeInForce::Gtc,
+            post_only: false,
+            reduce_only: false,
+            stop_price: None,
+            self_trade_prevention: SelfTradePrevention::default(),
+        }
+    }
+
+    /// An order that trades immediately within `max_slippage` cents of the best price.
+    pub fn market(is_yes: bool, side: OrderSide, amount: u64, max_slippage: u64) -> Self {
+        Self {
+            order_type: OrderType::Market { max_slippage },
+            time_in_force: TimeInForce::Ioc,
+            ..Self::limit(is_yes, side, 0, amount)
+        }
+    }
+
+    /// Checks the combination of options, independent of the book.
+    pub fn is_valid(&self, now: u64) -> bool {
+        let price_ok = match self.order_type {
+            OrderType::Limit { price } => price > 0 && price < 100,
+            // Market orders never rest
+            OrderType::Market { max_slippage } => {
+                max_slippage < 100 && matches!(self.time_in_force, TimeInForce::Ioc | TimeInForce::Fok)
+            }
+        };
+        let tif_ok = match self.time_in_force {
+            TimeInForce::Gtt { expires_at } => expires_at > now,
+            TimeInForce::Ioc | TimeInForce::Fok => !self.post_only,
+            TimeInForce::Gtc => true,
+        };
+        let stop_ok = self.stop_price.is_none_or(|stop| stop > 0 && stop < 100);
+        let reduce_ok = !self.reduce_only || self.side == OrderSide::Sell;
+        self.amount > 0 && price_ok && tif_ok && stop_ok && reduce_ok
+    }
+
+    /// Whether a stop order triggers at `last_price`: buy stops on a rise to the stop price,
+    /// sell stops on a fall to it.
+    pub fn triggers_at(&self, last_price: u64) -> bool {
+        match (self.stop_price, self.side) {
+            (None, _) => true,
+            (Some(stop), OrderSide::Buy) => last_price >= stop,
+            (Some(stop), OrderSide::Sell) => last_price <= stop,
+        }
+    }
+}
+
+/// A stop or stop-limit order waiting for its trigger. Funds are only reserved once it triggers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StopOrder {
+    pub order_id: u64,
+    pub owner: Address,
+    pub order: NewOrder,
+    pub placed_at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_combinations() {
+        let now = 1_000;
+        assert!(NewOrder::limit(true, OrderSide::Buy, 50, 10).is_valid(now));
+        assert!(NewOrder::market(true, OrderSide::Buy, 10, 5).is_valid(now));
+        assert!(!NewOrder::limit(true, OrderSide::Buy, 100, 10).is_valid(now));
+
+        let post_only_ioc = NewOrder {
+            post_only: true,
+            time_in_force: TimeInForce::Ioc,
+            ..NewOrder::limit(true, OrderSide::Buy, 50, 10)
+        };
+        assert!(!post_only_ioc.is_valid(now));
+
+        let expired = NewOrder {
+            time_in_force: TimeInForce::Gtt { expires_at: now },
+            ..NewOrder::limit(true, OrderSide::Buy, 50, 10)
+        };
+        assert!(!expired.is_valid(now));
+
+        let reduce_only_buy = NewOrder {
+            reduce_only: true,
+            ..NewOrder::limit(true, OrderSide::Buy, 50, 10)
+        };
+        assert!(!reduce_only_buy.is_valid(now));
+
+        let stop = NewOrder {
+            stop_price: Some(40),
+            ..NewOrder::market(true, OrderSide::Sell, 10, 5)
+        };
+        assert!(!stop.triggers_at(45));
+        assert!(stop.triggers_at(40));
+    }
+}
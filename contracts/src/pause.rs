@@ -0,0 +1,80 @@
+use ethers::types::Address;
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+/// Pause switches shared between the factory and its markets.
+///
+/// Holds the pauser role, a factory-wide switch and one switch per market. It only
+/// records state; callers check the role before flipping a switch and emit the events.
+#[derive(Debug, Default)]
+pub struct PauseController {
+    pausers: RwLock<HashSet<Address>>,
+    factory_paused: RwLock<bool>,
+    paused_markets: RwLock<HashSet<String>>,
+}
+
+impl PauseController {
+    pub fn new(pauser: Address) -> Self {
+        Self {
+            pausers: RwLock::new(HashSet::from([pauser])),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_pauser(&self, address: Address) -> bool {
+        self.pausers.read().unwrap().contains(&address)
+    }
+
+    pub fn grant_pauser(&self, address: Address) {
+        self.pausers.write().unwrap().insert(address);
+    }
+
+    pub fn revoke_pauser(&self, address: Address) {
+        self.pausers.write().unwrap().remove(&address);
+    }
+
+    pub fn is_factory_paused(&self) -> bool {
+        *self.factory_paused.read().unwrap()
+    }
+
+    pub fn set_factory_paused(&self, paused: bool) {
+        *self.factory_paused.write().unwrap() = paused;
+    }
+
+    pub fn set_market_paused(&self, market_id: &str, paused: bool) {
+        let mut paused_markets = self.paused_markets.write().unwrap();
+        if paused {
+            paused_markets.insert(market_id.to_string());
+        } else {
+            paused_markets.remove(market_id);
+        }
+    }
+
+    /// Whether `market_id` is paused on its own or through the factory.
+    pub fn is_paused(&self, market_id: &str) -> bool {
+        self.is_factory_paused() || self.paused_markets.read().unwrap().contains(market_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factory_pause_covers_every_market() {
+        let pauser = Address::random();
+        let controller = PauseController::new(pauser);
+        assert!(controller.is_pauser(pauser));
+        assert!(!controller.is_pauser(Address::random()));
+
+        controller.set_market_paused("0xa", true);
+        assert!(controller.is_paused("0xa"));
+        assert!(!controller.is_paused("0xb"));
+
+        controller.set_factory_paused(true);
+        assert!(controller.is_paused("0xb"));
+        controller.set_factory_paused(false);
+        controller.set_market_paused("0xa", false);
+        assert!(!controller.is_paused("0xa"));
+    }
+}
@@ -0,0 +1,161 @@
+use crate::fees::BPS_DENOMINATOR;
+use ethers::types::{Address, U256};
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use thiserror::Error;
+
+pub const DEFAULT_REFERRAL_SHARE_BPS: u64 = 2_000;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ReferralError {
+    #[error("Users can't refer themselves")]
+    SelfReferral,
+    #[error("User already has a referrer")]
+    AlreadyReferred,
+    #[error("Referrers must be registered before the first trade")]
+    AlreadyTraded,
+    #[error("Invalid referral share")]
+    InvalidShare,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Referral {
+    pub referrer: Address,
+    pub linked_at: u64, // Time of the referred user's first trade
+}
+
+/// Referral links shared between the factory and its markets.
+///
+/// A user registers a referrer before trading; the link takes effect at their first trade
+/// in any market. From then on the referrer is credited `share_bps` of the taker fees the
+/// user pays, out of the treasury's part. Like `PauseController`, it only records state;
+/// markets move the fees and emit the events.
+#[derive(Debug)]
+pub struct ReferralRegistry {
+    share_bps: RwLock<u64>,
+    pending: RwLock<HashMap<Address, Address>>, // Registered, waiting for the first trade
+    links: RwLock<HashMap<Address, Referral>>,
+    traded: RwLock<HashSet<Address>>,
+    earnings: RwLock<HashMap<Address, U256>>, // Credited per referrer, across all markets
+}
+
+impl Default for ReferralRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_REFERRAL_SHARE_BPS)
+    }
+}
+
+impl ReferralRegistry {
+    pub fn new(share_bps: u64) -> Self {
+        Self {
+            share_bps: RwLock::new(share_bps.min(BPS_DENOMINATOR)),
+            pending: RwLock::default(),
+            links: RwLock::default(),
+            traded: RwLock::default(),
+            earnings: RwLock::default(),
+        }
+    }
+
+    pub fn share_bps(&self) -> u64 {
+        *self.share_bps.read().unwrap()
+    }
+
+    pub fn set_share_bps(&self, share_bps: u64) -> Result<(), ReferralError> {
+        if share_bps > BPS_DENOMINATOR {
+            return Err(ReferralError::InvalidShare);
+        }
+        *self.share_bps.write().unwrap() = share_bps;
+        Ok(())
+    }
+
+    /// Records `referrer` for `user` until their first trade. Rejects referring yourself,
+    /// directly or through the referrer being referred by `user`.
+    pub fn register(&self, user: Address, referrer: Address) -> Result<(), ReferralError> {
+        if user == referrer || self.referrer_of(referrer) == Some(user) {
+            return Err(ReferralError::SelfReferral);
+        }
+        if self.links.read().unwrap().contains_key(&user) || self.pending.read().unwrap().contains_key(&user) {
+            return Err(ReferralError::AlreadyReferred);
+        }
+        if self.traded.read().unwrap().contains(&user) {
+            return Err(ReferralError::AlreadyTraded);
+        }
+        self.pending.write().unwrap().insert(user, referrer);
+        Ok(())
+    }
+
+    /// Notes a trade by `user`. Returns the referrer if this first trade linked them.
+    pub fn record_trade(&self, user: Address, timestamp: u64) -> Option<Address> {
+        if !self.traded.write().unwrap().insert(user) {
+            return None;
+        }
+        let referrer = self.pending.write().unwrap().remove(&user)?;
+        self.links.write().unwrap().insert(user, Referral { referrer, linked_at: timestamp });
+        Some(referrer)
+    }
+
+    /// The referrer of a linked or pending referral.
+    fn referrer_of(&self, user: Address) -> Option<Address> {
+        self.referral(user)
+            .map(|referral| referral.referrer)
+            .or_else(|| self.pending.read().unwrap().get(&user).copied())
+    }
+
+    /// The user's referral, once linked by their first trade.
+    pub fn referral(&self, user: Address) -> Option<Referral> {
+        self.links.read().unwrap().get(&user).copied()
+    }
+
+    /// The referrer's share of a taker fee paid by a linked user, and who gets it.
+    pub fn share_of(&self, taker: Address, taker_fee: U256) -> Option<(Address, U256)> {
+        let referral = self.referral(taker)?;
+        let share = taker_fee * U256::from(self.share_bps()) / U256::from(BPS_DENOMINATOR);
+        Some((referral.referrer, share))
+    }
+
+    pub fn credit(&self, referrer: Address, amount: U256) {
+        *self.earnings.write().unwrap().entry(referrer).or_default() += amount;
+    }
+
+    /// Users linked to `referrer`.
+    pub fn referred_by(&self, referrer: Address) -> Vec<(Address, Referral)> {
+        self.links
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, referral)| referral.referrer == referrer)
+            .map(|(&user, &referral)| (user, referral))
+            .collect()
+    }
+
+    /// Fees credited to `referrer` so far across all markets.
+    pub fn earnings(&self, referrer: Address) -> U256 {
+        self.earnings.read().unwrap().get(&referrer).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_at_first_trade() {
+        let registry = ReferralRegistry::new(1_000);
+        let (referrer, user, late) = (Address::random(), Address::random(), Address::random());
+        assert_eq!(registry.register(user, user), Err(ReferralError::SelfReferral));
+        registry.register(user, referrer).unwrap();
+        assert_eq!(registry.register(user, Address::random()), Err(ReferralError::AlreadyReferred));
+        // No referring each other
+        assert_eq!(registry.register(referrer, user), Err(ReferralError::SelfReferral));
+
+        assert!(registry.share_of(user, U256::from(100)).is_none());
+        assert_eq!(registry.record_trade(user, 7), Some(referrer));
+        assert_eq!(registry.record_trade(user, 8), None);
+        assert_eq!(registry.referral(user), Some(Referral { referrer, linked_at: 7 }));
+        assert_eq!(registry.share_of(user, U256::from(100)), Some((referrer, U256::from(10))));
+
+        registry.record_trade(late, 9);
+        assert_eq!(registry.register(late, referrer), Err(ReferralError::AlreadyTraded));
+        assert_eq!(registry.referred_by(referrer).len(), 1);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::batch_auction::BatchAuction;
+use crate::market::{MarketMetadata, ParentCondition};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use thiserror::Error;
+
+/// Values substituted for `{name}` placeholders in a template.
+pub type TemplateParams = BTreeMap<String, String>;
+
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("Missing template parameter: {0}")]
+    MissingParameter(String),
+    #[error("Invalid value for parameter {name}: {value}")]
+    InvalidParameter { name: String, value: String },
+    #[error("Invalid expiry rule: {0}")]
+    InvalidExpiry(String),
+    #[error("Unclosed placeholder in: {0}")]
+    UnclosedPlaceholder(String),
+    #[error("Unsupported parameter file: {0}")]
+    UnsupportedFile(String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+}
+
+/// How an instance's expiry timestamp is derived.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExpiryRule {
+    /// The same Unix timestamp for every instance.
+    Fixed { timestamp: u64 },
+    /// A Unix timestamp taken from the named parameter.
+    Parameter { name: String },
+    /// A fixed delay after the markets are created.
+    AfterCreation { seconds: u64 },
+    /// The next `weekday` (0 = Monday) at `hour_utc`, `weeks_ahead` weeks out.
+    NextWeekday { weekday: u64, hour_utc: u64, weeks_ahead: u64 },
+}
+
+/// Everything needed to create one market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketSpec {
+    pub question: String,
+    pub expiry_timestamp: u64,
+    pub oracle_id: Address,
+    pub collateral_token: String,
+    pub metadata: MarketMetadata,
+    pub salt: u64,
+    #[serde(default)]
+    pub parent_condition: Option<ParentCondition>,
+    #[serde(default)]
+    pub trading_close_timestamp: Option<u64>,
+    #[serde(default)]
+    pub resolution_timestamp: Option<u64>,
+    #[serde(default)]
+    pub batch_auction: Option<BatchAuction>, // Opening auction; continuous matching starts once it ends
+}
+
+/// A parameterized market definition, e.g. a weekly price threshold question
+/// instantiated across assets and thresholds.
+///
+/// The question and the metadata text fields may contain `{name}` placeholders. Besides
+/// the instance parameters, `{expiry}` (Unix timestamp) and `{expiry_date}` (YYYY-MM-DD,
+/// UTC) are always available.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketTemplate {
+    pub question: String,
+    pub expiry: ExpiryRule,
+    pub oracle_id: Address,
+    #[serde(default = "default_collateral_token")]
+    pub collateral_token: String,
+    #[serde(default)]
+    pub metadata: MarketMetadata,
+    #[serde(default)]
+    pub salt: u64,
+    /// Makes every instance conditional on the same parent market.
+    #[serde(default)]
+    pub parent_condition: Option<ParentCondition>,
+    /// Parameter grid; every combination of values becomes one instance.
+    #[serde(default)]
+    pub grid: BTreeMap<String, Vec<String>>,
+}
+
+fn default_collateral_token() -> String {
+    "USDC".to_string()
+}
+
+impl MarketTemplate {
+    pub fn load(path: &Path) -> Result<Self, TemplateError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// The cartesian product of the grid, in key order.
+    pub fn grid_params(&self) -> Vec<TemplateParams> {
+        let mut combinations = vec![TemplateParams::new()];
+        for (name, values) in &self.grid {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|params| {
+                    values.iter().map(move |value| {
+                        let mut params = params.clone();
+                        params.insert(name.clone(), value.clone());
+                        params
+                    })
+                })
+                .collect();
+        }
+        combinations
+    }
+
+    pub fn instantiate(&self, params: &TemplateParams, now: u64) -> Result<MarketSpec, TemplateError> {
+        let expiry_timestamp = self.expiry_timestamp(params, now)?;
+
+        let mut params = params.clone();
+        params.insert("expiry".to_string(), expiry_timestamp.to_string());
+        params.insert("expiry_date".to_string(), format_date(expiry_timestamp));
+
+        let metadata = MarketMetadata {
+            description: render(&self.metadata.description, &params)?,
+            rules: render(&self.metadata.rules, &params)?,
+            resolution_source: render(&self.metadata.resolution_source, &params)?,
+            resolution_source_urls: self.metadata.resolution_source_urls
+                .iter()
+                .map(|url| render(url, &params))
+                .collect::<Result<_, _>>()?,
+            category: render(&self.metadata.category, &params)?,
+            tags: self.metadata.tags
+                .iter()
+                .map(|tag| render(tag, &params))
+                .collect::<Result<_, _>>()?,
+            image_url: self.metadata.image_url
+                .as_deref()
+                .map(|url| render(url, &params))
+                .transpose()?,
+        };
+
+        Ok(MarketSpec {
+            question: render(&self.question, &params)?,
+            expiry_timestamp,
+            oracle_id: self.oracle_id,
+            collateral_token: self.collateral_token.clone(),
+            metadata,
+            salt: self.salt,
+            parent_condition: self.parent_condition.clone(),
+            trading_close_timestamp: None,
+            resolution_timestamp: None,
+            batch_auction: None,
+        })
+    }
+
+    fn expiry_timestamp(&self, params: &TemplateParams, now: u64) -> Result<u64, TemplateError> {
+        match &self.expiry {
+            ExpiryRule::Fixed { timestamp } => Ok(*timestamp),
+            ExpiryRule::Parameter { name } => {
+                let value = params
+                    .get(name)
+                    .ok_or_else(|| TemplateError::MissingParameter(name.clone()))?;
+                value.trim().parse().map_err(|_| TemplateError::InvalidParameter {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+            }
+            ExpiryRule::AfterCreation { seconds } => now.checked_add(*seconds).ok_or_else(expiry_overflow),
+            ExpiryRule::NextWeekday { weekday, hour_utc, weeks_ahead } => {
+                if *hour_utc > 23 {
+                    return Err(TemplateError::InvalidExpiry(format!("hour_utc {} is not 0-23", hour_utc)));
+                }
+                let today = now / 86_400;
+                // 1970-01-01 was a Thursday (weekday 3)
+                let days_until = (weekday % 7 + 7 - (today + 3) % 7) % 7;
+                let mut expiry = (today + days_until)
+                    .checked_mul(86_400)
+                    .and_then(|day| day.checked_add(hour_utc * 3_600))
+                    .ok_or_else(expiry_overflow)?;
+                if expiry <= now {
+                    expiry = expiry.checked_add(7 * 86_400).ok_or_else(expiry_overflow)?;
+                }
+                weeks_ahead
+                    .checked_mul(7 * 86_400)
+                    .and_then(|delay| expiry.checked_add(delay))
+                    .ok_or_else(expiry_overflow)
+            }
+        }
+    }
+}
+
+fn expiry_overflow() -> TemplateError {
+    TemplateError::InvalidExpiry("expiry is past the largest Unix timestamp".to_string())
+}
+
+/// Loads instance parameters from a CSV file with a header row, or a JSON array of objects.
+pub fn load_params(path: &Path) -> Result<Vec<TemplateParams>, TemplateError> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("csv") => {
+            let mut reader = csv::Reader::from_path(path)?;
+            reader
+                .deserialize()
+                .collect::<Result<_, _>>()
+                .map_err(TemplateError::from)
+        }
+        Some("json") => {
+            let rows: Vec<BTreeMap<String, serde_json::Value>> =
+                serde_json::from_str(&std::fs::read_to_string(path)?)?;
+            Ok(rows
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(name, value)| match value {
+                            serde_json::Value::String(value) => (name, value),
+                            other => (name, other.to_string()),
+                        })
+                        .collect()
+                })
+                .collect())
+        }
+        _ => Err(TemplateError::UnsupportedFile(path.display().to_string())),
+    }
+}
+
+/// Replaces every `{name}` in `text` with its parameter value.
+pub fn render(text: &str, params: &TemplateParams) -> Result<String, TemplateError> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| TemplateError::UnclosedPlaceholder(text.to_string()))?;
+        let name = &rest[start + 1..start + end];
+        let value = params
+            .get(name)
+            .ok_or_else(|| TemplateError::MissingParameter(name.to_string()))?;
+        rendered.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// UTC calendar date of a Unix timestamp, using Howard Hinnant's `civil_from_days`.
+fn format_date(timestamp: u64) -> String {
+    let z = (timestamp / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // Wednesday 2023-11-15 12:00:00 UTC
+    const NOW: u64 = 1_700_049_600;
+
+    fn weekly_template() -> MarketTemplate {
+        MarketTemplate {
+            question: "Will {asset} close above ${threshold} on {expiry_date}?".to_string(),
+            expiry: ExpiryRule::NextWeekday { weekday: 4, hour_utc: 20, weeks_ahead: 0 },
+            oracle_id: Address::zero(),
+            collateral_token: "USDC".to_string(),
+            metadata: MarketMetadata {
+                resolution_source: "https://www.coingecko.com/en/coins/{asset}".to_string(),
+                tags: vec!["{asset}".to_string(), "weekly".to_string()],
+                ..MarketMetadata::default()
+            },
+            salt: 0,
+            parent_condition: None,
+            grid: BTreeMap::from([
+                ("asset".to_string(), vec!["BTC".to_string(), "ETH".to_string()]),
+                ("threshold".to_string(), vec!["100".to_string(), "200".to_string(), "300".to_string()]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_grid_instances() {
+        let template = weekly_template();
+        let params = template.grid_params();
+        assert_eq!(params.len(), 6);
+
+        let spec = template.instantiate(&params[4], NOW).unwrap();
+        assert_eq!(spec.question, "Will ETH close above $200 on 2023-11-17?");
+        assert_eq!(spec.expiry_timestamp, 1_700_251_200); // Friday 20:00 UTC
+        assert_eq!(spec.metadata.resolution_source, "https://www.coingecko.com/en/coins/ETH");
+        assert_eq!(spec.metadata.tags, vec!["ETH", "weekly"]);
+    }
+
+    #[test]
+    fn test_missing_parameter() {
+        let template = weekly_template();
+        let params = TemplateParams::from([("asset".to_string(), "BTC".to_string())]);
+        assert!(matches!(
+            template.instantiate(&params, NOW),
+            Err(TemplateError::MissingParameter(name)) if name == "threshold"
+        ));
+    }
+
+    #[test]
+    fn test_invalid_expiry_rules() {
+        let params = TemplateParams::new();
+        let template = |expiry| MarketTemplate { expiry, ..weekly_template() };
+
+        let late = template(ExpiryRule::NextWeekday { weekday: 4, hour_utc: 24, weeks_ahead: 0 });
+        assert!(matches!(late.instantiate(&params, NOW), Err(TemplateError::InvalidExpiry(_))));
+
+        let far = template(ExpiryRule::NextWeekday { weekday: 4, hour_utc: 20, weeks_ahead: u64::MAX });
+        assert!(matches!(far.instantiate(&params, NOW), Err(TemplateError::InvalidExpiry(_))));
+
+        let delayed = template(ExpiryRule::AfterCreation { seconds: u64::MAX });
+        assert!(matches!(delayed.instantiate(&params, NOW), Err(TemplateError::InvalidExpiry(_))));
+    }
+
+    #[test]
+    fn test_load_csv_and_json_params() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let csv_path = dir.path().join("params.csv");
+        let mut file = std::fs::File::create(&csv_path).unwrap();
+        writeln!(file, "asset,threshold\nBTC,100000\nETH,5000").unwrap();
+        let params = load_params(&csv_path).unwrap();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[1]["asset"], "ETH");
+        assert_eq!(params[1]["threshold"], "5000");
+
+        let json_path = dir.path().join("params.json");
+        std::fs::write(&json_path, r#"[{"asset": "SOL", "threshold": 250}]"#).unwrap();
+        let params = load_params(&json_path).unwrap();
+        assert_eq!(params[0]["asset"], "SOL");
+        assert_eq!(params[0]["threshold"], "250");
+    }
+}
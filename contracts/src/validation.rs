@@ -0,0 +1,229 @@
+use crate::market::{Market, MarketError, MarketStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Configurable rules a new market must satisfy before the factory lists it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationRules {
+    pub min_time_to_expiry: u64, // Seconds
+    pub max_time_to_expiry: u64, // Seconds
+    pub min_question_length: usize,
+    pub max_question_length: usize,
+    pub banned_terms: Vec<String>, // Matched case-insensitively as whole words or phrases
+    pub allowed_collateral_tokens: Vec<String>,
+    pub require_resolution_source: bool,
+    /// Word-set similarity (0.0-1.0) at which a question counts as a near-duplicate of an
+    /// unresolved market. `None` disables duplicate detection.
+    pub duplicate_similarity: Option<f64>,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        Self {
+            min_time_to_expiry: 3_600,
+            max_time_to_expiry: 2 * 365 * 86_400,
+            min_question_length: 10,
+            max_question_length: 300,
+            banned_terms: Vec::new(),
+            allowed_collateral_tokens: vec!["USDC".to_string()],
+            require_resolution_source: true,
+            duplicate_similarity: Some(0.8),
+        }
+    }
+}
+
+/// The parts of a market creation request that are validated.
+#[derive(Debug, Clone, Copy)]
+pub struct MarketProposal<'a> {
+    pub question: &'a str,
+    pub expiry_timestamp: u64,
+    pub collateral_token: &'a str,
+    pub resolution_source: &'a str,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Violation {
+    #[error("Expiry must be at least {min_seconds}s in the future")]
+    ExpiryTooSoon { min_seconds: u64 },
+    #[error("Expiry must be at most {max_seconds}s in the future")]
+    ExpiryTooFar { max_seconds: u64 },
+    #[error("Question must be at least {min} characters")]
+    QuestionTooShort { min: usize },
+    #[error("Question must be at most {max} characters")]
+    QuestionTooLong { max: usize },
+    #[error("Question contains banned term: {term}")]
+    BannedTerm { term: String },
+    #[error("{}: {token}", MarketError::InvalidCollateralToken)]
+    InvalidCollateralToken { token: String },
+    #[error("Resolution source is required")]
+    MissingResolutionSource,
+    #[error("Question is a near-duplicate of market {market_id} ({similarity:.2} similar)")]
+    NearDuplicate { market_id: String, similarity: f64 },
+}
+
+impl ValidationRules {
+    /// Checks `proposal` against every rule and returns all violations, not just the first.
+    pub fn validate<'a>(
+        &self,
+        proposal: &MarketProposal,
+        existing: impl IntoIterator<Item = &'a Market>,
+        now: u64,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        let time_to_expiry = proposal.expiry_timestamp.saturating_sub(now);
+        if time_to_expiry < self.min_time_to_expiry {
+            violations.push(Violation::ExpiryTooSoon { min_seconds: self.min_time_to_expiry });
+        }
+        if time_to_expiry > self.max_time_to_expiry {
+            violations.push(Violation::ExpiryTooFar { max_seconds: self.max_time_to_expiry });
+        }
+
+        let length = proposal.question.trim().chars().count();
+        if length < self.min_question_length {
+            violations.push(Violation::QuestionTooShort { min: self.min_question_length });
+        }
+        if length > self.max_question_length {
+            violations.push(Violation::QuestionTooLong { max: self.max_question_length });
+        }
+
+        let normalized = format!(" {} ", normalize(proposal.question).join(" "));
+        for term in &self.banned_terms {
+            let term_words = normalize(term);
+            if !term_words.is_empty() && normalized.contains(&format!(" {} ", term_words.join(" "))) {
+                violations.push(Violation::BannedTerm { term: term.clone() });
+            }
+        }
+
+        if !self.allowed_collateral_tokens.iter().any(|token| token == proposal.collateral_token) {
+            violations.push(Violation::InvalidCollateralToken {
+                token: proposal.collateral_token.to_string(),
+            });
+        }
+
+        if self.require_resolution_source && proposal.resolution_source.trim().is_empty() {
+            violations.push(Violation::MissingResolutionSource);
+        }
+
+        if let Some(threshold) = self.duplicate_similarity {
+            for market in existing {
+                if matches!(market.status, MarketStatus::Resolved | MarketStatus::Voided) {
+                    continue;
+                }
+                let similarity = question_similarity(proposal.question, &market.question);
+                if similarity >= threshold {
+                    violations.push(Violation::NearDuplicate {
+                        market_id: market.id.clone(),
+                        similarity,
+                    });
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+/// Lowercased alphanumeric words, so punctuation and spacing don't hide duplicates.
+fn normalize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Numbers and tickers (all-caps words such as BTC), which pick out what a question is about.
+fn key_terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| {
+            word.chars().any(|c| c.is_ascii_digit())
+                || (word.len() > 1 && !word.chars().any(char::is_lowercase))
+        })
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Jaccard similarity of the two questions' word sets. Questions are never similar if one
+/// has a number or ticker the other lacks, e.g. strikes of the same ladder.
+pub fn question_similarity(a: &str, b: &str) -> f64 {
+    let a_words: HashSet<String> = normalize(a).into_iter().collect();
+    let b_words: HashSet<String> = normalize(b).into_iter().collect();
+    if key_terms(a).iter().any(|term| !b_words.contains(term))
+        || key_terms(b).iter().any(|term| !a_words.contains(term))
+    {
+        return 0.0;
+    }
+    if a_words.is_empty() && b_words.is_empty() {
+        return 1.0;
+    }
+    a_words.intersection(&b_words).count() as f64 / a_words.union(&b_words).count() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::tests::test_market;
+    use ethers::types::Address;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn market(id: &str, question: &str) -> Market {
+        Market {
+            question: question.to_string(),
+            expiry_timestamp: NOW + 86_400,
+            ..test_market(id, Address::zero())
+        }
+    }
+
+    #[test]
+    fn test_valid_proposal_passes() {
+        let proposal = MarketProposal {
+            question: "Will ETH close above $2000 on Friday?",
+            expiry_timestamp: NOW + 86_400,
+            collateral_token: "USDC",
+            resolution_source: "https://www.coingecko.com/en/coins/ethereum",
+        };
+        let existing = [market("0x1", "Will BTC close above $50000 on Friday?")];
+
+        assert!(ValidationRules::default().validate(&proposal, &existing, NOW).is_empty());
+    }
+
+    #[test]
+    fn test_all_violations_are_reported() {
+        let rules = ValidationRules {
+            banned_terms: vec!["assassination".to_string()],
+            ..ValidationRules::default()
+        };
+        let proposal = MarketProposal {
+            question: "Will the ETH price, close above $2000 on Friday? assassination",
+            expiry_timestamp: NOW + 60,
+            collateral_token: "DOGE",
+            resolution_source: " ",
+        };
+        let existing = [market("0x1", "Will the ETH price close above $2000 on Friday")];
+
+        let violations = rules.validate(&proposal, &existing, NOW);
+        assert_eq!(violations.len(), 5);
+        assert_eq!(violations[0], Violation::ExpiryTooSoon { min_seconds: 3_600 });
+        assert_eq!(violations[1], Violation::BannedTerm { term: "assassination".to_string() });
+        assert_eq!(violations[2], Violation::InvalidCollateralToken { token: "DOGE".to_string() });
+        assert_eq!(violations[3], Violation::MissingResolutionSource);
+        assert!(matches!(&violations[4], Violation::NearDuplicate { market_id, .. } if market_id == "0x1"));
+    }
+
+    #[test]
+    fn test_question_similarity_ignores_case_and_punctuation() {
+        assert_eq!(question_similarity("Will it rain?", "will IT rain"), 1.0);
+        assert!(question_similarity("Will it rain in Paris?", "Will it snow in Oslo?") < 0.5);
+    }
+
+    #[test]
+    fn test_question_similarity_distinguishes_numbers_and_tickers() {
+        let question = "Will BTC close above $100 on 2023-11-17?";
+        assert_eq!(question_similarity(question, "Will BTC close above $200 on 2023-11-17?"), 0.0);
+        assert_eq!(question_similarity(question, "Will ETH close above $100 on 2023-11-17?"), 0.0);
+        assert_eq!(question_similarity(question, "Will BTC close above $100 on 2023-11-24?"), 0.0);
+        assert_eq!(question_similarity(question, "will BTC close above $100 on 2023-11-17"), 1.0);
+    }
+}
@@ -0,0 +1,535 @@
+use async_trait::async_trait;
+use ethers::types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VenueError {
+    #[error("Transient venue error: {0}")]
+    Transient(String),
+    #[error("Rate limited by venue")]
+    RateLimited,
+    #[error("Rejected by venue: {0}")]
+    Rejected(String),
+    #[error("Venue authentication failed: {0}")]
+    Auth(String),
+    #[error("Network error: {0}")]
+    Network(String),
+    /// A non-idempotent call failed after the venue may already have acted on it. Check the
+    /// venue's state before trying again.
+    #[error("Venue outcome unknown: {0}")]
+    UnknownOutcome(String),
+    #[error("Invalid venue response: {0}")]
+    InvalidResponse(String),
+}
+
+impl VenueError {
+    /// Whether retrying could succeed. Server and network failures are only safe to retry
+    /// for idempotent calls, because the venue may have acted on a request whose answer we lost.
+    pub fn is_retryable(&self, idempotent: bool) -> bool {
+        match self {
+            VenueError::RateLimited => true,
+            VenueError::Transient(_) | VenueError::Network(_) => idempotent,
+            VenueError::Rejected(_)
+            | VenueError::Auth(_)
+            | VenueError::UnknownOutcome(_)
+            | VenueError::InvalidResponse(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
+/// A limit order on an outcome token. Prices are in cents of collateral per token.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderRequest {
+    pub owner: Address,
+    pub token: String,
+    pub side: OrderSide,
+    pub price: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacedOrder {
+    pub order_id: u64,
+    pub filled_size: u64,
+    pub resting_size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VenueFill {
+    pub order_id: u64,
+    pub token: String,
+    pub side: OrderSide,
+    pub price: u64,
+    pub size: u64,
+    pub timestamp: u64,
+}
+
+/// The trading venue that holds outcome tokens and collateral.
+///
+/// `HyperliquidClient` talks to the real exchange; `SimulatedVenue` keeps everything in
+/// memory for tests and offline development.
+#[async_trait]
+pub trait VenueClient: Send + Sync + std::fmt::Debug {
+    /// Returns the YES and NO tokens of `market_id`, creating them if needed. Must be
+    /// idempotent: a listing that fails after this call is retried under the same market id.
+    async fn create_market_pair(
+        &self,
+        market_id: &str,
+        collateral_token: &str,
+    ) -> Result<(String, String), VenueError>;
+
+    /// Moves collateral from `owner`'s wallet into `custody`, the escrow account backing a
+    /// market's collateral ledger. Markets only credit deposits once this succeeds.
+    async fn deposit_collateral(
+        &self,
+        owner: Address,
+        custody: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError>;
+
+    /// Pays collateral out of `custody` back to `owner`'s wallet.
+    async fn withdraw_collateral(
+        &self,
+        custody: Address,
+        owner: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError>;
+
+    /// Moves collateral between two accounts on the venue, e.g. fees into the treasury.
+    async fn transfer_collateral(
+        &self,
+        from: Address,
+        to: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError>;
+
+    async fn place_order(&self, order: OrderRequest) -> Result<PlacedOrder, VenueError>;
+
+    async fn cancel_order(&self, owner: Address, token: &str, order_id: u64) -> Result<(), VenueError>;
+
+    async fn balance(&self, owner: Address, token: &str) -> Result<U256, VenueError>;
+
+    async fn fills(&self, owner: Address) -> Result<Vec<VenueFill>, VenueError>;
+}
+
+/// The deterministic id of a market's YES or NO token, which every deployment can
+/// recompute from the market id. The simulated venue names its tokens by it.
+pub fn outcome_token_id(market_id: &str, is_yes: bool) -> String {
+    let mut hasher = Keccak256::new();
+    hasher.update(market_id.as_bytes());
+    hasher.update(if is_yes { b"YES\0\0" } else { b"NO\0\0\0" });
+    format!("0x{}", hex::encode(hasher.finalize()))
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedOrder {
+    order_id: u64,
+    owner: Address,
+    token: String,
+    side: OrderSide,
+    price: u64,
+    remaining: u64,
+}
+
+#[derive(Debug, Default)]
+struct SimulatedState {
+    collateral_tokens: HashMap<String, String>,
+    balances: HashMap<(Address, String), U256>,
+    resting_orders: Vec<SimulatedOrder>,
+    fills: Vec<(Address, VenueFill)>,
+    next_order_id: u64,
+    next_action: u64,
+}
+
+impl SimulatedState {
+    fn next_tx_hash(&mut self) -> H256 {
+        self.next_action += 1;
+        H256::from_low_u64_be(self.next_action)
+    }
+
+    fn credit(&mut self, owner: Address, token: &str, amount: U256) {
+        *self.balances.entry((owner, token.to_string())).or_default() += amount;
+    }
+
+    fn debit(&mut self, owner: Address, token: &str, amount: U256) -> Result<(), VenueError> {
+        let balance = self.balances.entry((owner, token.to_string())).or_default();
+        *balance = balance
+            .checked_sub(amount)
+            .ok_or_else(|| VenueError::Rejected(format!("Insufficient {} balance", token)))?;
+        Ok(())
+    }
+
+    /// The token and amount a resting order holds back: a sell's tokens, or the collateral
+    /// a buy pays at its limit price, rounded up.
+    fn reservation(&self, token: &str, side: OrderSide, price: u64, size: u64) -> Option<(String, U256)> {
+        match side {
+            OrderSide::Sell => Some((token.to_string(), U256::from(size))),
+            OrderSide::Buy => self
+                .collateral_tokens
+                .get(token)
+                .map(|collateral| {
+                    let cost = U256::from(price) * U256::from(size);
+                    (collateral.clone(), (cost + U256::from(99)) / U256::from(100))
+                }),
+        }
+    }
+
+    /// `owner`'s balance of `token` less what their resting orders hold back.
+    fn available(&self, owner: Address, token: &str) -> U256 {
+        let balance = self.balances.get(&(owner, token.to_string())).copied().unwrap_or_default();
+        let reserved = self
+            .resting_orders
+            .iter()
+            .filter(|order| order.owner == owner)
+            .filter_map(|order| self.reservation(&order.token, order.side, order.price, order.remaining))
+            .filter(|(reserved_token, _)| reserved_token == token)
+            .fold(U256::zero(), |total, (_, amount)| total + amount);
+        balance.saturating_sub(reserved)
+    }
+
+    /// Moves `amount` of `token` that `from` has not reserved for resting orders to `to`.
+    fn transfer(&mut self, from: Address, to: Address, token: &str, amount: U256) -> Result<H256, VenueError> {
+        if self.available(from, token) < amount {
+            return Err(VenueError::Rejected(format!("Insufficient {} balance", token)));
+        }
+        self.debit(from, token, amount)?;
+        self.credit(to, token, amount);
+        Ok(self.next_tx_hash())
+    }
+
+    fn settle(&mut self, buyer: Address, seller: Address, token: &str, price: u64, size: u64) -> Result<(), VenueError> {
+        self.debit(seller, token, U256::from(size))?;
+        self.credit(buyer, token, U256::from(size));
+        if let Some(collateral) = self.collateral_tokens.get(token).cloned() {
+            let notional = notional(price, size);
+            self.debit(buyer, &collateral, notional)?;
+            self.credit(seller, &collateral, notional);
+        }
+        Ok(())
+    }
+}
+
+fn notional(price: u64, size: u64) -> U256 {
+    U256::from(price) * U256::from(size) / U256::from(100)
+}
+
+/// An in-memory venue with price-time priority matching per token.
+///
+/// Clones share state, so one instance can be handed to the factory and to every market
+/// contract. Wallets are funded with `mint`; deposits and withdrawals move collateral
+/// between a wallet and the custody account, and fills move token and collateral balances
+/// between the two counterparties. Orders must be funded when placed: resting
+/// orders hold back their tokens or collateral until they fill or are cancelled.
+#[derive(Debug, Default, Clone)]
+pub struct SimulatedVenue {
+    state: Arc<Mutex<SimulatedState>>,
+}
+
+impl SimulatedVenue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Credits `amount` of `token` to `owner`'s wallet, as if bridged in from outside the venue.
+    pub fn mint(&self, owner: Address, token: &str, amount: U256) {
+        self.state.lock().unwrap().credit(owner, token, amount);
+    }
+}
+
+#[async_trait]
+impl VenueClient for SimulatedVenue {
+    async fn create_market_pair(
+        &self,
+        market_id: &str,
+        collateral_token: &str,
+    ) -> Result<(String, String), VenueError> {
+        let yes_token = outcome_token_id(market_id, true);
+        let no_token = outcome_token_id(market_id, false);
+
+        let mut state = self.state.lock().unwrap();
+        match state.collateral_tokens.get(&yes_token) {
+            Some(existing) if existing == collateral_token => return Ok((yes_token, no_token)),
+            Some(existing) => {
+                return Err(VenueError::Rejected(format!(
+                    "Token pair for {} already exists with collateral {}",
+                    market_id, existing
+                )))
+            }
+            None => {}
+        }
+        state.collateral_tokens.insert(yes_token.clone(), collateral_token.to_string());
+        state.collateral_tokens.insert(no_token.clone(), collateral_token.to_string());
+
+        Ok((yes_token, no_token))
+    }
+
+    async fn deposit_collateral(
+        &self,
+        owner: Address,
+        custody: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError> {
+        self.state.lock().unwrap().transfer(owner, custody, token, amount)
+    }
+
+    async fn withdraw_collateral(
+        &self,
+        custody: Address,
+        owner: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError> {
+        self.state.lock().unwrap().transfer(custody, owner, token, amount)
+    }
+
+    async fn transfer_collateral(
+        &self,
+        from: Address,
+        to: Address,
+        token: &str,
+        amount: U256,
+    ) -> Result<H256, VenueError> {
+        self.state.lock().unwrap().transfer(from, to, token, amount)
+    }
+
+    async fn place_order(&self, order: OrderRequest) -> Result<PlacedOrder, VenueError> {
+        if order.size == 0 || order.price == 0 || order.price >= 100 {
+            return Err(VenueError::Rejected("Invalid order".to_string()));
+        }
+
+        let mut state = self.state.lock().unwrap();
+        // Buys pay at most their limit price, so reserving the whole order covers every fill.
+        if let Some((token, required)) = state.reservation(&order.token, order.side, order.price, order.size) {
+            if state.available(order.owner, &token) < required {
+                return Err(VenueError::Rejected(format!("Insufficient {} balance", token)));
+            }
+        }
+        state.next_order_id += 1;
+        let order_id = state.next_order_id;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut remaining = order.size;
+        while remaining > 0 {
+            // Best opposite price first, then oldest.
+            let best = state
+                .resting_orders
+                .iter()
+                .enumerate()
+                .filter(|(_, resting)| {
+                    resting.token == order.token
+                        && resting.side == order.side.opposite()
+                        && match order.side {
+                            OrderSide::Buy => resting.price <= order.price,
+                            OrderSide::Sell => resting.price >= order.price,
+                        }
+                })
+                .min_by_key(|(_, resting)| match order.side {
+                    OrderSide::Buy => (resting.price, resting.order_id),
+                    OrderSide::Sell => (100 - resting.price, resting.order_id),
+                })
+                .map(|(index, _)| index);
+            let Some(index) = best else {
+                break;
+            };
+
+            let maker = state.resting_orders[index].clone();
+            let size = remaining.min(maker.remaining);
+            remaining -= size;
+            if size == maker.remaining {
+                state.resting_orders.remove(index);
+            } else {
+                state.resting_orders[index].remaining -= size;
+            }
+
+            let (buyer, seller) = match order.side {
+                OrderSide::Buy => (order.owner, maker.owner),
+                OrderSide::Sell => (maker.owner, order.owner),
+            };
+            state.settle(buyer, seller, &order.token, maker.price, size)?;
+            state.fills.push((maker.owner, VenueFill {
+                order_id: maker.order_id,
+                token: order.token.clone(),
+                side: maker.side,
+                price: maker.price,
+                size,
+                timestamp,
+            }));
+            state.fills.push((order.owner, VenueFill {
+                order_id,
+                token: order.token.clone(),
+                side: order.side,
+                price: maker.price,
+                size,
+                timestamp,
+            }));
+        }
+
+        if remaining > 0 {
+            state.resting_orders.push(SimulatedOrder {
+                order_id,
+                owner: order.owner,
+                token: order.token.clone(),
+                side: order.side,
+                price: order.price,
+                remaining,
+            });
+        }
+
+        Ok(PlacedOrder {
+            order_id,
+            filled_size: order.size - remaining,
+            resting_size: remaining,
+        })
+    }
+
+    async fn cancel_order(&self, owner: Address, token: &str, order_id: u64) -> Result<(), VenueError> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .resting_orders
+            .iter()
+            .position(|o| o.order_id == order_id && o.owner == owner && o.token == token)
+            .ok_or_else(|| VenueError::Rejected(format!("Order {} not found", order_id)))?;
+        state.resting_orders.remove(index);
+        Ok(())
+    }
+
+    async fn balance(&self, owner: Address, token: &str) -> Result<U256, VenueError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .balances
+            .get(&(owner, token.to_string()))
+            .copied()
+            .unwrap_or_default())
+    }
+
+    async fn fills(&self, owner: Address) -> Result<Vec<VenueFill>, VenueError> {
+        Ok(self
+            .state
+            .lock()
+            .unwrap()
+            .fills
+            .iter()
+            .filter(|(fill_owner, _)| *fill_owner == owner)
+            .map(|(_, fill)| fill.clone())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_simulated_deposit_and_withdraw() {
+        let venue = SimulatedVenue::new();
+        let user = Address::random();
+        let custody = Address::random();
+
+        // Deposits come out of the user's wallet, not out of nothing
+        assert!(venue.deposit_collateral(user, custody, "USDC", U256::from(1)).await.is_err());
+        venue.mint(user, "USDC", U256::from(1000));
+
+        venue.deposit_collateral(user, custody, "USDC", U256::from(1000)).await.unwrap();
+        assert!(venue.balance(user, "USDC").await.unwrap().is_zero());
+        venue.withdraw_collateral(custody, user, "USDC", U256::from(400)).await.unwrap();
+        assert_eq!(venue.balance(user, "USDC").await.unwrap(), U256::from(400));
+        assert_eq!(venue.balance(custody, "USDC").await.unwrap(), U256::from(600));
+
+        let result = venue.withdraw_collateral(custody, user, "USDC", U256::from(601)).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_matching_moves_balances() {
+        let venue = SimulatedVenue::new();
+        let (yes_token, _) = venue.create_market_pair("MARKET", "USDC").await.unwrap();
+        let maker = Address::random();
+        let taker = Address::random();
+        venue.mint(maker, &yes_token, U256::from(100));
+        venue.mint(taker, "USDC", U256::from(1000));
+
+        let resting = venue.place_order(OrderRequest {
+            owner: maker,
+            token: yes_token.clone(),
+            side: OrderSide::Sell,
+            price: 60,
+            size: 50,
+        }).await.unwrap();
+        assert_eq!(resting.resting_size, 50);
+
+        let taken = venue.place_order(OrderRequest {
+            owner: taker,
+            token: yes_token.clone(),
+            side: OrderSide::Buy,
+            price: 65,
+            size: 80,
+        }).await.unwrap();
+        assert_eq!(taken.filled_size, 50);
+        assert_eq!(taken.resting_size, 30);
+
+        assert_eq!(venue.balance(taker, &yes_token).await.unwrap(), U256::from(50));
+        assert_eq!(venue.balance(taker, "USDC").await.unwrap(), U256::from(970));
+        assert_eq!(venue.balance(maker, "USDC").await.unwrap(), U256::from(30));
+
+        let maker_fills = venue.fills(maker).await.unwrap();
+        assert_eq!(maker_fills.len(), 1);
+        assert_eq!(maker_fills[0].price, 60);
+
+        venue.cancel_order(taker, &yes_token, taken.order_id).await.unwrap();
+        assert!(venue.cancel_order(taker, &yes_token, taken.order_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulated_orders_must_be_funded() {
+        let venue = SimulatedVenue::new();
+        let (yes_token, _) = venue.create_market_pair("MARKET", "USDC").await.unwrap();
+        let user = Address::random();
+        let order = |side, size| OrderRequest {
+            owner: user,
+            token: yes_token.clone(),
+            side,
+            price: 50,
+            size,
+        };
+
+        assert!(venue.place_order(order(OrderSide::Sell, 1)).await.is_err());
+        venue.mint(user, "USDC", U256::from(30));
+        assert!(venue.place_order(order(OrderSide::Buy, 61)).await.is_err()); // 30.5 rounds up
+        venue.place_order(order(OrderSide::Buy, 60)).await.unwrap();
+
+        // The resting buy holds back all of it
+        assert!(venue.place_order(order(OrderSide::Buy, 1)).await.is_err());
+        let withdrawal = venue.transfer_collateral(user, Address::random(), "USDC", U256::from(1)).await;
+        assert!(withdrawal.is_err());
+    }
+}
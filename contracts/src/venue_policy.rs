@@ -0,0 +1,246 @@
+use crate::venue::VenueError;
+use rand::Rng;
+use std::{future::Future, time::Duration};
+use tokio::{sync::Mutex, time::Instant};
+
+/// Hyperliquid allows 1200 weight per minute per IP address.
+pub const HYPERLIQUID_WEIGHT_PER_MINUTE: u32 = 1200;
+
+/// Request weights from Hyperliquid's rate limit documentation.
+pub mod weights {
+    /// Any exchange action (order, cancel, transfer) with a single element.
+    pub const EXCHANGE_ACTION: u32 = 1;
+    /// Light info requests such as `l2Book` and `spotClearinghouseState`.
+    pub const LIGHT_INFO: u32 = 2;
+    /// All other info requests, including `userFills` and `spotMeta`.
+    pub const INFO: u32 = 20;
+}
+
+#[derive(Debug, Clone)]
+pub struct VenuePolicyConfig {
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    pub weight_per_minute: u32,
+}
+
+impl Default for VenuePolicyConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_retries: 3,
+            base_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            weight_per_minute: HYPERLIQUID_WEIGHT_PER_MINUTE,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Client-side token bucket so we throttle ourselves before the venue does.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl TokenBucket {
+    pub fn per_minute(capacity: u32) -> Self {
+        Self {
+            capacity: capacity as f64,
+            refill_per_second: capacity as f64 / 60.0,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Waits until `weight` tokens are available and takes them.
+    pub async fn acquire(&self, weight: u32) {
+        let weight = (weight as f64).min(self.capacity);
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+                bucket.refilled_at = now;
+
+                if bucket.tokens >= weight {
+                    bucket.tokens -= weight;
+                    return;
+                }
+                Duration::from_secs_f64((weight - bucket.tokens) / self.refill_per_second)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Timeout, retry and rate limit policy applied to every venue call.
+#[derive(Debug)]
+pub struct VenuePolicy {
+    config: VenuePolicyConfig,
+    rate_limiter: TokenBucket,
+}
+
+impl Default for VenuePolicy {
+    fn default() -> Self {
+        Self::new(VenuePolicyConfig::default())
+    }
+}
+
+impl VenuePolicy {
+    pub fn new(config: VenuePolicyConfig) -> Self {
+        Self {
+            rate_limiter: TokenBucket::per_minute(config.weight_per_minute),
+            config,
+        }
+    }
+
+    /// Runs `operation` under the policy.
+    ///
+    /// Rate-limited failures are retried with jittered exponential backoff. Server and
+    /// network failures, including timeouts, are only retried when `idempotent` is set,
+    /// since a request that never got a proper answer may still have been executed; for
+    /// other calls they come back as `VenueError::UnknownOutcome`.
+    pub async fn execute<T, F, Fut>(
+        &self,
+        weight: u32,
+        idempotent: bool,
+        mut operation: F,
+    ) -> Result<T, VenueError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, VenueError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            self.rate_limiter.acquire(weight).await;
+
+            let result = match tokio::time::timeout(self.config.timeout, operation()).await {
+                Ok(result) => result,
+                Err(_) => Err(VenueError::Network(format!(
+                    "request timed out after {:?}",
+                    self.config.timeout
+                ))),
+            };
+
+            match result {
+                Err(e) if attempt < self.config.max_retries && e.is_retryable(idempotent) => {
+                    log::warn!("Venue call failed (attempt {}): {}", attempt + 1, e);
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e @ (VenueError::Transient(_) | VenueError::Network(_))) if !idempotent => {
+                    return Err(VenueError::UnknownOutcome(e.to_string()));
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Equal-jitter backoff: half the exponential delay plus a random share of the other half.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .config
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_backoff);
+        let half = exponential / 2;
+        half + half.mul_f64(rand::thread_rng().gen::<f64>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy() -> VenuePolicy {
+        VenuePolicy::new(VenuePolicyConfig {
+            timeout: Duration::from_millis(50),
+            max_retries: 3,
+            base_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(4),
+            weight_per_minute: HYPERLIQUID_WEIGHT_PER_MINUTE,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let policy = fast_policy();
+        let attempts = AtomicU32::new(0);
+        let busy = || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(VenueError::Transient("busy".to_string()))
+            } else {
+                Ok(7)
+            }
+        };
+
+        let result = policy.execute(weights::INFO, true, busy).await;
+        assert_eq!(result, Ok(7));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // A server error on an order may have been executed, so it is not resent
+        attempts.store(0, Ordering::SeqCst);
+        let result = policy.execute(weights::EXCHANGE_ACTION, false, busy).await;
+        assert!(matches!(result, Err(VenueError::UnknownOutcome(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejections_are_not_retried() {
+        let policy = fast_policy();
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = policy
+            .execute(weights::EXCHANGE_ACTION, true, || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(VenueError::Rejected("post only would cross".to_string()))
+            })
+            .await;
+
+        assert!(matches!(result, Err(VenueError::Rejected(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_only_retried_when_idempotent() {
+        let policy = fast_policy();
+        let attempts = AtomicU32::new(0);
+        let slow = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(())
+        };
+
+        let result = policy.execute(weights::EXCHANGE_ACTION, false, slow).await;
+        assert!(matches!(result, Err(VenueError::UnknownOutcome(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let result = policy.execute(weights::INFO, true, slow).await;
+        assert!(matches!(result, Err(VenueError::Network(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1 + 4);
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket_throttles_when_exhausted() {
+        // 600 weight per minute refills 10 per second.
+        let bucket = TokenBucket::per_minute(600);
+        bucket.acquire(600).await;
+
+        let started = std::time::Instant::now();
+        bucket.acquire(1).await;
+        assert!(started.elapsed() >= Duration::from_millis(80));
+    }
+}
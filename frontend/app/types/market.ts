@@ -2,12 +2,31 @@ export enum MarketStatus {
   Active = "Active",
   Expired = "Expired",
   Resolved = "Resolved",
+  Locked = "Locked",
+  Voided = "Voided",
 }
 
 export interface Market {
   id: string;
   question: string;
   description: string;
+  rules?: string;
+  resolutionSource?: string;
+  resolutionSourceUrls?: string[];
+  category?: string;
+  tags?: string[];
+  imageUrl?: string;
+  groupId?: string;
+  parentMarketId?: string;
+  parentRequiredOutcome?: boolean;
+  paused?: boolean;
+  haltedUntil?: number;
+  tradingCloseTimestamp?: number;
+  resolutionTimestamp?: number;
+  proposedOutcome?: boolean;
+  eventTimestamp?: number;
+  batchAuctionInterval?: number; // Set while orders clear in batches instead of continuously
+  batchAuctionEndsAt?: number;
   status: MarketStatus;
   expiryTimestamp: number;
   oracleId: string;
@@ -54,6 +54,12 @@ export const useMarket = (marketId: string) => {
     price,
     amount,
     orderType,
+    timeInForce,
+    expiresAt,
+    postOnly,
+    reduceOnly,
+    stopPrice,
+    maxSlippage,
   }: PlaceOrderParams) => {
     if (!provider || !account) {
       throw new Error("Wallet not connected");
@@ -85,6 +91,12 @@ export const useMarket = (marketId: string) => {
           price,
           amount,
           order_type: orderType,
+          time_in_force: timeInForce,
+          expires_at: expiresAt,
+          post_only: postOnly ?? false,
+          reduce_only: reduceOnly ?? false,
+          stop_price: stopPrice?.toString(),
+          max_slippage: maxSlippage?.toString(),
           signature,
           message,
         }),
@@ -15,6 +15,7 @@ export type Market = {
 };
 
 export type OrderType = "limit" | "market";
+export type TimeInForce = "GTC" | "IOC" | "FOK" | "GTT";
 export type Side = "buy" | "sell";
 
 export type Order = {
@@ -25,7 +26,7 @@ export type Order = {
   price: number;
   amount: number;
   filled_amount: number;
-  status: "open" | "filled" | "cancelled";
+  status: "open" | "filled" | "cancelled" | "pending" | "expired";
   created_at: string;
   updated_at: string;
   tx_hash?: string;
@@ -81,4 +82,10 @@ export type PlaceOrderParams = {
   price: number;
   amount: number;
   orderType: OrderType;
+  timeInForce?: TimeInForce;
+  expiresAt?: number; // Required for GTT
+  postOnly?: boolean;
+  reduceOnly?: boolean;
+  stopPrice?: number; // Stop or stop-limit trigger on the last trade price
+  maxSlippage?: number; // Market orders, in cents from the best price
 };